@@ -179,7 +179,7 @@ impl Embed for OpenAIEmbed {
             .try_buffered(10)
             .map_ok(|(embeddings, values)| {
                 let x: Vec<Vec<f32>> = embeddings.data.into_iter().map(|e| e.embedding).collect();
-                x.into_iter().zip(values.into_iter()).collect()
+                x.into_iter().zip(values).collect()
             })
     }
 }
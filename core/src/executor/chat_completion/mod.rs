@@ -31,8 +31,11 @@ use tracing_futures::Instrument;
 use uuid::Uuid;
 
 use super::context::ExecutorContext;
-use super::{get_key_credentials, use_langdb_proxy};
-use crate::executor::chat_completion::stream_wrapper::ChatCompletionStream;
+use super::{get_key_credentials, use_langdb_proxy, ProvidersConfig};
+use crate::executor::chat_completion::stream_wrapper::{wrap_stream, ChatCompletionStream};
+use crate::types::credentials::Credentials;
+use crate::types::provider::InferenceModelProvider;
+use futures::TryStreamExt;
 
 pub mod basic_executor;
 pub mod routed_executor;
@@ -138,6 +141,17 @@ pub async fn execute<T: Serialize + DeserializeOwned + Debug + Clone>(
             &user.to_string(),
         )?);
     }
+
+    let rate_limit_reporter = RateLimitReporter {
+        providers_config: executor_context.providers_config.clone(),
+        provider_name: resolved_model_context
+            .llm_model
+            .inference_provider
+            .provider
+            .to_string(),
+        credentials: resolved_model_context.credentials.clone(),
+    };
+
     let ch = executor_context.callbackhandler.clone();
     let db_model = resolved_model_context.db_model.clone();
     let handle = tokio::spawn(async move {
@@ -206,19 +220,25 @@ pub async fn execute<T: Serialize + DeserializeOwned + Debug + Clone>(
         .and_then(|e| e.variables.clone())
         .unwrap_or_default();
     if is_stream {
-        Ok(Left(
-            stream_chunks(
-                resolved_model_context.completion_model_definition,
-                resolved_model_context.model_instance,
-                messages.clone(),
-                executor_context.callbackhandler.clone().into(),
-                executor_context.tags.clone(),
-                input_vars,
-                stream_cache_context,
-            )
-            .instrument(span)
-            .await,
-        ))
+        let result = stream_chunks(
+            resolved_model_context.completion_model_definition,
+            resolved_model_context.model_instance,
+            messages.clone(),
+            executor_context.callbackhandler.clone().into(),
+            executor_context.tags.clone(),
+            input_vars,
+            stream_cache_context,
+        )
+        .instrument(span)
+        .await;
+
+        if let Err(e) = &result {
+            rate_limit_reporter.report(e);
+        }
+        let result = result
+            .map(|stream| wrap_stream(stream.inspect_err(move |e| rate_limit_reporter.report(e))));
+
+        Ok(Left(result))
     } else {
         let result = basic_executor::execute(
             request,
@@ -235,6 +255,10 @@ pub async fn execute<T: Serialize + DeserializeOwned + Debug + Clone>(
         .instrument(span)
         .await;
 
+        if let Err(e) = &result {
+            rate_limit_reporter.report(e);
+        }
+
         // if let Ok(completion_response) = &result {
         //     let ChatCompletionResponse { choices, .. } = completion_response;
         //     for choice in choices {
@@ -267,20 +291,32 @@ pub async fn resolve_model_instance<T: Serialize + DeserializeOwned + Debug + Cl
     cache_state: Option<ResponseCacheState>,
     llm_model: &ModelMetadata,
 ) -> Result<ResolvedModelContext, GatewayApiError> {
-    let (key_credentials, llm_model) = use_langdb_proxy(executor_context, llm_model.clone());
+    let (key_credentials, mut llm_model) = use_langdb_proxy(executor_context, llm_model.clone());
 
     let key = get_key_credentials(
         key_credentials.as_ref(),
         executor_context.providers_config.as_ref(),
         &llm_model.inference_provider.provider.to_string(),
     );
-    let provider_specific = request.provider_specific.clone();
-    let execution_options = request
-        .max_retries
-        .map(|retries| ExecutionOptions {
-            max_retries: Some(retries),
+    if let (
+        InferenceModelProvider::Proxy(_),
+        Some(Credentials::ApiKeyWithEndpoint { endpoint, .. }),
+    ) = (&llm_model.inference_provider.provider, &key)
+    {
+        // Endpoint configured next to the key overrides the provider's default base URL
+        llm_model.inference_provider.endpoint = Some(endpoint.clone());
+    }
+    let client_options = key.as_ref().and_then(|key| {
+        executor_context.providers_config.as_ref().and_then(|p| {
+            p.get_client_options(&llm_model.inference_provider.provider.to_string(), key)
         })
-        .unwrap_or_default();
+    });
+    let provider_specific = request.provider_specific.clone();
+    let execution_options = ExecutionOptions {
+        max_retries: request.max_retries,
+        images: executor_context.images.clone(),
+        tool_loop: request.tool_loop.clone(),
+    };
 
     let request = request.request.clone();
 
@@ -288,6 +324,7 @@ pub async fn resolve_model_instance<T: Serialize + DeserializeOwned + Debug + Cl
         &llm_model,
         &request,
         key.clone(),
+        client_options,
         provider_specific.as_ref(),
         Some(execution_options.clone()),
     )?;
@@ -339,6 +376,7 @@ pub async fn resolve_model_instance<T: Serialize + DeserializeOwned + Debug + Cl
         model_instance,
         db_model,
         llm_model,
+        credentials: key,
     })
 }
 
@@ -347,4 +385,34 @@ pub struct ResolvedModelContext {
     pub model_instance: Box<dyn ModelInstance>,
     pub db_model: Model,
     pub llm_model: ModelMetadata,
+    pub credentials: Option<Credentials>,
+}
+
+/// Feeds provider rate limit errors back to the key pool the credentials were taken from.
+struct RateLimitReporter {
+    providers_config: Option<ProvidersConfig>,
+    provider_name: String,
+    credentials: Option<Credentials>,
+}
+
+impl RateLimitReporter {
+    fn report(&self, error: &GatewayApiError) {
+        let Some(rate_limit) = error.rate_limit() else {
+            return;
+        };
+
+        if let (Some(providers_config), Some(credentials)) =
+            (&self.providers_config, &self.credentials)
+        {
+            tracing::warn!(
+                "Provider {} rate limited the request, throttling its key",
+                self.provider_name
+            );
+            providers_config.report_rate_limited(
+                &self.provider_name,
+                credentials,
+                rate_limit.retry_after,
+            );
+        }
+    }
 }
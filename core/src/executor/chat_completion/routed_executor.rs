@@ -46,6 +46,9 @@ pub enum RoutedExecutorError {
 
     #[error("Failed serializing merged request with target: {0}")]
     FailedToSerializeMergedRequestResult(serde_json::Error),
+
+    #[error("Failed applying virtual model defaults: {0}")]
+    FailedToApplyVirtualModel(serde_json::Error),
 }
 
 pub struct RoutedExecutor {
@@ -76,11 +79,46 @@ impl RoutedExecutor {
                 )));
             }
 
+            let parent_model = request.request.model.clone();
+            let is_router_target = target.is_some();
             if let Some(t) = target {
                 request.router = None;
                 request = Self::merge_request_with_target(&request, &t)?;
             }
 
+            // Router targets that keep the parent model already carry its virtual model defaults
+            if !is_router_target || request.request.model != parent_model {
+                if let Some(virtual_model) = executor_context
+                    .model_metadata_factory
+                    .get_virtual_model(&request.request.model)
+                {
+                    let model_name = request.request.model.clone();
+                    request = virtual_model
+                        .apply(request)
+                        .map_err(RoutedExecutorError::FailedToApplyVirtualModel)?;
+
+                    // The target may itself be an alias or a virtual model
+                    if request.request.model != model_name {
+                        targets.push((request, None));
+                        continue;
+                    }
+                }
+            }
+
+            // Wildcard provider fans out to every provider serving the model
+            if request.router.is_none() && request.request.model.starts_with("*/") {
+                let models = executor_context
+                    .model_metadata_factory
+                    .get_models_by_name(&request.request.model, project_id)
+                    .await?;
+                for model in models.iter().rev() {
+                    let mut request = request.clone();
+                    request.request.model = model.qualified_model_name();
+                    targets.push((request, None));
+                }
+                continue;
+            }
+
             if let Some(router) = &request.router {
                 let router_name = request
                     .request
@@ -110,6 +110,8 @@ pub async fn stream_chunks(
                 |e| Some(Err(e)),
                 |model_event| match model_event.event {
                     ModelEventType::LlmContent(_)
+                    | ModelEventType::LlmReasoning(_)
+                    | ModelEventType::LlmMetadata(_)
                     | ModelEventType::ToolStart(_)
                     | ModelEventType::LlmStop(_) => Some(Ok(model_event)),
                     _ => None,
@@ -123,7 +125,27 @@ pub async fn stream_chunks(
                         Some(ChatCompletionDelta {
                             role: Some("assistant".to_string()),
                             content: Some(content.content),
-                            tool_calls: None,
+                            ..Default::default()
+                        }),
+                        None,
+                        None,
+                    )),
+                    ModelEventType::LlmReasoning(reasoning) => Ok((
+                        Some(ChatCompletionDelta {
+                            role: Some("assistant".to_string()),
+                            reasoning_content: Some(reasoning.content),
+                            reasoning_signature: reasoning.signature,
+                            ..Default::default()
+                        }),
+                        None,
+                        None,
+                    )),
+                    ModelEventType::LlmMetadata(metadata) => Ok((
+                        Some(ChatCompletionDelta {
+                            role: Some("assistant".to_string()),
+                            safety_ratings: metadata.safety_ratings,
+                            grounding_metadata: metadata.grounding_metadata,
+                            ..Default::default()
                         }),
                         None,
                         None,
@@ -141,6 +163,7 @@ pub async fn stream_chunks(
                                     arguments: tool_call.input.clone(),
                                 },
                             }]),
+                            ..Default::default()
                         }),
                         None,
                         None,
@@ -170,6 +193,7 @@ pub async fn stream_chunks(
                                         })
                                         .collect(),
                                 ),
+                                ..Default::default()
                             }),
                             _ => None,
                         };
@@ -1,3 +1,4 @@
+use crate::model::images::ImageOptions;
 use crate::model::ModelMetadataFactory;
 use crate::routing::interceptor::rate_limiter::RateLimiterService;
 use crate::types::guardrails::service::GuardrailsEvaluator;
@@ -21,6 +22,7 @@ pub struct ExecutorContext {
     pub metadata: HashMap<String, serde_json::Value>,
     pub key_credentials: Option<Credentials>,
     pub providers_config: Option<ProvidersConfig>,
+    pub images: Option<ImageOptions>,
     pub evaluator_service: Arc<Box<dyn GuardrailsEvaluator>>,
     pub model_metadata_factory: Arc<Box<dyn ModelMetadataFactory>>,
     pub rate_limiter_service: Arc<dyn RateLimiterService>,
@@ -43,7 +45,8 @@ impl ExecutorContext {
         let tags = extract_tags(req)?;
 
         let key_credentials = req.extensions().get::<Credentials>().cloned();
-        let providers_config = req.app_data::<ProvidersConfig>().cloned();
+        let providers_config = ProvidersConfig::from_request(req);
+        let images = req.app_data::<ImageOptions>().cloned();
 
         Ok(Self {
             callbackhandler,
@@ -53,6 +56,7 @@ impl ExecutorContext {
             key_credentials,
             metadata,
             providers_config,
+            images,
             evaluator_service,
             rate_limiter_service,
         })
@@ -10,9 +10,8 @@ use crate::model::CredentialsIdent;
 use crate::models::ModelMetadata;
 use crate::types::embed::EmbeddingResult;
 use crate::types::engine::EmbeddingsModelDefinition;
-use crate::types::gateway::CreateEmbeddingRequest;
+use crate::types::gateway::{CreateEmbeddingRequest, EncodingFormat, Input};
 use crate::types::provider::InferenceModelProvider;
-use crate::GatewayError;
 use crate::{
     model::types::ModelEventType,
     types::{
@@ -21,10 +20,11 @@ use crate::{
         gateway::CostCalculator,
     },
 };
-use actix_web::HttpRequest;
+use crate::{GatewayApiError, GatewayError};
 use tracing::Span;
 use tracing_futures::Instrument;
 
+use super::context::ExecutorContext;
 use super::get_key_credentials;
 use super::ProvidersConfig;
 
@@ -35,12 +35,11 @@ pub async fn handle_embeddings(
     key_credentials: Option<&Credentials>,
     cost_calculator: Arc<Box<dyn CostCalculator>>,
     tags: HashMap<String, String>,
-    req: HttpRequest,
+    providers_config: Option<ProvidersConfig>,
 ) -> Result<EmbeddingResult, GatewayError> {
     let span = Span::current();
     request.model = llm_model.inference_provider.model_name.clone();
 
-    let providers_config = req.app_data::<ProvidersConfig>().cloned();
     let key = get_key_credentials(
         key_credentials,
         providers_config.as_ref(),
@@ -110,3 +109,51 @@ pub async fn handle_embeddings(
 
     Ok(result)
 }
+
+/// Embeds `texts` with the model, returning the vectors in the order of the texts.
+pub async fn embed_texts(
+    executor_context: &ExecutorContext,
+    model_name: &str,
+    texts: Vec<String>,
+) -> Result<Vec<Vec<f32>>, GatewayApiError> {
+    if texts.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let llm_model = executor_context
+        .model_metadata_factory
+        .get_model_metadata(model_name, false, false, None)
+        .await?;
+    let request = CreateEmbeddingRequest {
+        model: model_name.to_string(),
+        input: Input::Array(texts),
+        user: None,
+        dimensions: None,
+        encoding_format: EncodingFormat::Float,
+    };
+
+    let result = handle_embeddings(
+        request,
+        &executor_context.callbackhandler,
+        &llm_model,
+        executor_context.key_credentials.as_ref(),
+        executor_context.cost_calculator.clone(),
+        executor_context.tags.clone(),
+        executor_context.providers_config.clone(),
+    )
+    .await?;
+
+    match result {
+        EmbeddingResult::Float(response) => {
+            let mut data = response.data;
+            data.sort_by_key(|embedding| embedding.index);
+            Ok(data
+                .into_iter()
+                .map(|embedding| embedding.embedding)
+                .collect())
+        }
+        EmbeddingResult::Base64(_) => Err(GatewayApiError::CustomError(
+            "Expected float embeddings".to_string(),
+        )),
+    }
+}
@@ -40,7 +40,7 @@ pub async fn handle_image_generation(
     let span = Span::current();
     request.model = llm_model.inference_provider.model_name.clone();
 
-    let providers_config = req.app_data::<ProvidersConfig>().cloned();
+    let providers_config = ProvidersConfig::from_request(&req);
     let key = get_key_credentials(
         key_credentials,
         providers_config.as_ref(),
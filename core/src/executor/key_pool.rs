@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::types::credentials::{ApiKeyCredentials, ClientOptions, Credentials};
+
+/// How long a key is skipped after a 429 when the provider does not say otherwise.
+const DEFAULT_THROTTLE_DURATION: Duration = Duration::from_secs(60);
+
+/// Credentials configured for a single provider: either one key entry or a pool of keys.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum ProviderCredentials {
+    Single(ProviderKeyConfig),
+    Pool(CredentialsPool),
+}
+
+impl ProviderCredentials {
+    pub fn get_credentials(&self) -> Option<Credentials> {
+        match self {
+            ProviderCredentials::Single(key) => Some(key.resolve_credentials()),
+            ProviderCredentials::Pool(pool) => pool.select(),
+        }
+    }
+
+    /// Client options of the configured entry that produced `credentials`.
+    pub fn get_client_options(&self, credentials: &Credentials) -> Option<ClientOptions> {
+        match self {
+            ProviderCredentials::Single(key) => Some(key),
+            ProviderCredentials::Pool(pool) => pool
+                .keys
+                .iter()
+                .map(|k| &k.key)
+                .find(|k| &k.resolve_credentials() == credentials),
+        }
+        .filter(|key| &key.resolve_credentials() == credentials)
+        .map(|key| key.client_options())
+    }
+
+    pub fn report_rate_limited(&self, credentials: &Credentials, retry_after: Option<Duration>) {
+        if let ProviderCredentials::Pool(pool) = self {
+            pool.report_rate_limited(credentials, retry_after);
+        }
+    }
+}
+
+impl From<ApiKeyCredentials> for ProviderCredentials {
+    fn from(value: ApiKeyCredentials) -> Self {
+        ProviderCredentials::Single(Credentials::ApiKey(value).into())
+    }
+}
+
+/// A single credentials entry from the providers config.
+///
+/// Accepts any [`Credentials`] variant (API key, AWS IAM / API key, Vertex service account)
+/// plus optional overrides for OpenAI compatible clients.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ProviderKeyConfig {
+    #[serde(flatten)]
+    pub credentials: Credentials,
+    /// Custom base URL, e.g. an Azure OpenAI resource or a self hosted OpenAI compatible server
+    pub endpoint: Option<String>,
+    pub organization: Option<String>,
+    pub api_version: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+impl ProviderKeyConfig {
+    /// Credentials passed to the provider, with `endpoint` folded into API key credentials.
+    pub fn resolve_credentials(&self) -> Credentials {
+        match (&self.credentials, &self.endpoint) {
+            (Credentials::ApiKey(key), Some(endpoint)) => Credentials::ApiKeyWithEndpoint {
+                api_key: key.api_key.clone(),
+                endpoint: endpoint.clone(),
+            },
+            (credentials, _) => credentials.clone(),
+        }
+    }
+
+    pub fn client_options(&self) -> ClientOptions {
+        ClientOptions {
+            organization: self.organization.clone(),
+            api_version: self.api_version.clone(),
+            headers: self.headers.clone(),
+        }
+    }
+}
+
+impl From<Credentials> for ProviderKeyConfig {
+    fn from(credentials: Credentials) -> Self {
+        Self {
+            credentials,
+            endpoint: None,
+            organization: None,
+            api_version: None,
+            headers: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum KeySelectionStrategy {
+    /// Weighted round robin over keys that are not currently throttled
+    #[default]
+    RoundRobin,
+    /// Prefer the key that was rate limited the longest time ago (or never)
+    LeastRecentlyThrottled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PooledCredentials {
+    #[serde(flatten)]
+    pub key: ProviderKeyConfig,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+#[derive(Debug, Default)]
+struct KeyState {
+    throttled_until: Option<Instant>,
+    last_throttled: Option<Instant>,
+}
+
+/// Runtime state shared by every clone of a pool, so all workers see the same throttling.
+#[derive(Debug, Default)]
+struct PoolState {
+    counter: AtomicUsize,
+    keys: Mutex<HashMap<usize, KeyState>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CredentialsPool {
+    pub keys: Vec<PooledCredentials>,
+    #[serde(default)]
+    pub strategy: KeySelectionStrategy,
+    #[serde(skip)]
+    state: Arc<PoolState>,
+}
+
+impl CredentialsPool {
+    pub fn new(keys: Vec<PooledCredentials>, strategy: KeySelectionStrategy) -> Self {
+        Self {
+            keys,
+            strategy,
+            state: Arc::default(),
+        }
+    }
+
+    pub fn select(&self) -> Option<Credentials> {
+        self.select_index(Instant::now())
+            .map(|idx| self.keys[idx].key.resolve_credentials())
+    }
+
+    pub fn report_rate_limited(&self, credentials: &Credentials, retry_after: Option<Duration>) {
+        let Some(idx) = self
+            .keys
+            .iter()
+            .position(|k| &k.key.resolve_credentials() == credentials)
+        else {
+            return;
+        };
+
+        let now = Instant::now();
+        let mut keys = self.state.keys.lock();
+        let state = keys.entry(idx).or_default();
+        state.throttled_until = Some(now + retry_after.unwrap_or(DEFAULT_THROTTLE_DURATION));
+        state.last_throttled = Some(now);
+    }
+
+    fn select_index(&self, now: Instant) -> Option<usize> {
+        if self.keys.is_empty() {
+            return None;
+        }
+
+        let keys = self.state.keys.lock();
+        let is_available = |idx: &usize| {
+            keys.get(idx)
+                .and_then(|s| s.throttled_until)
+                .is_none_or(|until| until <= now)
+        };
+
+        // Keys with a weight of 0 are disabled
+        let enabled = (0..self.keys.len())
+            .filter(|idx| self.keys[*idx].weight > 0)
+            .collect::<Vec<_>>();
+        let available = enabled
+            .iter()
+            .copied()
+            .filter(is_available)
+            .collect::<Vec<_>>();
+
+        if available.is_empty() {
+            // Every key is throttled: use the one that becomes available first
+            return enabled
+                .into_iter()
+                .min_by_key(|idx| keys.get(idx).and_then(|s| s.throttled_until));
+        }
+
+        match self.strategy {
+            KeySelectionStrategy::RoundRobin => Some(self.weighted_round_robin(&available)),
+            KeySelectionStrategy::LeastRecentlyThrottled => {
+                let oldest = available
+                    .iter()
+                    .map(|idx| keys.get(idx).and_then(|s| s.last_throttled))
+                    .min()
+                    .flatten();
+                let candidates = available
+                    .into_iter()
+                    .filter(|idx| keys.get(idx).and_then(|s| s.last_throttled) == oldest)
+                    .collect::<Vec<_>>();
+                Some(self.weighted_round_robin(&candidates))
+            }
+        }
+    }
+
+    fn weighted_round_robin(&self, candidates: &[usize]) -> usize {
+        let total_weight: usize = candidates
+            .iter()
+            .map(|idx| self.keys[*idx].weight as usize)
+            .sum();
+        let mut position = self.state.counter.fetch_add(1, Ordering::Relaxed) % total_weight.max(1);
+
+        for idx in candidates {
+            let weight = self.keys[*idx].weight as usize;
+            if position < weight {
+                return *idx;
+            }
+            position -= weight;
+        }
+
+        candidates[0]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::credentials::BedrockCredentials;
+
+    fn api_key(key: &str) -> Credentials {
+        Credentials::ApiKey(ApiKeyCredentials {
+            api_key: key.to_string(),
+        })
+    }
+
+    #[test]
+    fn test_deserialize_provider_credentials() {
+        let single: ProviderCredentials =
+            serde_json::from_value(serde_json::json!({ "api_key": "key" })).unwrap();
+        assert_eq!(single.get_credentials(), Some(api_key("key")));
+
+        let pool: ProviderCredentials = serde_json::from_value(serde_json::json!({
+            "strategy": "least_recently_throttled",
+            "keys": [
+                { "api_key": "key1", "weight": 2 },
+                { "api_key": "key2", "endpoint": "https://example.com" },
+                { "access_key": "access", "access_secret": "secret", "region": "us-east-1" }
+            ]
+        }))
+        .unwrap();
+        let ProviderCredentials::Pool(pool) = pool else {
+            panic!("Expected pool");
+        };
+        assert_eq!(pool.strategy, KeySelectionStrategy::LeastRecentlyThrottled);
+        assert_eq!(pool.keys[0].weight, 2);
+        assert!(matches!(
+            pool.keys[1].key.resolve_credentials(),
+            Credentials::ApiKeyWithEndpoint { .. }
+        ));
+        assert!(matches!(pool.keys[2].key.credentials, Credentials::Aws(_)));
+    }
+
+    #[test]
+    fn test_deserialize_key_config() {
+        let azure: ProviderCredentials = serde_json::from_value(serde_json::json!({
+            "api_key": "key",
+            "endpoint": "https://my-resource.openai.azure.com",
+            "api_version": "2025-01-01-preview",
+            "organization": "org-123",
+            "headers": { "x-team": "search" }
+        }))
+        .unwrap();
+        let credentials = azure.get_credentials().unwrap();
+        assert_eq!(
+            credentials,
+            Credentials::ApiKeyWithEndpoint {
+                api_key: "key".to_string(),
+                endpoint: "https://my-resource.openai.azure.com".to_string(),
+            }
+        );
+        let options = azure.get_client_options(&credentials).unwrap();
+        assert_eq!(options.api_version.as_deref(), Some("2025-01-01-preview"));
+        assert_eq!(options.organization.as_deref(), Some("org-123"));
+        assert_eq!(
+            options.headers.get("x-team").map(String::as_str),
+            Some("search")
+        );
+        // Options are not applied to credentials supplied elsewhere
+        assert!(azure.get_client_options(&api_key("other")).is_none());
+
+        let bedrock: ProviderCredentials = serde_json::from_value(serde_json::json!({
+            "access_key": "access",
+            "access_secret": "secret",
+            "region": "eu-west-1"
+        }))
+        .unwrap();
+        assert!(matches!(
+            bedrock.get_credentials(),
+            Some(Credentials::Aws(BedrockCredentials::IAM(_)))
+        ));
+    }
+
+    #[test]
+    fn test_weighted_round_robin() {
+        let pool = CredentialsPool::new(
+            vec![
+                PooledCredentials {
+                    key: api_key("key1").into(),
+                    weight: 2,
+                },
+                PooledCredentials {
+                    key: api_key("key2").into(),
+                    weight: 1,
+                },
+            ],
+            KeySelectionStrategy::RoundRobin,
+        );
+
+        let selected = (0..6).map(|_| pool.select().unwrap()).collect::<Vec<_>>();
+        assert_eq!(
+            selected.iter().filter(|c| **c == api_key("key1")).count(),
+            4
+        );
+    }
+
+    #[test]
+    fn test_throttled_key_is_skipped() {
+        let pool = CredentialsPool::new(
+            vec![
+                PooledCredentials {
+                    key: api_key("key1").into(),
+                    weight: 1,
+                },
+                PooledCredentials {
+                    key: api_key("key2").into(),
+                    weight: 1,
+                },
+            ],
+            KeySelectionStrategy::LeastRecentlyThrottled,
+        );
+
+        pool.report_rate_limited(&api_key("key1"), None);
+        for _ in 0..4 {
+            assert_eq!(pool.select().unwrap(), api_key("key2"));
+        }
+
+        pool.report_rate_limited(&api_key("key2"), Some(Duration::from_secs(120)));
+        // Both throttled, key1 becomes available first
+        assert_eq!(pool.select().unwrap(), api_key("key1"));
+    }
+
+    #[test]
+    fn test_disabled_key_is_never_selected() {
+        let pool = CredentialsPool::new(
+            vec![
+                PooledCredentials {
+                    key: api_key("disabled").into(),
+                    weight: 0,
+                },
+                PooledCredentials {
+                    key: api_key("key").into(),
+                    weight: 1,
+                },
+            ],
+            KeySelectionStrategy::RoundRobin,
+        );
+
+        pool.report_rate_limited(&api_key("key"), None);
+        assert_eq!(pool.select().unwrap(), api_key("key"));
+    }
+}
@@ -1,13 +1,17 @@
 use std::collections::HashMap;
+use std::time::Duration;
 
+use actix_web::HttpRequest;
 use context::ExecutorContext;
+use key_pool::ProviderCredentials;
 use serde::{Deserialize, Serialize};
 
 use crate::{
     models::ModelMetadata,
     types::{
-        credentials::{ApiKeyCredentials, Credentials},
+        credentials::{ClientOptions, Credentials},
         provider::InferenceModelProvider,
+        reloadable::Reloadable,
         LANGDB_API_URL,
     },
 };
@@ -16,10 +20,51 @@ pub mod chat_completion;
 pub mod context;
 pub mod embeddings;
 pub mod image_generation;
+pub mod key_pool;
 pub mod responses;
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
-pub struct ProvidersConfig(pub HashMap<String, ApiKeyCredentials>);
+pub struct ProvidersConfig(pub HashMap<String, ProviderCredentials>);
+
+impl ProvidersConfig {
+    /// Providers of the app, read from the reloadable handle when the app registered one.
+    pub fn from_request(req: &HttpRequest) -> Option<Self> {
+        match req.app_data::<Reloadable<Option<ProvidersConfig>>>() {
+            Some(providers) => providers.load().as_ref().clone(),
+            None => req.app_data::<ProvidersConfig>().cloned(),
+        }
+    }
+
+    /// Picks credentials for the provider, load balancing across pooled keys.
+    pub fn get_credentials(&self, provider_name: &str) -> Option<Credentials> {
+        self.0
+            .get(provider_name)
+            .and_then(|credentials| credentials.get_credentials())
+    }
+
+    /// Client options (organization, api version, headers) configured alongside `credentials`.
+    pub fn get_client_options(
+        &self,
+        provider_name: &str,
+        credentials: &Credentials,
+    ) -> Option<ClientOptions> {
+        self.0
+            .get(provider_name)
+            .and_then(|provider_credentials| provider_credentials.get_client_options(credentials))
+    }
+
+    /// Marks pooled credentials as throttled after the provider answered with a rate limit.
+    pub fn report_rate_limited(
+        &self,
+        provider_name: &str,
+        credentials: &Credentials,
+        retry_after: Option<Duration>,
+    ) {
+        if let Some(provider_credentials) = self.0.get(provider_name) {
+            provider_credentials.report_rate_limited(credentials, retry_after);
+        }
+    }
+}
 
 pub fn get_key_credentials(
     key_credentials: Option<&Credentials>,
@@ -29,10 +74,7 @@ pub fn get_key_credentials(
     match key_credentials {
         Some(credentials) => Some(credentials.clone()),
         None => match providers_config {
-            Some(providers_config) => providers_config
-                .0
-                .get(provider_name)
-                .map(|credentials| Credentials::ApiKey(credentials.clone())),
+            Some(providers_config) => providers_config.get_credentials(provider_name),
             None => None,
         },
     }
@@ -46,9 +88,9 @@ pub fn get_langdb_proxy_key(
         key_credentials,
         providers_config
             .as_ref()
-            .and_then(|p| p.0.get("langdb_proxy")),
+            .and_then(|p| p.get_credentials("langdb_proxy")),
     ) {
-        (None, Some(key)) => Some(Credentials::ApiKey(key.clone())),
+        (None, Some(key)) => Some(key),
         (credentials, _) => credentials,
     }
 }
@@ -62,10 +104,10 @@ pub fn use_langdb_proxy(
         executor_context
             .providers_config
             .as_ref()
-            .and_then(|p| p.0.get("langdb_proxy")),
+            .and_then(|p| p.get_credentials("langdb_proxy")),
     ) {
         (None, Some(key)) => (
-            Some(Credentials::ApiKey(key.clone())),
+            Some(key),
             Some(format!(
                 "{}/v1",
                 std::env::var("LANGDB_API_URL")
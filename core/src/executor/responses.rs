@@ -21,7 +21,7 @@ pub async fn handle_create_response(
 ) -> Result<Response, GatewayError> {
     let mut responses_request = request.clone();
     let mut custom_endpoint = None;
-    let providers_config = req.app_data::<ProvidersConfig>().cloned();
+    let providers_config = ProvidersConfig::from_request(req);
     let key = match get_key_credentials(
         key_credentials,
         providers_config.as_ref(),
@@ -1,9 +1,7 @@
 use std::collections::HashMap;
 
 use crate::events::JsonValue;
-use crate::executor::context::ExecutorContext;
-use crate::model::DefaultModelMetadataFactory;
-use crate::routing::interceptor::rate_limiter::InMemoryRateLimiterService;
+use crate::handler::guards::executor_context;
 use crate::routing::RoutingStrategy;
 use crate::types::gateway::ChatCompletionRequestWithTools;
 use crate::types::gateway::CompletionModelUsage;
@@ -18,7 +16,6 @@ use valuable::Valuable;
 
 use crate::handler::AvailableModels;
 use crate::handler::CallbackHandlerFn;
-use crate::model::ModelMetadataFactory;
 use crate::types::gateway::{
     ChatCompletionChunk, ChatCompletionChunkChoice, ChatCompletionDelta, ChatCompletionUsage,
     CostCalculator,
@@ -69,21 +66,14 @@ pub async fn create_chat_completion(
         );
     }
 
-    let memory_storage = req.app_data::<Arc<Mutex<InMemoryStorage>>>().cloned();
-    let rate_limiter_service = InMemoryRateLimiterService::new();
-    let guardrails_evaluator_service = evaluator_service.clone().into_inner();
-    let executor_context = ExecutorContext::new(
-        callback_handler.get_ref().clone(),
-        cost_calculator.into_inner(),
-        Arc::new(
-            Box::new(DefaultModelMetadataFactory::new(&provided_models.0))
-                as Box<dyn ModelMetadataFactory>,
-        ),
+    let executor_context = executor_context(
         &req,
-        HashMap::new(),
-        guardrails_evaluator_service,
-        Arc::new(rate_limiter_service),
+        callback_handler,
+        provided_models,
+        cost_calculator,
+        &evaluator_service,
     )?;
+    let memory_storage = req.app_data::<Arc<Mutex<InMemoryStorage>>>().cloned();
 
     let executor = RoutedExecutor::new(request.clone());
     executor
@@ -107,11 +97,7 @@ pub fn map_sso_event(
                 model: model_name.clone(),
                 choices: vec![ChatCompletionChunkChoice {
                     index: 0,
-                    delta: ChatCompletionDelta {
-                        content: None,
-                        role: None,
-                        tool_calls: None,
-                    },
+                    delta: ChatCompletionDelta::default(),
                     finish_reason: Some(finish_reason.clone()),
                     logprobs: None,
                 }],
@@ -1,4 +1,5 @@
 use crate::executor::embeddings::handle_embeddings;
+use crate::executor::ProvidersConfig;
 use crate::types::credentials::Credentials;
 use crate::types::embed::EmbeddingResult;
 use actix_web::{web, HttpResponse};
@@ -14,7 +15,7 @@ use crate::handler::CallbackHandlerFn;
 use crate::handler::{extract_tags, AvailableModels};
 use crate::GatewayApiError;
 
-use super::{can_execute_llm_for_request, find_model_by_full_name};
+use super::{can_execute_llm_for_request, find_model_by_full_name, resolve_model_alias};
 
 pub async fn embeddings_handler(
     request: web::Json<CreateEmbeddingRequest>,
@@ -26,7 +27,10 @@ pub async fn embeddings_handler(
     can_execute_llm_for_request(&req).await?;
     let request = request.into_inner();
     let available_models = models.into_inner();
-    let llm_model = find_model_by_full_name(&request.model, &available_models.0)?;
+    let llm_model = find_model_by_full_name(
+        &resolve_model_alias(&req, &request.model),
+        &available_models.0,
+    )?;
     let key_credentials = req.extensions().get::<Credentials>().cloned();
 
     let span = Span::or_current(tracing::info_span!(
@@ -48,7 +52,7 @@ pub async fn embeddings_handler(
         key_credentials.as_ref(),
         cost_calculator.into_inner(),
         tags,
-        req,
+        ProvidersConfig::from_request(&req),
     )
     .instrument(span)
     .await?;
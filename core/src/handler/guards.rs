@@ -0,0 +1,322 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+use actix_web::{web, HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::Mutex;
+use tracing::Span;
+use tracing_futures::Instrument;
+use valuable::Valuable;
+
+use crate::error::GatewayError;
+use crate::events::JsonValue;
+use crate::executor::context::ExecutorContext;
+use crate::executor::embeddings::embed_texts;
+use crate::handler::{AvailableModels, CallbackHandlerFn};
+use crate::model::{DefaultModelMetadataFactory, ModelMetadataFactory};
+use crate::models::ModelAliases;
+use crate::routing::interceptor::rate_limiter::InMemoryRateLimiterService;
+use crate::types::gateway::{ChatCompletionMessage, CostCalculator};
+use crate::types::guardrails::service::GuardrailsEvaluator;
+use crate::types::guardrails::{
+    DatasetStore, Guard, GuardAction, GuardError, GuardExample, GuardStage, GuardTemplate,
+    StoredExample,
+};
+use crate::usage::InMemoryStorage;
+use crate::GatewayApiError;
+
+/// Listing of a guard. Parameters are left out as they can hold prompts,
+/// patterns and partner credentials.
+#[derive(Serialize)]
+pub struct GuardSummary {
+    pub id: String,
+    pub name: String,
+    pub r#type: String,
+    pub stage: GuardStage,
+    pub action: GuardAction,
+    pub template_id: String,
+}
+
+impl From<&Guard> for GuardSummary {
+    fn from(guard: &Guard) -> Self {
+        Self {
+            id: guard.id().clone(),
+            name: guard.name().clone(),
+            r#type: guard.r#type(),
+            stage: guard.stage().clone(),
+            action: guard.action().clone(),
+            template_id: guard.termplate_id().clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct GuardsResponse {
+    pub guards: Vec<GuardSummary>,
+    pub templates: BTreeMap<String, GuardTemplate>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GuardEvaluationRequest {
+    pub messages: Vec<ChatCompletionMessage>,
+    /// Runtime parameters merged over the guard configuration
+    #[serde(default)]
+    pub parameters: Option<Value>,
+    /// Stage to evaluate, defaults to the stage of the guard
+    #[serde(default)]
+    pub stage: Option<GuardStage>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AddExamplesRequest {
+    pub examples: Vec<GuardExample>,
+}
+
+#[derive(Serialize)]
+pub struct ExamplesResponse {
+    pub examples: Vec<StoredExample>,
+}
+
+pub(crate) fn executor_context(
+    req: &HttpRequest,
+    callback_handler: web::Data<CallbackHandlerFn>,
+    provided_models: web::Data<AvailableModels>,
+    cost_calculator: web::Data<Box<dyn CostCalculator>>,
+    evaluator_service: &web::Data<Box<dyn GuardrailsEvaluator>>,
+) -> Result<ExecutorContext, GatewayError> {
+    let memory_storage = req.app_data::<Arc<Mutex<InMemoryStorage>>>().cloned();
+    let model_aliases = req.app_data::<ModelAliases>().cloned().unwrap_or_default();
+    ExecutorContext::new(
+        callback_handler.get_ref().clone(),
+        cost_calculator.into_inner(),
+        Arc::new(Box::new(
+            DefaultModelMetadataFactory::new(&provided_models.0)
+                .with_aliases(model_aliases)
+                .with_metrics_storage(memory_storage),
+        ) as Box<dyn ModelMetadataFactory>),
+        req,
+        HashMap::new(),
+        evaluator_service.clone().into_inner(),
+        Arc::new(InMemoryRateLimiterService::new()),
+    )
+}
+
+/// Dataset name and embedding model of a guard with a managed dataset
+fn managed_dataset(
+    evaluator_service: &web::Data<Box<dyn GuardrailsEvaluator>>,
+    guard_id: &str,
+) -> Result<(String, String), GuardError> {
+    let guard = evaluator_service
+        .guards()
+        .remove(guard_id)
+        .ok_or_else(|| GuardError::GuardNotFound(guard_id.to_string()))?;
+
+    match guard {
+        Guard::Dataset {
+            dataset,
+            embedding_model,
+            ..
+        } => match dataset.managed() {
+            Some(config) => Ok((
+                config.map_err(GuardError::DatasetError)?.name,
+                embedding_model,
+            )),
+            None => Err(GuardError::DatasetNotManaged(guard_id.to_string())),
+        },
+        _ => Err(GuardError::DatasetNotManaged(guard_id.to_string())),
+    }
+}
+
+pub async fn list_guards(
+    evaluator_service: web::Data<Box<dyn GuardrailsEvaluator>>,
+) -> Result<HttpResponse, GatewayApiError> {
+    let mut guards: Vec<GuardSummary> = evaluator_service
+        .guards()
+        .values()
+        .map(GuardSummary::from)
+        .collect();
+    guards.sort_by(|a, b| a.id.cmp(&b.id));
+
+    Ok(HttpResponse::Ok().json(GuardsResponse {
+        guards,
+        templates: evaluator_service.templates().into_iter().collect(),
+    }))
+}
+
+pub async fn evaluate_guard(
+    path: web::Path<String>,
+    request: web::Json<GuardEvaluationRequest>,
+    callback_handler: web::Data<CallbackHandlerFn>,
+    req: HttpRequest,
+    provided_models: web::Data<AvailableModels>,
+    cost_calculator: web::Data<Box<dyn CostCalculator>>,
+    evaluator_service: web::Data<Box<dyn GuardrailsEvaluator>>,
+) -> Result<HttpResponse, GatewayApiError> {
+    let guard_id = path.into_inner();
+    let request = request.into_inner();
+
+    let guard = evaluator_service
+        .guards()
+        .remove(&guard_id)
+        .ok_or_else(|| GatewayError::from(GuardError::GuardNotFound(guard_id.clone())))?;
+    let stage = request.stage.unwrap_or_else(|| guard.stage().clone());
+
+    let span = Span::or_current(tracing::info_span!(
+        target: "langdb::user_tracing::api_invoke",
+        "api_invoke",
+        request = JsonValue(&serde_json::to_value(&request.messages)?).as_value(),
+        response = tracing::field::Empty,
+        error = tracing::field::Empty,
+    ));
+
+    let executor_context = executor_context(
+        &req,
+        callback_handler,
+        provided_models,
+        cost_calculator,
+        &evaluator_service,
+    )?;
+
+    let result = evaluator_service
+        .evaluate(
+            &request.messages,
+            &guard_id,
+            &executor_context,
+            request.parameters.as_ref(),
+            &stage,
+        )
+        .instrument(span.clone())
+        .await;
+
+    match result {
+        Ok(result) => {
+            let response = serde_json::to_value(&result)?;
+            span.record("response", JsonValue(&response).as_value());
+            Ok(HttpResponse::Ok().json(response))
+        }
+        Err(e) => {
+            span.record("error", e.as_str());
+            Err(GatewayError::from(GuardError::GuardEvaluationError(e)).into())
+        }
+    }
+}
+
+pub async fn list_examples(
+    path: web::Path<String>,
+    evaluator_service: web::Data<Box<dyn GuardrailsEvaluator>>,
+    dataset_store: web::Data<Arc<dyn DatasetStore>>,
+) -> Result<HttpResponse, GatewayApiError> {
+    let (dataset, _) =
+        managed_dataset(&evaluator_service, &path.into_inner()).map_err(GatewayError::from)?;
+
+    let examples = dataset_store
+        .list(&dataset)
+        .await
+        .map_err(|e| GatewayError::from(GuardError::DatasetError(e)))?;
+
+    Ok(HttpResponse::Ok().json(ExamplesResponse { examples }))
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn add_examples(
+    path: web::Path<String>,
+    request: web::Json<AddExamplesRequest>,
+    callback_handler: web::Data<CallbackHandlerFn>,
+    req: HttpRequest,
+    provided_models: web::Data<AvailableModels>,
+    cost_calculator: web::Data<Box<dyn CostCalculator>>,
+    evaluator_service: web::Data<Box<dyn GuardrailsEvaluator>>,
+    dataset_store: web::Data<Arc<dyn DatasetStore>>,
+) -> Result<HttpResponse, GatewayApiError> {
+    let (dataset, embedding_model) =
+        managed_dataset(&evaluator_service, &path.into_inner()).map_err(GatewayError::from)?;
+    let mut examples = request.into_inner().examples;
+
+    // Embeddings are computed once here so guard evaluations only embed the input
+    let missing: Vec<String> = examples
+        .iter()
+        .filter(|example| example.embedding.is_none())
+        .map(|example| example.text.clone())
+        .collect();
+    if !missing.is_empty() {
+        let executor_context = executor_context(
+            &req,
+            callback_handler,
+            provided_models,
+            cost_calculator,
+            &evaluator_service,
+        )?;
+        let mut embeddings = embed_texts(&executor_context, &embedding_model, missing)
+            .await?
+            .into_iter();
+        for example in examples.iter_mut().filter(|e| e.embedding.is_none()) {
+            example.embedding = embeddings.next();
+        }
+    }
+
+    let examples = dataset_store
+        .add(&dataset, examples)
+        .await
+        .map_err(|e| GatewayError::from(GuardError::DatasetError(e)))?;
+
+    Ok(HttpResponse::Created().json(ExamplesResponse { examples }))
+}
+
+pub async fn remove_example(
+    path: web::Path<(String, String)>,
+    evaluator_service: web::Data<Box<dyn GuardrailsEvaluator>>,
+    dataset_store: web::Data<Arc<dyn DatasetStore>>,
+) -> Result<HttpResponse, GatewayApiError> {
+    let (guard_id, example_id) = path.into_inner();
+    let (dataset, _) =
+        managed_dataset(&evaluator_service, &guard_id).map_err(GatewayError::from)?;
+
+    let removed = dataset_store
+        .remove(&dataset, &example_id)
+        .await
+        .map_err(|e| GatewayError::from(GuardError::DatasetError(e)))?;
+
+    if removed {
+        Ok(HttpResponse::NoContent().finish())
+    } else {
+        Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "Example not found",
+            "example_id": example_id
+        })))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::guardrails::guard_fixture;
+
+    #[test]
+    fn test_guard_summary_leaves_out_parameters() {
+        let guard = guard_fixture(
+            "partner",
+            serde_json::json!({
+                "provider": {
+                    "name": "webhook",
+                    "url": "https://moderation.example.com",
+                    "headers": {"Authorization": "Bearer secret"},
+                    "response_mapping": {"passed": "$.passed"},
+                },
+            }),
+        );
+
+        let summary = serde_json::to_value(GuardSummary::from(&guard)).unwrap();
+        assert_eq!(
+            summary,
+            serde_json::json!({
+                "id": "partner",
+                "name": "partner",
+                "type": "partner",
+                "stage": "input",
+                "action": "validate",
+                "template_id": "partner",
+            })
+        );
+    }
+}
@@ -13,6 +13,7 @@ use tracing_futures::Instrument;
 use super::can_execute_llm_for_request;
 use super::extract_tags;
 use super::find_model_by_full_name;
+use super::resolve_model_alias;
 
 pub async fn create_image(
     request: web::Json<CreateImageRequest>,
@@ -25,7 +26,10 @@ pub async fn create_image(
 
     let request = request.into_inner();
     let available_models = models.into_inner();
-    let llm_model = find_model_by_full_name(&request.model, &available_models.0)?;
+    let llm_model = find_model_by_full_name(
+        &resolve_model_alias(&req, &request.model),
+        &available_models.0,
+    )?;
 
     let span = Span::or_current(tracing::info_span!(
         target: "langdb::user_tracing::api_invoke",
@@ -1,3 +1,4 @@
+use crate::types::reloadable::Reloadable;
 use crate::usage::{InMemoryStorage, LimitPeriod};
 use actix_web::dev::forward_ready;
 use actix_web::{
@@ -61,8 +62,11 @@ where
         let service = Rc::clone(&self.service);
 
         Box::pin(async move {
-            let rate_limit_config = req.app_data::<Option<RateLimiting>>().cloned();
-            if let Some(Some(rate_limit)) = rate_limit_config {
+            let rate_limit_config = match req.app_data::<Reloadable<Option<RateLimiting>>>() {
+                Some(rate_limit) => rate_limit.load().as_ref().clone(),
+                None => req.app_data::<Option<RateLimiting>>().cloned().flatten(),
+            };
+            if let Some(rate_limit) = rate_limit_config {
                 let storage = req
                     .app_data::<Arc<Mutex<InMemoryStorage>>>()
                     .unwrap()
@@ -1,13 +1,16 @@
 pub mod chat;
 pub mod embedding;
+pub mod guards;
 pub mod image;
 pub mod middleware;
 pub mod models;
 pub mod responses;
+pub mod traces;
 
 use crate::model::types::ModelEvent;
-use crate::models::ModelMetadata;
+use crate::models::{ModelAliases, ModelMetadata};
 use crate::types::engine::Model;
+use crate::types::reloadable::Reloadable;
 use crate::GatewayApiError;
 use crate::{error::GatewayError, model::error::ModelError};
 use actix_web::HttpRequest;
@@ -55,6 +58,13 @@ pub fn find_model_by_full_name(
     }
 }
 
+/// Resolves config-defined aliases and virtual models to the model name they target.
+pub fn resolve_model_alias(req: &HttpRequest, model_name: &str) -> String {
+    req.app_data::<ModelAliases>()
+        .and_then(|aliases| aliases.resolve_target(model_name))
+        .unwrap_or_else(|| model_name.to_string())
+}
+
 // extract langdb-tags from headers, shoule be sth like this: tag1=value1&tag2=value2 => result should be a Map<String, String>
 pub fn extract_tags(req: &HttpRequest) -> Result<HashMap<String, String>, GatewayError> {
     Ok(match req.headers().get("x-tags") {
@@ -120,7 +130,7 @@ pub struct DollarUsage {
 }
 
 #[async_trait::async_trait]
-pub trait LimitCheck {
+pub trait LimitCheck: Send {
     async fn can_execute_llm(&mut self) -> Result<bool, Box<dyn std::error::Error>>;
     async fn get_usage(&self) -> Result<DollarUsage, Box<dyn std::error::Error>>;
 }
@@ -182,8 +192,14 @@ impl LimitCheckWrapper {
 }
 
 pub(crate) async fn can_execute_llm_for_request(req: &HttpRequest) -> Result<(), GatewayApiError> {
-    let limit_checker = req.app_data::<Option<LimitCheckWrapper>>();
-    if let Some(Some(l)) = limit_checker {
+    let limit_checker = match req.app_data::<Reloadable<Option<LimitCheckWrapper>>>() {
+        Some(limit_checker) => limit_checker.load().as_ref().clone(),
+        None => req
+            .app_data::<Option<LimitCheckWrapper>>()
+            .cloned()
+            .flatten(),
+    };
+    if let Some(l) = limit_checker {
         let can_execute = l
             .can_execute_llm()
             .await
@@ -1,12 +1,13 @@
 use std::collections::HashMap;
 
-use crate::{models::ModelCapability, types::gateway::ChatModel};
-use actix_web::{web, HttpResponse};
+use crate::models::{ModelAliases, ModelCapability};
+use crate::types::gateway::ChatModel;
+use actix_web::{web, HttpRequest, HttpResponse};
 use serde::Serialize;
 
 use crate::GatewayApiError;
 
-use super::AvailableModels;
+use super::{find_model_by_full_name, AvailableModels};
 
 #[derive(Serialize)]
 pub struct ChatModelsResponse {
@@ -16,20 +17,47 @@ pub struct ChatModelsResponse {
 
 pub async fn list_gateway_models(
     models: web::Data<AvailableModels>,
+    req: HttpRequest,
 ) -> Result<HttpResponse, GatewayApiError> {
-    let response = ChatModelsResponse {
-        object: "list".to_string(),
-        data: models
-            .into_inner()
-            .0
-            .iter()
-            .map(|v| ChatModel {
-                id: v.qualified_model_name(),
+    let models = models.into_inner();
+    let mut data: Vec<ChatModel> = models
+        .0
+        .iter()
+        .map(|v| ChatModel {
+            id: v.qualified_model_name(),
+            object: "model".to_string(),
+            created: 1686935002,
+            owned_by: v.model_provider.to_string(),
+        })
+        .collect();
+
+    if let Some(aliases) = req.app_data::<ModelAliases>() {
+        let mut names: Vec<&String> = aliases
+            .aliases
+            .keys()
+            .chain(aliases.virtual_models.keys())
+            .collect();
+        names.sort();
+
+        for name in names {
+            let owned_by = aliases
+                .resolve_target(name)
+                .and_then(|target| find_model_by_full_name(&target, &models.0).ok())
+                .map(|m| m.model_provider)
+                .unwrap_or("virtual".to_string());
+
+            data.push(ChatModel {
+                id: name.clone(),
                 object: "model".to_string(),
                 created: 1686935002,
-                owned_by: v.model_provider.to_string(),
-            })
-            .collect(),
+                owned_by,
+            });
+        }
+    }
+
+    let response = ChatModelsResponse {
+        object: "list".to_string(),
+        data,
     };
 
     Ok(HttpResponse::Ok().json(response))
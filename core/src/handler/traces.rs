@@ -0,0 +1,232 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use actix_web::{web, HttpResponse};
+use bytes::Bytes;
+use serde::Deserialize;
+use tokio::sync::broadcast;
+
+use crate::telemetry::{ProjectTraceMap, Span, DEFAULT_PROJECT_ID};
+use crate::GatewayApiError;
+
+const TRACE_CHANNEL_CAPACITY: usize = 1000;
+const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
+#[derive(Debug, Deserialize, Default)]
+pub struct TraceStreamQuery {
+    pub project: Option<String>,
+    pub thread_id: Option<String>,
+    pub run_id: Option<String>,
+    /// Comma separated `key=value` pairs, all of which must match the span tags
+    pub tags: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct TraceFilter {
+    thread_id: Option<String>,
+    run_id: Option<String>,
+    tags: HashMap<String, String>,
+}
+
+impl TraceFilter {
+    fn new(query: &TraceStreamQuery) -> Self {
+        let tags = query
+            .tags
+            .as_deref()
+            .unwrap_or_default()
+            .split(',')
+            .filter(|tag| !tag.trim().is_empty())
+            .map(|tag| {
+                tag.split_once('=')
+                    .map_or((tag.trim().to_string(), "-".to_string()), |(k, v)| {
+                        (k.trim().to_string(), v.trim().to_string())
+                    })
+            })
+            .collect();
+
+        Self {
+            thread_id: query.thread_id.clone(),
+            run_id: query.run_id.clone(),
+            tags,
+        }
+    }
+
+    fn matches(&self, span: &Span) -> bool {
+        if self.thread_id.is_some() && span.thread_id != self.thread_id {
+            return false;
+        }
+
+        if self.run_id.is_some() && span.run_id != self.run_id {
+            return false;
+        }
+
+        self.tags
+            .iter()
+            .all(|(key, value)| match span.tags.get(key) {
+                Some(serde_json::Value::String(v)) => v == value,
+                Some(v) => {
+                    serde_json::from_str::<serde_json::Value>(value).is_ok_and(|value| value == *v)
+                }
+                None => false,
+            })
+    }
+}
+
+fn sse_event(event: &str, data: &str) -> Bytes {
+    Bytes::from(format!("event: {event}\ndata: {data}\n\n"))
+}
+
+/// Receiver of a project channel. The channel is removed with its last subscriber,
+/// so channels of projects nobody watches do not pile up.
+struct Subscription {
+    receiver: Option<broadcast::Receiver<Span>>,
+    project_id: String,
+    senders: Arc<ProjectTraceMap>,
+}
+
+impl Subscription {
+    fn new(senders: Arc<ProjectTraceMap>, project_id: String) -> Self {
+        let receiver = senders
+            .entry(project_id.clone())
+            .or_insert_with(|| broadcast::channel(TRACE_CHANNEL_CAPACITY).0)
+            .subscribe();
+
+        Self {
+            receiver: Some(receiver),
+            project_id,
+            senders,
+        }
+    }
+}
+
+impl Drop for Subscription {
+    fn drop(&mut self) {
+        self.receiver.take();
+        self.senders
+            .remove_if(&self.project_id, |_, sender| sender.receiver_count() == 0);
+    }
+}
+
+/// Waits for the next span matching the filter, emitting keep-alive comments while idle.
+/// Returns `None` once the project channel is closed.
+async fn next_event(
+    receiver: &mut broadcast::Receiver<Span>,
+    keep_alive: &mut tokio::time::Interval,
+    filter: &TraceFilter,
+) -> Option<Bytes> {
+    loop {
+        tokio::select! {
+            span = receiver.recv() => match span {
+                Ok(span) if filter.matches(&span) => match serde_json::to_string(&span) {
+                    Ok(data) => return Some(sse_event("span", &data)),
+                    Err(e) => tracing::error!("Failed to serialize span: {e}"),
+                },
+                Ok(_) => {}
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    let data = serde_json::json!({ "skipped": skipped }).to_string();
+                    return Some(sse_event("lagged", &data));
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            },
+            _ = keep_alive.tick() => return Some(Bytes::from_static(b": keep-alive\n\n")),
+        }
+    }
+}
+
+/// Streams finished spans of a project as server-sent events.
+pub async fn stream_traces(
+    query: web::Query<TraceStreamQuery>,
+    project_trace_senders: web::Data<Arc<ProjectTraceMap>>,
+) -> Result<HttpResponse, GatewayApiError> {
+    let query = query.into_inner();
+    let project_id = query
+        .project
+        .clone()
+        .unwrap_or(DEFAULT_PROJECT_ID.to_string());
+    let filter = TraceFilter::new(&query);
+
+    let subscription = Subscription::new(project_trace_senders.get_ref().clone(), project_id);
+
+    let mut keep_alive = tokio::time::interval(KEEP_ALIVE_INTERVAL);
+    keep_alive.tick().await;
+
+    let stream = futures::stream::unfold(
+        (subscription, keep_alive, filter),
+        |(mut subscription, mut keep_alive, filter)| async move {
+            let receiver = subscription.receiver.as_mut()?;
+            let event = next_event(receiver, &mut keep_alive, &filter).await?;
+            Some((
+                Ok::<_, GatewayApiError>(event),
+                (subscription, keep_alive, filter),
+            ))
+        },
+    );
+
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(stream))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use opentelemetry::trace::{SpanId, SpanKind, TraceId};
+
+    fn span(thread_id: Option<&str>, tags: serde_json::Value) -> Span {
+        Span {
+            trace_id: TraceId::from_bytes([1; 16]),
+            span_id: SpanId::from_bytes([1; 8]),
+            parent_span_id: None,
+            operation_name: "model_call".to_string(),
+            kind: SpanKind::Internal,
+            start_time_unix_nano: 0,
+            end_time_unix_nano: 0,
+            attributes: Default::default(),
+            tenant_id: None,
+            project_id: None,
+            thread_id: thread_id.map(|t| t.to_string()),
+            tags: tags.as_object().cloned().unwrap_or_default(),
+            run_id: None,
+        }
+    }
+
+    #[test]
+    fn test_channel_removed_with_last_subscriber() {
+        let senders = Arc::new(ProjectTraceMap::new());
+
+        let first = Subscription::new(senders.clone(), "project".to_string());
+        let second = Subscription::new(senders.clone(), "project".to_string());
+        drop(first);
+        assert!(senders.contains_key("project"));
+
+        drop(second);
+        assert!(senders.is_empty());
+    }
+
+    #[test]
+    fn test_trace_filter() {
+        let filter = TraceFilter::new(&TraceStreamQuery {
+            thread_id: Some("thread-1".to_string()),
+            tags: Some("env=prod, team=search".to_string()),
+            ..Default::default()
+        });
+
+        assert!(filter.matches(&span(
+            Some("thread-1"),
+            serde_json::json!({ "env": "prod", "team": "search", "extra": "x" })
+        )));
+        assert!(!filter.matches(&span(
+            Some("thread-2"),
+            serde_json::json!({ "env": "prod", "team": "search" })
+        )));
+        assert!(!filter.matches(&span(
+            Some("thread-1"),
+            serde_json::json!({ "env": "prod" })
+        )));
+
+        assert!(TraceFilter::new(&TraceStreamQuery::default())
+            .matches(&span(None, serde_json::Value::Null)));
+    }
+}
@@ -61,6 +61,14 @@ pub enum GatewayApiError {
 }
 
 impl GatewayApiError {
+    /// Rate limit reported by the provider, if it rejected the call because of one.
+    pub fn rate_limit(&self) -> Option<model::error::RateLimit> {
+        match self {
+            GatewayApiError::GatewayError(GatewayError::ModelError(e)) => e.rate_limit(),
+            _ => None,
+        }
+    }
+
     pub fn is_countable_error(&self) -> bool {
         !matches!(
             self,
@@ -1,11 +1,12 @@
 use std::collections::HashSet;
 
+use crate::model::files::{mime_type_from_name, parse_data_url};
 use crate::types::{
-    gateway::{ChatCompletionContent, ChatCompletionMessage, ContentType},
+    gateway::{CacheControl, ChatCompletionContent, ChatCompletionMessage, ContentType, InputFile},
     message::{MessageType, PromptMessage},
     threads::{
-        AudioDetail, AudioFormat, Message, MessageContentPart, MessageContentPartOptions,
-        MessageContentType,
+        AudioDetail, AudioFormat, FileDetail, Message, MessageContentPart,
+        MessageContentPartOptions, MessageContentType,
     },
 };
 
@@ -55,6 +56,11 @@ impl MessageMapper {
                                             .to_string(),
                                     ))
                                 }
+                                ContentType::File => {
+                                    return Err(GatewayError::CustomError(
+                                        "Files are not supported for system messages".to_string(),
+                                    ))
+                                }
                             }
                         }
                     },
@@ -155,6 +161,12 @@ impl MessageMapper {
                                     cache_control: c.cache_control.clone(),
                                 }
                             }
+                            ContentType::File => {
+                                let file = c.file.as_ref().ok_or(GatewayError::CustomError(
+                                    "File data is empty".to_string(),
+                                ))?;
+                                Self::map_file_content(file, c.cache_control.clone())?
+                            }
                         })
                     })
                     .collect::<Result<Vec<MessageContentPart>, GatewayError>>(),
@@ -173,6 +185,43 @@ impl MessageMapper {
             r#type: Self::map_role_to_message_type(message.role.as_str()),
             tool_calls: message.tool_calls.clone(),
             tool_call_id: message.tool_call_id.clone(),
+            reasoning_content: message.reasoning_content.clone(),
+            reasoning_signature: message.reasoning_signature.clone(),
+        })
+    }
+
+    /// Stores inline file data as a data url so providers can tell it apart from file urls.
+    fn map_file_content(
+        file: &InputFile,
+        cache_control: Option<CacheControl>,
+    ) -> Result<MessageContentPart, GatewayError> {
+        let mime_type = file
+            .mime_type
+            .clone()
+            .or(file.filename.as_deref().and_then(mime_type_from_name));
+
+        let value = match (&file.file_data, &file.file_url) {
+            (Some(data), _) if parse_data_url(data).is_some() => data.clone(),
+            (Some(data), _) => format!(
+                "data:{};base64,{data}",
+                mime_type.as_deref().unwrap_or("application/octet-stream")
+            ),
+            (None, Some(url)) => url.clone(),
+            (None, None) => {
+                return Err(GatewayError::CustomError(
+                    "File requires either file_data or file_url".to_string(),
+                ))
+            }
+        };
+
+        Ok(MessageContentPart {
+            r#type: MessageContentType::File,
+            value,
+            additional_options: Some(MessageContentPartOptions::File(FileDetail {
+                filename: file.filename.clone(),
+                mime_type,
+            })),
+            cache_control,
         })
     }
 
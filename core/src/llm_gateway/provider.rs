@@ -5,7 +5,7 @@ use clust::messages::StopSequence;
 use crate::{
     models::ModelMetadata,
     types::{
-        credentials::{ApiKeyCredentials, Credentials},
+        credentials::{ApiKeyCredentials, ClientOptions, Credentials},
         engine::{
             AnthropicModelParams, BedrockModelParams, ClaudeModel, CompletionEngineParams,
             EmbeddingsEngineParams, ExecutionOptions, GeminiModelParams,
@@ -28,6 +28,7 @@ impl Provider {
         model: &ModelMetadata,
         request: &ChatCompletionRequest,
         credentials: Option<Credentials>,
+        client_options: Option<ClientOptions>,
         provider_specific: Option<&ProviderSpecificRequest>,
         execution_options: Option<ExecutionOptions>,
     ) -> Result<CompletionEngineParams, GatewayError> {
@@ -48,6 +49,9 @@ impl Provider {
                     user: request.user.clone(),
                     response_format: request.response_format.clone(),
                     prompt_cache_key: request.prompt_cache_key.clone(),
+                    reasoning_effort: request.reasoning_effort,
+                    tool_choice: request.tool_choice.clone(),
+                    parallel_tool_calls: request.parallel_tool_calls,
                 };
                 let mut custom_endpoint = None;
                 let api_key_credentials = credentials.and_then(|cred| match cred {
@@ -61,12 +65,14 @@ impl Provider {
                     }
                     _ => None,
                 });
+                let client_options = client_options.unwrap_or_default();
                 match &model.inference_provider.provider {
                     InferenceModelProvider::OpenAI => Ok(CompletionEngineParams::OpenAi {
                         params,
                         execution_options: execution_options.unwrap_or_default(),
                         credentials: api_key_credentials,
-                        endpoint: None,
+                        endpoint: custom_endpoint,
+                        client_options,
                     }),
                     InferenceModelProvider::Proxy(proxy_provider) => {
                         if proxy_provider == "azure" {
@@ -75,12 +81,14 @@ impl Provider {
                                 execution_options: execution_options.unwrap_or_default(),
                                 credentials: api_key_credentials,
                                 endpoint: custom_endpoint,
+                                client_options,
                             })
                         } else {
                             Ok(CompletionEngineParams::Proxy {
                                 params,
                                 execution_options: execution_options.unwrap_or_default(),
                                 credentials: api_key_credentials,
+                                client_options,
                             })
                         }
                     }
@@ -95,16 +103,32 @@ impl Provider {
                     "mistral" => BedrockProvider::Mistral,
                     p => BedrockProvider::Other(p.to_string()),
                 };
+                let mut additional_parameters = HashMap::new();
+                let mut max_tokens = request.max_tokens.map(u64::from);
+                // Claude models on Bedrock take the Anthropic thinking config as an additional field
+                if model.model_provider == "anthropic" {
+                    if let Some(effort) = request.reasoning_effort {
+                        let limit = effort.max_tokens_with(max_tokens);
+                        if let Some(thinking) = effort.as_thinking(limit) {
+                            additional_parameters
+                                .insert("thinking".to_string(), serde_json::to_value(thinking)?);
+                            max_tokens = Some(limit);
+                        }
+                    }
+                }
                 Ok(CompletionEngineParams::Bedrock {
                     credentials: aws_creds,
                     execution_options: execution_options.unwrap_or_default(),
                     params: BedrockModelParams {
                         model_id: Some(model.inference_provider.model_name.clone()),
-                        max_tokens: request.max_tokens.map(|x| x as i32),
+                        max_tokens: max_tokens.map(|x| x as i32),
                         temperature: request.temperature,
                         top_p: request.top_p,
                         stop_sequences: request.stop.clone(),
-                        additional_parameters: HashMap::new(),
+                        tool_choice: request.tool_choice.clone(),
+                        guardrail_config: provider_specific
+                            .and_then(|ps| ps.guardrail_config.clone()),
+                        additional_parameters,
                     },
                     provider,
                 })
@@ -116,13 +140,26 @@ impl Provider {
                 });
                 let model_name = get_anthropic_model(&model.inference_provider.model_name);
                 let model = serde_json::from_str::<ClaudeModel>(&format!("\"{model_name}\""))?;
+                let mut max_tokens = request.max_tokens.map(u64::from);
+                let thinking = match provider_specific.and_then(|ps| ps.thinking.clone()) {
+                    Some(thinking) => Some(thinking),
+                    None => request.reasoning_effort.and_then(|effort| {
+                        // Anthropic defaults max_tokens below the larger budgets
+                        let limit = effort.max_tokens_with(max_tokens);
+                        let thinking = effort.as_thinking(limit)?;
+                        max_tokens = Some(limit);
+                        Some(thinking)
+                    }),
+                };
                 Ok(CompletionEngineParams::Anthropic {
                     credentials: api_key_credentials,
                     execution_options: execution_options.unwrap_or_default(),
                     params: AnthropicModelParams {
                         model: Some(model.clone()),
-                        max_tokens: match request.max_tokens {
-                            Some(x) => Some(clust::messages::MaxTokens::new(x, model.model)?),
+                        max_tokens: match max_tokens {
+                            Some(x) => {
+                                Some(clust::messages::MaxTokens::new(x as u32, model.model)?)
+                            }
                             None => None,
                         },
                         stop_sequences: request
@@ -140,14 +177,12 @@ impl Provider {
                         },
                         top_k: provider_specific
                             .and_then(|ps| ps.top_k.map(clust::messages::TopK::new)),
-                        thinking: provider_specific.and_then(|ps| {
-                            ps.thinking
-                                .as_ref()
-                                .map(|thinking| clust::messages::Thinking {
-                                    r#type: thinking.r#type.clone(),
-                                    budget_tokens: thinking.budget_tokens,
-                                })
+                        thinking: thinking.map(|thinking| clust::messages::Thinking {
+                            r#type: thinking.r#type,
+                            budget_tokens: thinking.budget_tokens,
                         }),
+                        tool_choice: request.tool_choice.clone(),
+                        parallel_tool_calls: request.parallel_tool_calls,
                     },
                 })
             }
@@ -172,11 +207,24 @@ impl Provider {
                         // Not supported by request inteface
                         // response_logprobs: request.response_logprobs,
                         // logprobs: request.logprobs,
-                        // top_k: request.top_k,
                         response_logprobs: None,
                         logprobs: None,
-                        top_k: None,
+                        top_k: provider_specific.and_then(|ps| ps.top_k.map(|k| k as i32)),
                         response_format: request.response_format.clone(),
+                        thinking_budget: request.reasoning_effort.and_then(|e| {
+                            match request.max_tokens {
+                                Some(max_tokens) => e.budget_tokens_within(u64::from(max_tokens)),
+                                None => Some(e.budget_tokens()),
+                            }
+                            .map(|budget| budget as i32)
+                        }),
+                        tool_choice: request.tool_choice.clone(),
+                        safety_settings: provider_specific
+                            .and_then(|ps| ps.safety_settings.clone()),
+                        thinking_config: provider_specific
+                            .and_then(|ps| ps.thinking_config.clone()),
+                        google_search: provider_specific.and_then(|ps| ps.google_search),
+                        code_execution: provider_specific.and_then(|ps| ps.code_execution),
                     },
                 })
             }
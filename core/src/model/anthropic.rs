@@ -1,8 +1,8 @@
 use super::error::{AuthorizationError, ModelError};
 use super::tools::Tool;
 use super::types::{
-    LLMContentEvent, LLMFinishEvent, LLMStartEvent, ModelEvent, ModelEventType, ModelFinishReason,
-    ModelToolCall, ToolStartEvent,
+    LLMContentEvent, LLMFinishEvent, LLMReasoningEvent, LLMStartEvent, ModelEvent, ModelEventType,
+    ModelFinishReason, ModelToolCall, ToolStartEvent,
 };
 use super::{CredentialsIdent, ModelInstance};
 use crate::error::GatewayError;
@@ -10,18 +10,22 @@ use crate::events::JsonValue;
 use crate::events::SPAN_ANTHROPIC;
 use crate::events::{self, RecordResult};
 use crate::model::error::{AnthropicError, ModelFinishError};
-use crate::model::handler::handle_tool_call;
+use crate::model::files::{FilePart, FileSource};
+use crate::model::handler::{handle_tool_call, ToolLoopGuard};
+use crate::model::images::{resolve_images, ImageData};
 use crate::model::types::LLMFirstToken;
 use crate::model::{async_trait, DEFAULT_MAX_RETRIES};
 use crate::types::credentials::ApiKeyCredentials;
 use crate::types::engine::{AnthropicModelParams, ExecutionOptions, Prompt};
 use crate::types::gateway::{
     ChatCompletionContent, ChatCompletionMessage, ChatCompletionMessageWithFinishReason, ToolCall,
+    ToolChoice, ToolChoiceMode,
 };
 use crate::types::gateway::{CompletionModelUsage, PromptTokensDetails};
 use crate::types::message::{MessageType, PromptMessage};
-use crate::types::threads::{InnerMessage, Message};
+use crate::types::threads::{InnerMessage, Message, MessageContentPart};
 use crate::{create_model_span, GatewayResult};
+use clust::messages::MessagesError;
 use clust::messages::MessagesResponseBody;
 use clust::messages::{
     Content, ContentBlock, ImageContentBlock, ImageContentSource, Message as ClustMessage,
@@ -51,7 +55,7 @@ macro_rules! target {
 }
 
 enum InnerExecutionResult {
-    Finish(ChatCompletionMessageWithFinishReason),
+    Finish(Box<ChatCompletionMessageWithFinishReason>),
     NextCall((Option<SystemPrompt>, Vec<ClustMessage>)),
 }
 
@@ -59,18 +63,229 @@ fn custom_err(e: impl ToString) -> ModelError {
     ModelError::CustomError(e.to_string())
 }
 
+fn messages_err(e: MessagesError) -> ModelError {
+    match &e {
+        MessagesError::ApiError(api_error) if api_error.status.as_u16() == 429 => {
+            ModelError::RateLimited {
+                message: api_error.to_string(),
+                retry_after: None,
+            }
+        }
+        _ => custom_err(e),
+    }
+}
+
+fn anthropic_api_key(credentials: Option<&ApiKeyCredentials>) -> Result<String, ModelError> {
+    match credentials {
+        Some(credentials) => Ok(credentials.api_key.clone()),
+        None => Ok(std::env::var("LANGDB_ANTHROPIC_API_KEY")
+            .map_err(|_| AuthorizationError::InvalidApiKey)?),
+    }
+}
+
 pub fn anthropic_client(
     credentials: Option<&ApiKeyCredentials>,
 ) -> Result<clust::Client, ModelError> {
-    let api_key = if let Some(credentials) = credentials {
-        credentials.api_key.clone()
-    } else {
-        std::env::var("LANGDB_ANTHROPIC_API_KEY").map_err(|_| AuthorizationError::InvalidApiKey)?
-    };
+    let api_key = anthropic_api_key(credentials)?;
     let client = Client::from_api_key(clust::ApiKey::new(api_key));
     Ok(client)
 }
 
+/// Reads the signature of a thinking block or signature delta.
+fn reasoning_signature<T: serde::Serialize>(block: &T) -> Option<String> {
+    serde_json::to_value(block)
+        .ok()?
+        .get("signature")?
+        .as_str()
+        .map(|s| s.to_string())
+}
+
+/// Rebuilds a signed thinking block so it can be sent back to Anthropic.
+fn thinking_block(thinking: &str, signature: &str) -> Result<ContentBlock, GatewayError> {
+    Ok(serde_json::from_value(serde_json::json!({
+        "type": "thinking",
+        "thinking": thinking,
+        "signature": signature,
+    }))?)
+}
+
+/// Maps the OpenAI style tool choice onto Anthropic's `tool_choice` object.
+pub(crate) fn anthropic_tool_choice(
+    tool_choice: Option<&ToolChoice>,
+    parallel_tool_calls: Option<bool>,
+) -> Option<Value> {
+    let mut choice = match tool_choice {
+        Some(ToolChoice::Mode(ToolChoiceMode::None)) => {
+            return Some(serde_json::json!({ "type": "none" }))
+        }
+        Some(ToolChoice::Mode(ToolChoiceMode::Auto)) => serde_json::json!({ "type": "auto" }),
+        Some(ToolChoice::Mode(ToolChoiceMode::Required)) => serde_json::json!({ "type": "any" }),
+        Some(ToolChoice::Named(named)) => {
+            serde_json::json!({ "type": "tool", "name": named.function.name })
+        }
+        None if parallel_tool_calls == Some(false) => serde_json::json!({ "type": "auto" }),
+        None => return None,
+    };
+
+    if parallel_tool_calls == Some(false) {
+        choice["disable_parallel_tool_use"] = Value::Bool(true);
+    }
+
+    Some(choice)
+}
+
+/// `none` leaves the tools out of the request. Anthropic rejects `tool_use`/`tool_result`
+/// blocks without tool definitions, so they are kept once the conversation has used tools.
+pub(crate) fn anthropic_sends_tools(
+    tool_choice: Option<&ToolChoice>,
+    messages: &[ClustMessage],
+) -> bool {
+    if tool_choice != Some(&ToolChoice::Mode(ToolChoiceMode::None)) {
+        return true;
+    }
+
+    messages.iter().any(|message| match &message.content {
+        Content::MultipleBlocks(blocks) => blocks.iter().any(|block| {
+            matches!(
+                block,
+                ContentBlock::ToolUse(_) | ContentBlock::ToolResult(_)
+            )
+        }),
+        Content::SingleText(_) => false,
+    })
+}
+
+const MESSAGES_URL: &str = "https://api.anthropic.com/v1/messages";
+
+/// A messages request with the fields clust's request body doesn't have.
+#[derive(Debug, Clone, serde::Serialize)]
+pub(crate) struct AnthropicRequest {
+    #[serde(flatten)]
+    pub body: MessagesRequestBody,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<Value>,
+}
+
+/// Whether any content block asks for the one hour cache, which needs a beta header.
+fn uses_one_hour_cache(body: &Value) -> bool {
+    match body {
+        Value::Object(map) => map.iter().any(|(key, value)| {
+            (key == "cache_control" && value.get("ttl") == Some(&Value::from("1h")))
+                || uses_one_hour_cache(value)
+        }),
+        Value::Array(values) => values.iter().any(uses_one_hour_cache),
+        _ => false,
+    }
+}
+
+/// Posts a request clust can't send, with the headers its client sets.
+async fn post_message(
+    http: &clust::reqwest::Client,
+    api_key: &str,
+    request: &AnthropicRequest,
+) -> Result<clust::reqwest::Response, ModelError> {
+    let body = serde_json::to_value(request).map_err(custom_err)?;
+    let mut builder = http
+        .post(MESSAGES_URL)
+        .header("x-api-key", api_key)
+        .header("anthropic-version", clust::Version::V2023_06_01.to_string());
+    if uses_one_hour_cache(&body) {
+        builder = builder.header(
+            "anthropic-beta",
+            clust::Beta::ExtendedCacheTtl2025_04_11.to_string(),
+        );
+    }
+
+    let response = builder
+        .json(&body)
+        .send()
+        .await
+        .map_err(|e| messages_err(clust::ClientError::HttpRequestError(e).into()))?;
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+
+    let text = response
+        .text()
+        .await
+        .map_err(|e| messages_err(clust::ClientError::ReadResponseTextFailed(e).into()))?;
+    let error = match serde_json::from_str(&text) {
+        Ok(response) => clust::ApiError {
+            status,
+            _type: status.into(),
+            response,
+        }
+        .into(),
+        Err(error) => clust::ClientError::ErrorResponseDeserializationFailed { error, text }.into(),
+    };
+    Err(messages_err(error))
+}
+
+/// Parses one server sent event of a messages stream, `None` for events that carry no chunk.
+fn parse_chunk(event: &str) -> Result<Option<MessageChunk>, StreamError> {
+    let mut name = None;
+    let mut data = None;
+    for line in event.lines() {
+        if let Some(value) = line.strip_prefix("event:") {
+            name = Some(value.trim());
+        } else if let Some(value) = line.strip_prefix("data:") {
+            data = Some(value.trim());
+        }
+    }
+    let (Some(name), Some(data)) = (name, data) else {
+        return Ok(None);
+    };
+
+    let variant = match name {
+        "message_start" => "MessageStart",
+        "content_block_start" => "ContentBlockStart",
+        "ping" => "Ping",
+        "content_block_delta" => "ContentBlockDelta",
+        "content_block_stop" => "ContentBlockStop",
+        "message_delta" => "MessageDelta",
+        "message_stop" => "MessageStop",
+        "error" => return Err(StreamError::ParseChunkStringError(data.to_string())),
+        _ => return Ok(None),
+    };
+    let data: Value = serde_json::from_str(data)?;
+    Ok(Some(serde_json::from_value(
+        serde_json::json!({ variant: data }),
+    )?))
+}
+
+/// Splits the response body into message chunks, like clust's chunk stream.
+fn chunk_stream(
+    response: clust::reqwest::Response,
+) -> impl Stream<Item = Result<MessageChunk, StreamError>> {
+    futures::stream::unfold(
+        (response.bytes_stream().fuse(), Vec::new()),
+        |(mut bytes, mut buffer)| async move {
+            loop {
+                if let Some(end) = buffer.windows(2).position(|w| w == b"\n\n") {
+                    let event: Vec<u8> = buffer.drain(..end + 2).collect();
+                    let chunk = String::from_utf8(event)
+                        .map_err(StreamError::from)
+                        .and_then(|event| parse_chunk(&event));
+                    match chunk {
+                        Ok(None) => continue,
+                        Ok(Some(chunk)) => return Some((Ok(chunk), (bytes, buffer))),
+                        Err(e) => return Some((Err(e), (bytes, buffer))),
+                    }
+                }
+
+                match bytes.next().await {
+                    Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                    Some(Err(e)) => return Some((Err(e.into()), (bytes, buffer))),
+                    None if buffer.iter().all(u8::is_ascii_whitespace) => return None,
+                    // The last event may not be followed by a blank line
+                    None => buffer.extend_from_slice(b"\n\n"),
+                }
+            }
+        },
+    )
+}
+
 fn tool_definition(tool: &dyn Tool) -> clust::messages::ToolDefinition {
     let name = tool.name();
     let description = Some(tool.description());
@@ -90,6 +305,8 @@ pub struct AnthropicModel {
     params: AnthropicModelParams,
     execution_options: ExecutionOptions,
     client: Client,
+    http: clust::reqwest::Client,
+    api_key: String,
     prompt: Prompt,
     tools: Arc<HashMap<String, Box<dyn Tool>>>,
     credentials_ident: CredentialsIdent,
@@ -108,6 +325,8 @@ impl AnthropicModel {
             params,
             execution_options,
             client,
+            http: clust::reqwest::Client::new(),
+            api_key: anthropic_api_key(credentials)?,
             prompt,
             tools: Arc::new(tools),
             credentials_ident: credentials
@@ -121,41 +340,66 @@ impl AnthropicModel {
         tools: &HashMap<String, Box<dyn Tool>>,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> Vec<ClustMessage> {
-        futures::future::join_all(function_calls.map(|tool_use| {
-            let tags_value = tags.clone();
-            async move {
-                let tool_call = Self::map_tool_call(tool_use);
-                let tool_call = tool_call.map_err(|e| GatewayError::CustomError(e.to_string()));
-                let result = match tool_call {
-                    Ok(tool_call) => {
-                        let result =
-                            handle_tool_call(&tool_call, tools, tx, tags_value.clone()).await;
-                        match result {
-                            Ok(content) => ToolResult::success(tool_use.id.clone(), Some(content)),
-                            Err(e) => ToolResult::error(tool_use.id.clone(), Some(e.to_string())),
+        guard
+            .run_tools(function_calls.map(|tool_use| {
+                let tags_value = tags.clone();
+                async move {
+                    let tool_call = Self::map_tool_call(tool_use);
+                    let tool_call = tool_call.map_err(|e| GatewayError::CustomError(e.to_string()));
+                    let result = match tool_call {
+                        Ok(tool_call) => {
+                            let result =
+                                handle_tool_call(&tool_call, tools, tx, tags_value.clone(), guard)
+                                    .await;
+                            match result {
+                                Ok(output) if !output.images.is_empty() => {
+                                    let mut blocks = vec![ContentBlock::ToolResult(
+                                        ToolResultContentBlock::new(ToolResult::success(
+                                            tool_use.id.clone(),
+                                            Some(output.text),
+                                        )),
+                                    )];
+                                    blocks.extend(output.images.into_iter().map(|image| {
+                                        ContentBlock::Image(ImageContentBlock::from(
+                                            ImageContentSource::base64(
+                                                image_media_type(&image.mime_type),
+                                                image.data,
+                                            ),
+                                        ))
+                                    }));
+                                    return ClustMessage::user(Content::MultipleBlocks(blocks));
+                                }
+                                Ok(output) => {
+                                    ToolResult::success(tool_use.id.clone(), Some(output.text))
+                                }
+                                Err(e) => {
+                                    ToolResult::error(tool_use.id.clone(), Some(e.to_string()))
+                                }
+                            }
                         }
-                    }
-                    Err(e) => {
-                        tracing::error!("Error calling tool ({}): {}", tool_use.id, e);
-                        ToolResult::error(tool_use.id.clone(), Some(e.to_string()))
-                    }
-                };
+                        Err(e) => {
+                            tracing::error!("Error calling tool ({}): {}", tool_use.id, e);
+                            ToolResult::error(tool_use.id.clone(), Some(e.to_string()))
+                        }
+                    };
 
-                ClustMessage::user(result)
-            }
-        }))
-        .await
+                    ClustMessage::user(result)
+                }
+            }))
+            .await
     }
 
-    fn build_request(
+    pub(crate) fn build_request(
         &self,
         system_message: Option<&SystemPrompt>,
         messages: Vec<ClustMessage>,
         stream: bool,
-    ) -> Result<MessagesRequestBody, AnthropicError> {
+        guard: &ToolLoopGuard,
+    ) -> Result<AnthropicRequest, AnthropicError> {
         let model = self.params.model.as_ref().unwrap();
-        let builder = MessagesRequestBuilder::new(**model);
+        let builder = MessagesRequestBuilder::new((**model).clone());
         let model_params = &self.params;
 
         let builder = if let Some(system_message) = system_message {
@@ -199,13 +443,10 @@ impl AnthropicModel {
             builder
         };
 
-        let builder = builder.messages(messages.clone());
-
-        let builder = match stream {
-            true => builder.stream(StreamOption::ReturnStream),
-            false => builder.stream(StreamOption::ReturnOnce),
-        };
-        let builder = if !self.tools.is_empty() {
+        let tool_choice = guard.tool_choice(model_params.tool_choice.as_ref());
+        let sends_tools =
+            !self.tools.is_empty() && anthropic_sends_tools(tool_choice.as_ref(), &messages);
+        let builder = if sends_tools {
             let mut tools: Vec<ToolDefinition> = vec![];
             for (_, tool) in self.tools.clone().iter() {
                 tools.push(tool_definition(tool.deref()));
@@ -216,7 +457,22 @@ impl AnthropicModel {
             builder
         };
 
-        Ok(builder.build())
+        let builder = builder.messages(messages);
+
+        let builder = match stream {
+            true => builder.stream(StreamOption::ReturnStream),
+            false => builder.stream(StreamOption::ReturnOnce),
+        };
+
+        let tool_choice = if sends_tools {
+            anthropic_tool_choice(tool_choice.as_ref(), model_params.parallel_tool_calls)
+        } else {
+            None
+        };
+        Ok(AnthropicRequest {
+            body: builder.build(),
+            tool_choice,
+        })
     }
 
     fn handle_max_tokens_error() -> GatewayError {
@@ -229,17 +485,29 @@ impl AnthropicModel {
         tool_call_states: &[ToolUse],
         usage: Usage,
         stream_content: String,
+        reasoning: Option<ContentBlock>,
         stop_reason: &StopReason,
     ) -> MessagesResponseBody {
-        let content = if tool_call_states.is_empty() {
-            Content::SingleText(stream_content)
-        } else {
-            Content::MultipleBlocks(
-                tool_call_states
-                    .iter()
-                    .map(|t| ContentBlock::ToolUse(ToolUseContentBlock::new(t.clone())))
+        let content = match (reasoning, tool_call_states.is_empty()) {
+            (None, true) => Content::SingleText(stream_content),
+            (reasoning, true) => Content::MultipleBlocks(
+                reasoning
+                    .into_iter()
+                    .chain(std::iter::once(ContentBlock::Text(TextContentBlock::new(
+                        stream_content,
+                    ))))
                     .collect(),
-            )
+            ),
+            (reasoning, false) => Content::MultipleBlocks(
+                reasoning
+                    .into_iter()
+                    .chain(
+                        tool_call_states
+                            .iter()
+                            .map(|t| ContentBlock::ToolUse(ToolUseContentBlock::new(t.clone()))),
+                    )
+                    .collect(),
+            ),
         };
 
         MessagesResponseBody {
@@ -272,6 +540,8 @@ impl AnthropicModel {
         };
         let mut first_response_received = false;
         let mut stream_content = String::new();
+        let mut reasoning_content = String::new();
+        let mut thinking_signature = None;
         let mut response_id = "".to_string();
         loop {
             let r = stream.next().await.transpose();
@@ -300,14 +570,18 @@ impl AnthropicModel {
                             stream_content.push_str(&block.text);
                         }
                         clust::messages::ContentBlockStart::ThinkingContentBlock(thinking) => {
-                            tx.send(Some(ModelEvent::new(
-                                &tracing::Span::current(),
-                                ModelEventType::LlmContent(LLMContentEvent {
-                                    content: format!("thinking: {}", thinking.thinking),
-                                }),
-                            )))
-                            .await
-                            .map_err(|e| GatewayError::CustomError(e.to_string()))?;
+                            if !thinking.thinking.is_empty() {
+                                tx.send(Some(ModelEvent::new(
+                                    &tracing::Span::current(),
+                                    ModelEventType::LlmReasoning(LLMReasoningEvent {
+                                        content: thinking.thinking.clone(),
+                                        signature: None,
+                                    }),
+                                )))
+                                .await
+                                .map_err(|e| GatewayError::CustomError(e.to_string()))?;
+                                reasoning_content.push_str(&thinking.thinking);
+                            }
                         }
                         clust::messages::ContentBlockStart::ToolUseContentBlock(tool_use_block) => {
                             tool_call_states.insert(block.index, tool_use_block.tool_use);
@@ -329,15 +603,29 @@ impl AnthropicModel {
                         clust::messages::ContentBlockDelta::ThinkingDeltaContentBlock(delta) => {
                             tx.send(Some(ModelEvent::new(
                                 &tracing::Span::current(),
-                                ModelEventType::LlmContent(LLMContentEvent {
+                                ModelEventType::LlmReasoning(LLMReasoningEvent {
                                     content: delta.thinking.clone(),
+                                    signature: None,
                                 }),
                             )))
                             .await
                             .map_err(|e| GatewayError::CustomError(e.to_string()))?;
-                            stream_content.push_str(&delta.thinking);
+                            reasoning_content.push_str(&delta.thinking);
+                        }
+                        clust::messages::ContentBlockDelta::SignatureDeltaContentBlock(delta) => {
+                            if let Some(signature) = reasoning_signature(&delta) {
+                                tx.send(Some(ModelEvent::new(
+                                    &tracing::Span::current(),
+                                    ModelEventType::LlmReasoning(LLMReasoningEvent {
+                                        content: String::new(),
+                                        signature: Some(signature.clone()),
+                                    }),
+                                )))
+                                .await
+                                .map_err(|e| GatewayError::CustomError(e.to_string()))?;
+                                thinking_signature = Some(signature);
+                            }
                         }
-                        clust::messages::ContentBlockDelta::SignatureDeltaContentBlock(_) => {}
                         clust::messages::ContentBlockDelta::InputJsonDeltaBlock(
                             input_json_block,
                         ) => {
@@ -373,11 +661,18 @@ impl AnthropicModel {
                         usage.output_tokens = delta.usage.output_tokens;
 
                         if let Some(stop_reason) = delta.delta.stop_reason {
+                            let reasoning = match &thinking_signature {
+                                Some(signature) => {
+                                    Some(thinking_block(&reasoning_content, signature)?)
+                                }
+                                None => None,
+                            };
                             let response = self.build_response(
                                 response_id.clone(),
                                 &tool_call_states.values().cloned().collect::<Vec<_>>(),
                                 usage,
                                 stream_content,
+                                reasoning,
                                 &stop_reason,
                             );
                             return Ok((
@@ -405,12 +700,13 @@ impl AnthropicModel {
     async fn execute_inner(
         &self,
         span: Span,
-        request: MessagesRequestBody,
+        request: AnthropicRequest,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<InnerExecutionResult> {
-        let system_message = request.system.clone();
-        let input_messages = request.messages.clone();
+        let system_message = request.body.system.clone();
+        let input_messages = request.body.messages.clone();
 
         tx.send(Some(ModelEvent::new(
             &span,
@@ -429,14 +725,24 @@ impl AnthropicModel {
         .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
         let response = async move {
-            let result = self.client.create_a_message(request).await;
+            let result = match request.tool_choice {
+                None => self
+                    .client
+                    .create_a_message(request.body, None)
+                    .await
+                    .map_err(messages_err),
+                Some(_) => match post_message(&self.http, &self.api_key, &request).await {
+                    Ok(response) => response.json().await.map_err(custom_err),
+                    Err(e) => Err(e),
+                },
+            };
             let _ = result
                 .as_ref()
                 .map(|response| serde_json::to_value(response).unwrap())
                 .as_ref()
                 .map(JsonValue)
                 .record();
-            let response = result.map_err(custom_err)?;
+            let response = result?;
 
             let span = Span::current();
             span.record("output", serde_json::to_string(&response)?);
@@ -501,7 +807,7 @@ impl AnthropicModel {
                         .await
                         .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                        Ok(InnerExecutionResult::Finish(
+                        Ok(InnerExecutionResult::Finish(Box::new(
                             ChatCompletionMessageWithFinishReason::new(
                                 ChatCompletionMessage {
                                     content: Some(ChatCompletionContent::Text(content.to_owned())),
@@ -510,18 +816,20 @@ impl AnthropicModel {
                                 },
                                 ModelFinishReason::Stop,
                             ),
-                        ))
+                        )))
                     }
                     Content::MultipleBlocks(blocks) => {
                         let mut final_text = String::new();
+                        let mut reasoning_content = None;
+                        let mut signature = None;
                         for b in blocks.iter() {
                             match b {
                                 ContentBlock::Text(text) => {
                                     final_text.push_str(&text.text);
                                 }
                                 ContentBlock::Thinking(thinking) => {
-                                    final_text
-                                        .push_str(&format!("thinking: {}\n\n", thinking.thinking));
+                                    reasoning_content = Some(thinking.thinking.clone());
+                                    signature = reasoning_signature(thinking);
                                 }
                                 _ => {
                                     return Err(ModelError::CustomError(
@@ -552,16 +860,18 @@ impl AnthropicModel {
                         .await
                         .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                        Ok(InnerExecutionResult::Finish(
+                        Ok(InnerExecutionResult::Finish(Box::new(
                             ChatCompletionMessageWithFinishReason::new(
                                 ChatCompletionMessage {
                                     content: Some(ChatCompletionContent::Text(final_text)),
                                     role: "assistant".to_string(),
+                                    reasoning_content,
+                                    reasoning_signature: signature,
                                     ..Default::default()
                                 },
                                 ModelFinishReason::Stop,
                             ),
-                        ))
+                        )))
                     }
                 }
             }
@@ -580,11 +890,17 @@ impl AnthropicModel {
                 let mut messages: Vec<ClustMessage> = vec![ClustMessage::assistant(content)];
                 let mut tool_runs = Vec::new();
                 let mut text_content = None;
+                let mut reasoning_content = None;
+                let mut signature = None;
                 for b in blocks.iter() {
                     match b {
                         ContentBlock::ToolUse(tool) => {
                             tool_runs.push(tool.tool_use.clone());
                         }
+                        ContentBlock::Thinking(thinking) => {
+                            reasoning_content = Some(thinking.thinking.clone());
+                            signature = reasoning_signature(thinking);
+                        }
                         ContentBlock::Text(t) => {
                             // Ignore text for now
                             // messages.push(ClustMessage::assistant(t.text.clone()))
@@ -640,7 +956,7 @@ impl AnthropicModel {
                     .await
                     .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                    Ok(InnerExecutionResult::Finish(
+                    Ok(InnerExecutionResult::Finish(Box::new(
                         ChatCompletionMessageWithFinishReason::new(
                             ChatCompletionMessage {
                                 role: "assistant".to_string(),
@@ -664,16 +980,23 @@ impl AnthropicModel {
                                         })
                                         .collect::<Result<Vec<ToolCall>, GatewayError>>()?,
                                 ),
+                                reasoning_content,
+                                reasoning_signature: signature,
                                 ..Default::default()
                             },
                             ModelFinishReason::ToolCalls,
                         ),
-                    ))
+                    )))
                 } else {
-                    let result_tool_calls =
-                        Self::handle_tool_calls(tool_runs.iter(), &self.tools, tx, tags.clone())
-                            .instrument(tools_span.clone())
-                            .await;
+                    let result_tool_calls = Self::handle_tool_calls(
+                        tool_runs.iter(),
+                        &self.tools,
+                        tx,
+                        tags.clone(),
+                        guard,
+                    )
+                    .instrument(tools_span.clone())
+                    .await;
                     messages.extend(result_tool_calls);
 
                     let conversation_messages = [input_messages, messages].concat();
@@ -698,6 +1021,7 @@ impl AnthropicModel {
             .execution_options
             .max_retries
             .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut guard = ToolLoopGuard::new(self.execution_options.tool_loop.as_ref());
         while let Some((system_message, input_messages)) = calls.pop() {
             let input = serde_json::to_string(&input_messages)?;
             let call_span = create_model_span!(
@@ -710,7 +1034,12 @@ impl AnthropicModel {
             );
 
             let request = self
-                .build_request(system_message.as_ref(), input_messages.clone(), false)
+                .build_request(
+                    system_message.as_ref(),
+                    input_messages.clone(),
+                    false,
+                    &guard,
+                )
                 .map_err(custom_err)?;
             call_span.record(
                 "request",
@@ -721,11 +1050,12 @@ impl AnthropicModel {
             }
 
             match self
-                .execute_inner(call_span.clone(), request, tx, tags.clone())
+                .execute_inner(call_span.clone(), request, tx, tags.clone(), &guard)
                 .await
             {
-                Ok(InnerExecutionResult::Finish(message)) => return Ok(message),
+                Ok(InnerExecutionResult::Finish(message)) => return Ok(*message),
                 Ok(InnerExecutionResult::NextCall((system_prompt, messages))) => {
+                    guard.next_iteration()?;
                     calls.push((system_prompt, messages));
                 }
                 Err(e) => {
@@ -755,6 +1085,7 @@ impl AnthropicModel {
             .execution_options
             .max_retries
             .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut guard = ToolLoopGuard::new(self.execution_options.tool_loop.as_ref());
         while let Some((system_message, input_messages)) = calls.pop() {
             let input = serde_json::to_string(&input_messages)?;
             let call_span = create_model_span!(
@@ -767,7 +1098,12 @@ impl AnthropicModel {
             );
 
             let request = self
-                .build_request(system_message.as_ref(), input_messages.clone(), true)
+                .build_request(
+                    system_message.as_ref(),
+                    input_messages.clone(),
+                    true,
+                    &guard,
+                )
                 .map_err(custom_err)?;
             call_span.record(
                 "request",
@@ -778,11 +1114,12 @@ impl AnthropicModel {
             }
 
             match self
-                .execute_stream_inner(request, call_span.clone(), tx, tags.clone())
+                .execute_stream_inner(request, call_span.clone(), tx, tags.clone(), &guard)
                 .await
             {
                 Ok(InnerExecutionResult::Finish(_)) => return Ok(()),
                 Ok(InnerExecutionResult::NextCall((system_prompt, messages))) => {
+                    guard.next_iteration()?;
                     calls.push((system_prompt, messages));
                 }
                 Err(e) => {
@@ -836,13 +1173,14 @@ impl AnthropicModel {
 
     async fn execute_stream_inner(
         &self,
-        request: MessagesRequestBody,
+        request: AnthropicRequest,
         span: Span,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<InnerExecutionResult> {
-        let system_message = request.system.clone();
-        let input_messages = request.messages.clone();
+        let system_message = request.body.system.clone();
+        let input_messages = request.body.messages.clone();
         let credentials_ident = self.credentials_ident.clone();
 
         tx.send(Some(ModelEvent::new(
@@ -862,11 +1200,18 @@ impl AnthropicModel {
         .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
         let started_at = std::time::Instant::now();
-        let stream = self
-            .client
-            .create_a_message_stream(request)
-            .await
-            .map_err(custom_err)?;
+        // clust can only send the requests without a tool choice
+        let stream = match request.tool_choice {
+            None => self
+                .client
+                .create_a_message_stream(request.body, None)
+                .await
+                .map_err(messages_err)?
+                .boxed(),
+            Some(_) => {
+                chunk_stream(post_message(&self.http, &self.api_key, &request).await?).boxed()
+            }
+        };
         let (stop_reason, tool_calls, usage, response) = self
             .process_stream(stream, tx, started_at)
             .instrument(span.clone())
@@ -908,12 +1253,12 @@ impl AnthropicModel {
 
         match stop_reason {
             StopReason::EndTurn | StopReason::StopSequence => Ok(InnerExecutionResult::Finish(
-                ChatCompletionMessageWithFinishReason::new(
+                Box::new(ChatCompletionMessageWithFinishReason::new(
                     ChatCompletionMessage {
                         ..Default::default()
                     },
                     ModelFinishReason::Stop,
-                ),
+                )),
             )),
             StopReason::MaxTokens => Err(Self::handle_max_tokens_error()),
             StopReason::ToolUse => {
@@ -933,25 +1278,26 @@ impl AnthropicModel {
                     .await
                     .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                    Ok(InnerExecutionResult::Finish(
+                    Ok(InnerExecutionResult::Finish(Box::new(
                         ChatCompletionMessageWithFinishReason::new(
                             ChatCompletionMessage {
                                 ..Default::default()
                             },
                             ModelFinishReason::ToolCalls,
                         ),
-                    ))
+                    )))
                 } else {
-                    let mut messages = vec![ClustMessage::assistant(Content::MultipleBlocks(
-                        tool_calls
-                            .iter()
-                            .map(|t| ContentBlock::ToolUse(ToolUseContentBlock::new(t.clone())))
-                            .collect(),
-                    ))];
-                    let result_tool_calls =
-                        Self::handle_tool_calls(tool_calls.iter(), &self.tools, tx, tags.clone())
-                            .instrument(tools_span.clone())
-                            .await;
+                    // Response content keeps the signed thinking block ahead of the tool uses
+                    let mut messages = vec![ClustMessage::assistant(response.content.clone())];
+                    let result_tool_calls = Self::handle_tool_calls(
+                        tool_calls.iter(),
+                        &self.tools,
+                        tx,
+                        tags.clone(),
+                        guard,
+                    )
+                    .instrument(tools_span.clone())
+                    .await;
                     messages.extend(result_tool_calls);
 
                     let conversation_messages = [input_messages, messages].concat();
@@ -976,24 +1322,41 @@ impl AnthropicModel {
             match m.r#type {
                 MessageType::SystemMessage => {}
                 MessageType::AIMessage => {
+                    // Thinking blocks can only be replayed together with their signature
+                    let reasoning = match (&m.reasoning_content, &m.reasoning_signature) {
+                        (Some(thinking), Some(signature)) => {
+                            Some(thinking_block(thinking, signature)?)
+                        }
+                        _ => None,
+                    };
+
                     if let Some(tool_calls) = &m.tool_calls {
                         tool_results_remaining = tool_calls.len();
                         tool_calls_collected = vec![];
 
+                        let tool_blocks = tool_calls
+                            .iter()
+                            .map(|t| {
+                                Ok(ContentBlock::ToolUse(ToolUseContentBlock::new(
+                                    ToolUse::new(
+                                        t.id.clone(),
+                                        t.function.name.clone(),
+                                        serde_json::from_str(&t.function.arguments)?,
+                                    ),
+                                )))
+                            })
+                            .collect::<Result<Vec<ContentBlock>, GatewayError>>()?;
+
                         messages.push(ClustMessage::assistant(Content::MultipleBlocks(
-                            tool_calls
-                                .iter()
-                                .map(|t| {
-                                    Ok(ContentBlock::ToolUse(ToolUseContentBlock::new(
-                                        ToolUse::new(
-                                            t.id.clone(),
-                                            t.function.name.clone(),
-                                            serde_json::from_str(&t.function.arguments)?,
-                                        ),
-                                    )))
-                                })
-                                .collect::<Result<Vec<ContentBlock>, GatewayError>>()?,
+                            reasoning.into_iter().chain(tool_blocks).collect(),
                         )));
+                    } else if let Some(reasoning) = reasoning {
+                        messages.push(ClustMessage::assistant(Content::MultipleBlocks(vec![
+                            reasoning,
+                            ContentBlock::Text(TextContentBlock::new(
+                                m.content.clone().unwrap_or_default(),
+                            )),
+                        ])));
                     } else {
                         messages.push(ClustMessage::assistant(Content::SingleText(
                             m.content.clone().unwrap_or_default(),
@@ -1001,7 +1364,7 @@ impl AnthropicModel {
                     }
                 }
                 MessageType::HumanMessage => {
-                    messages.push(construct_user_message(&m.clone().into()));
+                    messages.push(construct_user_message(&m.clone().into())?);
                 }
                 MessageType::ToolResult => {
                     tool_results_remaining -= 1;
@@ -1033,6 +1396,11 @@ impl ModelInstance for AnthropicModel {
         previous_messages: Vec<Message>,
         tags: HashMap<String, String>,
     ) -> GatewayResult<ChatCompletionMessageWithFinishReason> {
+        let previous_messages = resolve_images(
+            previous_messages,
+            &self.execution_options.images.clone().unwrap_or_default(),
+        )
+        .await?;
         let (system_prompt, conversational_messages) =
             self.construct_messages(input_variables, previous_messages)?;
         self.execute(system_prompt, conversational_messages, &tx, tags)
@@ -1046,6 +1414,11 @@ impl ModelInstance for AnthropicModel {
         previous_messages: Vec<Message>,
         tags: HashMap<String, String>,
     ) -> GatewayResult<()> {
+        let previous_messages = resolve_images(
+            previous_messages,
+            &self.execution_options.images.clone().unwrap_or_default(),
+        )
+        .await?;
         let (system_prompt, conversational_messages) =
             self.construct_messages(input_variables, previous_messages)?;
         self.execute_stream(system_prompt, conversational_messages, &tx, tags)
@@ -1141,7 +1514,7 @@ fn map_chat_messages(
             } else {
                 InnerMessage::Text(Prompt::render(msg.clone(), variables))
             };
-            construct_user_message(&inner_message)
+            construct_user_message(&inner_message)?
         }
         _ => {
             return Err(GatewayError::CustomError(
@@ -1152,7 +1525,55 @@ fn map_chat_messages(
     Ok(message)
 }
 
-fn construct_user_message(m: &InnerMessage) -> ClustMessage {
+fn image_media_type(mime_type: &str) -> clust::messages::ImageMediaType {
+    match mime_type {
+        "image/jpeg" => clust::messages::ImageMediaType::Jpeg,
+        "image/gif" => clust::messages::ImageMediaType::Gif,
+        "image/webp" => clust::messages::ImageMediaType::Webp,
+        _ => clust::messages::ImageMediaType::Png,
+    }
+}
+
+/// Maps a file part to a document block, falling back to the extracted text
+/// for files Anthropic can't take as a document.
+fn document_block(part: &MessageContentPart) -> GatewayResult<ContentBlock> {
+    let file = FilePart::from_message_part(part)
+        .ok_or(ModelError::CustomError("Invalid file content".to_string()))?;
+    let source = match &file.source {
+        FileSource::Base64(data) if file.is_pdf() => Some(serde_json::json!({
+            "type": "base64",
+            "media_type": file.mime_type,
+            "data": data,
+        })),
+        FileSource::Url(url) if file.is_pdf() => Some(serde_json::json!({
+            "type": "url",
+            "url": url,
+        })),
+        FileSource::Base64(_) if file.is_text() => Some(serde_json::json!({
+            "type": "text",
+            "media_type": "text/plain",
+            "data": String::from_utf8_lossy(&file.bytes()?),
+        })),
+        _ => None,
+    };
+
+    let document = source.and_then(|source| {
+        let mut document = serde_json::json!({ "type": "document", "source": source });
+        if let Some(filename) = file.filename {
+            document["title"] = Value::String(filename.to_string());
+        }
+        serde_json::from_value::<ContentBlock>(document).ok()
+    });
+
+    match document {
+        Some(document) => Ok(document),
+        None => Ok(ContentBlock::Text(TextContentBlock::new(
+            file.extract_text()?,
+        ))),
+    }
+}
+
+fn construct_user_message(m: &InnerMessage) -> GatewayResult<ClustMessage> {
     let content = match m {
         crate::types::threads::InnerMessage::Text(text) => Content::SingleText(text.to_owned()),
         crate::types::threads::InnerMessage::Array(content_array) => {
@@ -1174,18 +1595,16 @@ fn construct_user_message(m: &InnerMessage) -> ClustMessage {
                         }
                     }
                     crate::types::threads::MessageContentType::ImageUrl => {
-                        let url = m.value.clone();
-                        let base64_data = url
-                            .split_once(',')
-                            .map_or_else(|| url.as_str(), |(_, data)| data);
+                        let image = ImageData::from_url(&m.value)?;
                         ContentBlock::Image(ImageContentBlock::from(ImageContentSource::base64(
-                            clust::messages::ImageMediaType::Png,
-                            base64_data,
+                            image_media_type(&image.mime_type),
+                            image.data,
                         )))
                     }
                     crate::types::threads::MessageContentType::InputAudio => {
                         todo!()
                     }
+                    crate::types::threads::MessageContentType::File => document_block(m)?,
                 };
                 blocks.push(msg)
             }
@@ -1194,10 +1613,46 @@ fn construct_user_message(m: &InnerMessage) -> ClustMessage {
         }
     };
 
-    ClustMessage::user(content)
+    Ok(ClustMessage::user(content))
 }
 
 pub fn record_map_err(e: impl Into<GatewayError> + ToString, span: tracing::Span) -> GatewayError {
     span.record("error", e.to_string());
     e.into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_chunk() {
+        let chunk = parse_chunk(
+            "event: content_block_delta\ndata: {\"type\": \"content_block_delta\", \"index\": 0, \"delta\": {\"type\": \"text_delta\", \"text\": \"Hi\"}}\n\n",
+        )
+        .unwrap();
+        assert!(matches!(chunk, Some(MessageChunk::ContentBlockDelta(_))));
+
+        let ping = parse_chunk("event: ping\ndata: {\"type\": \"ping\"}\n\n").unwrap();
+        assert!(matches!(ping, Some(MessageChunk::Ping(_))));
+        assert!(parse_chunk("event: unknown\ndata: {}\n\n")
+            .unwrap()
+            .is_none());
+        assert!(parse_chunk(
+            "event: error\ndata: {\"type\": \"error\", \"error\": {\"type\": \"overloaded_error\"}}"
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_uses_one_hour_cache() {
+        let body = serde_json::json!({
+            "messages": [{
+                "role": "user",
+                "content": [{ "type": "text", "text": "Hi", "cache_control": { "type": "ephemeral", "ttl": "1h" } }]
+            }]
+        });
+        assert!(uses_one_hour_cache(&body));
+        assert!(!uses_one_hour_cache(&serde_json::json!({ "messages": [] })));
+    }
+}
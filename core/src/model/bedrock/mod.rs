@@ -1,12 +1,14 @@
 use super::types::{
-    LLMContentEvent, LLMFinishEvent, LLMStartEvent, ModelEvent, ModelEventType, ModelFinishReason,
-    ModelToolCall,
+    LLMContentEvent, LLMFinishEvent, LLMReasoningEvent, LLMStartEvent, ModelEvent, ModelEventType,
+    ModelFinishReason, ModelToolCall,
 };
 use super::{CredentialsIdent, ModelInstance};
 use crate::error::GatewayError;
 use crate::events::{self, JsonValue, RecordResult, SPAN_BEDROCK};
 use crate::model::error::{BedrockError, ModelFinishError};
-use crate::model::handler::handle_tool_call;
+use crate::model::files::FilePart;
+use crate::model::handler::{handle_tool_call, ToolLoopGuard};
+use crate::model::images::{resolve_images, ImageData};
 use crate::model::types::LLMFirstToken;
 use crate::model::DEFAULT_MAX_RETRIES;
 use crate::model::{ModelProviderInstance, Tool as LangdbTool};
@@ -18,12 +20,13 @@ use crate::types::credentials::BedrockCredentials;
 use crate::types::engine::{BedrockModelParams, ExecutionOptions, Prompt};
 use crate::types::gateway::{
     ChatCompletionContent, ChatCompletionMessage, ChatCompletionMessageWithFinishReason,
-    CompletionModelUsage, ToolCall,
+    CompletionModelUsage, GuardrailStreamProcessingMode, PromptTokensDetails, ToolCall, ToolChoice,
+    ToolChoiceMode,
 };
 use crate::types::message::{MessageType, PromptMessage};
 use crate::types::provider::{CompletionModelPrice, InferenceModelProvider, ModelPrice};
-use crate::types::threads::InnerMessage;
 use crate::types::threads::Message as LMessage;
+use crate::types::threads::{InnerMessage, MessageContentPart};
 use crate::{create_model_span, GatewayApiError, GatewayResult};
 use async_trait::async_trait;
 use aws_config::{BehaviorVersion, SdkConfig};
@@ -35,14 +38,19 @@ use aws_sdk_bedrockruntime::operation::converse_stream::{self, ConverseStreamErr
 use aws_sdk_bedrockruntime::types::builders::ImageBlockBuilder;
 use aws_sdk_bedrockruntime::types::ConverseOutput::Message as MessageVariant;
 use aws_sdk_bedrockruntime::types::{
-    ContentBlock, ContentBlockDelta, ContentBlockStart, ConversationRole, ConverseOutput,
-    ConverseStreamOutput, InferenceConfiguration, Message, ReasoningContentBlock, StopReason,
-    SystemContentBlock, TokenUsage, Tool, ToolConfiguration, ToolInputSchema, ToolResultBlock,
-    ToolResultContentBlock, ToolResultStatus, ToolSpecification, ToolUseBlock,
+    AnyToolChoice, AutoToolChoice, CachePointBlock, CachePointType, ContentBlock,
+    ContentBlockDelta, ContentBlockStart, ConversationRole, ConverseOutput,
+    ConverseStreamMetadataEvent, ConverseStreamOutput, DocumentBlock, DocumentFormat,
+    DocumentSource, GuardrailConfiguration, GuardrailStreamConfiguration,
+    GuardrailStreamProcessingMode as BedrockGuardrailStreamProcessingMode, GuardrailTrace,
+    ImageFormat, InferenceConfiguration, Message, ReasoningContentBlock,
+    ReasoningContentBlockDelta, ReasoningTextBlock, SpecificToolChoice, StopReason,
+    SystemContentBlock, TokenUsage, Tool, ToolChoice as BedrockToolChoice, ToolConfiguration,
+    ToolInputSchema, ToolResultBlock, ToolResultContentBlock, ToolResultStatus, ToolSpecification,
+    ToolUseBlock,
 };
 use aws_sdk_bedrockruntime::Client;
 use aws_smithy_types::{Blob, Document};
-use base64::Engine;
 use serde::de::IntoDeserializer;
 use serde::Deserialize;
 use serde::Serialize;
@@ -69,10 +77,45 @@ macro_rules! target {
 }
 
 enum InnerExecutionResult {
-    Finish(ChatCompletionMessageWithFinishReason),
+    Finish(Box<ChatCompletionMessageWithFinishReason>),
     NextCall(Vec<Message>),
 }
 
+fn reasoning_block(text: &str, signature: Option<String>) -> Result<ContentBlock, ModelError> {
+    Ok(ContentBlock::ReasoningContent(
+        ReasoningContentBlock::ReasoningText(
+            ReasoningTextBlock::builder()
+                .text(text)
+                .set_signature(signature)
+                .build()
+                .map_err(build_err)?,
+        ),
+    ))
+}
+
+/// Maps the OpenAI style tool choice onto the Converse tool choice.
+/// Converse has no `none` mode and no switch for parallel tool use, so `none` is
+/// handled by [`BedrockModel::get_tools_config`].
+pub(crate) fn bedrock_tool_choice(
+    tool_choice: Option<&ToolChoice>,
+) -> Result<Option<BedrockToolChoice>, ModelError> {
+    Ok(match tool_choice {
+        None | Some(ToolChoice::Mode(ToolChoiceMode::None)) => None,
+        Some(ToolChoice::Mode(ToolChoiceMode::Auto)) => {
+            Some(BedrockToolChoice::Auto(AutoToolChoice::builder().build()))
+        }
+        Some(ToolChoice::Mode(ToolChoiceMode::Required)) => {
+            Some(BedrockToolChoice::Any(AnyToolChoice::builder().build()))
+        }
+        Some(ToolChoice::Named(named)) => Some(BedrockToolChoice::Tool(
+            SpecificToolChoice::builder()
+                .name(named.function.name.clone())
+                .build()
+                .map_err(build_err)?,
+        )),
+    })
+}
+
 fn build_err(e: impl ToString) -> ModelError {
     ModelError::CustomError(e.to_string())
 }
@@ -170,6 +213,13 @@ impl BedrockModel {
             if m.r#type == MessageType::SystemMessage {
                 if let Some(content) = m.content.clone() {
                     system_messages.push(SystemContentBlock::Text(content));
+                } else {
+                    for part in &m.content_array {
+                        system_messages.push(SystemContentBlock::Text(part.value.clone()));
+                        if part.cache_control.is_some() {
+                            system_messages.push(SystemContentBlock::CachePoint(cache_point()?));
+                        }
+                    }
                 }
             }
         }
@@ -199,10 +249,23 @@ impl BedrockModel {
             let message = match m.r#type {
                 MessageType::AIMessage => {
                     let mut contents = vec![];
+                    // Reasoning can only be replayed together with its signature
+                    if let (Some(reasoning), Some(signature)) =
+                        (&m.reasoning_content, &m.reasoning_signature)
+                    {
+                        contents.push(reasoning_block(reasoning, Some(signature.clone()))?);
+                    }
                     if let Some(content) = m.content.clone() {
                         if !content.is_empty() {
                             contents.push(ContentBlock::Text(content));
                         }
+                    } else {
+                        for part in &m.content_array {
+                            contents.push(ContentBlock::Text(part.value.clone()));
+                            if part.cache_control.is_some() {
+                                contents.push(ContentBlock::CachePoint(cache_point()?));
+                            }
+                        }
                     }
                     if let Some(tool_calls) = m.tool_calls.clone() {
                         tool_results_expected = tool_calls.len();
@@ -317,27 +380,50 @@ impl BedrockModel {
         tools: &HashMap<String, Box<dyn LangdbTool>>,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<Message> {
-        let content = futures::future::join_all(tool_uses.iter().map(|tool| {
-            let tags_value = tags.clone();
-            async move {
-                let tool_use_id = tool.tool_use_id.clone();
-                tracing::trace!("Calling tool ({tool_use_id}) {:?}", tool.name);
-                let tool_call = Self::map_tool_call(tool)?;
-                let result = handle_tool_call(&tool_call, tools, tx, tags_value.clone()).await;
-                tracing::trace!("Result ({tool_use_id}): {result:?}");
-                let content = result.unwrap_or_else(|err| err.to_string());
-                Ok(ContentBlock::ToolResult(
-                    ToolResultBlock::builder()
-                        .tool_use_id(tool_use_id.clone())
-                        .content(ToolResultContentBlock::Text(content))
-                        .status(ToolResultStatus::Success)
-                        .build()
-                        .unwrap(),
-                ))
-            }
-        }))
-        .await;
+        let content = guard
+            .run_tools(tool_uses.iter().map(|tool| {
+                let tags_value = tags.clone();
+                async move {
+                    let tool_use_id = tool.tool_use_id.clone();
+                    tracing::trace!("Calling tool ({tool_use_id}) {:?}", tool.name);
+                    let tool_call = Self::map_tool_call(tool)?;
+                    let result =
+                        handle_tool_call(&tool_call, tools, tx, tags_value.clone(), guard).await;
+                    tracing::trace!("Result ({tool_use_id}): {result:?}");
+                    let (content, status) = match result {
+                        Ok(output) => {
+                            let mut content = vec![ToolResultContentBlock::Text(output.text)];
+                            for image in output.images {
+                                content.push(ToolResultContentBlock::Image(
+                                    ImageBlockBuilder::default()
+                                        .format(image_format(&image.mime_type))
+                                        .source(aws_sdk_bedrockruntime::types::ImageSource::Bytes(
+                                            Blob::new(image.bytes()?),
+                                        ))
+                                        .build()
+                                        .map_err(build_err)?,
+                                ));
+                            }
+                            (content, ToolResultStatus::Success)
+                        }
+                        Err(err) => (
+                            vec![ToolResultContentBlock::Text(err.to_string())],
+                            ToolResultStatus::Error,
+                        ),
+                    };
+                    Ok(ContentBlock::ToolResult(
+                        ToolResultBlock::builder()
+                            .tool_use_id(tool_use_id.clone())
+                            .set_content(Some(content))
+                            .status(status)
+                            .build()
+                            .unwrap(),
+                    ))
+                }
+            }))
+            .await;
 
         let c = content
             .into_iter()
@@ -349,10 +435,28 @@ impl BedrockModel {
             .unwrap())
     }
 
-    pub(crate) fn get_tools_config(&self) -> Result<Option<ToolConfiguration>, GatewayError> {
+    /// Converse rejects `toolUse`/`toolResult` blocks without a tool config, so `none` only
+    /// drops the tools until the conversation has used them and otherwise just stops forcing.
+    pub(crate) fn get_tools_config(
+        &self,
+        messages: &[Message],
+        guard: &ToolLoopGuard,
+    ) -> Result<Option<ToolConfiguration>, GatewayError> {
         if self.tools.is_empty() {
             return Ok(None);
         }
+        let tool_choice = guard.tool_choice(self.params.tool_choice.as_ref());
+        if tool_choice == Some(ToolChoice::Mode(ToolChoiceMode::None)) {
+            let used_tools = messages.iter().flat_map(|m| m.content()).any(|block| {
+                matches!(
+                    block,
+                    ContentBlock::ToolUse(_) | ContentBlock::ToolResult(_)
+                )
+            });
+            if !used_tools {
+                return Ok(None);
+            }
+        }
 
         let mut tools = vec![];
 
@@ -378,41 +482,82 @@ impl BedrockModel {
 
         let config = ToolConfiguration::builder()
             .set_tools(Some(tools))
+            .set_tool_choice(bedrock_tool_choice(tool_choice.as_ref())?)
             .build()
             .map_err(build_err)?;
 
         Ok(Some(config))
     }
 
-    pub fn build_request(
-        &self,
-        input_messages: &[Message],
-        system_messages: &[SystemContentBlock],
-    ) -> GatewayResult<ConverseFluentBuilder> {
+    fn inference_config(&self) -> InferenceConfiguration {
         let model_params = &self.params;
-        let inference_config = InferenceConfiguration::builder()
+        InferenceConfiguration::builder()
             .set_max_tokens(model_params.max_tokens)
             .set_temperature(model_params.temperature)
             .set_top_p(model_params.top_p)
             .set_stop_sequences(model_params.stop_sequences.clone())
-            .build();
+            .build()
+    }
+
+    fn guardrail_config(&self) -> Result<Option<GuardrailConfiguration>, ModelError> {
+        let Some(config) = &self.params.guardrail_config else {
+            return Ok(None);
+        };
+
+        let config = GuardrailConfiguration::builder()
+            .guardrail_identifier(&config.identifier)
+            .guardrail_version(&config.version)
+            .trace(guardrail_trace(config.trace))
+            .build()
+            .map_err(build_err)?;
+        Ok(Some(config))
+    }
+
+    fn guardrail_stream_config(&self) -> Result<Option<GuardrailStreamConfiguration>, ModelError> {
+        let Some(config) = &self.params.guardrail_config else {
+            return Ok(None);
+        };
+
+        let config = GuardrailStreamConfiguration::builder()
+            .guardrail_identifier(&config.identifier)
+            .guardrail_version(&config.version)
+            .trace(guardrail_trace(config.trace))
+            .set_stream_processing_mode(config.stream_processing_mode.map(|mode| match mode {
+                GuardrailStreamProcessingMode::Sync => BedrockGuardrailStreamProcessingMode::Sync,
+                GuardrailStreamProcessingMode::Async => BedrockGuardrailStreamProcessingMode::Async,
+            }))
+            .build()
+            .map_err(build_err)?;
+        Ok(Some(config))
+    }
 
+    fn additional_model_request_fields(&self) -> GatewayResult<Document> {
+        Ok(Document::deserialize(
+            self.params
+                .additional_parameters
+                .clone()
+                .into_deserializer(),
+        )?)
+    }
+
+    pub(crate) fn build_request(
+        &self,
+        input_messages: &[Message],
+        system_messages: &[SystemContentBlock],
+        guard: &ToolLoopGuard,
+    ) -> GatewayResult<ConverseFluentBuilder> {
         tracing::warn!("Bedrock Model name: {}", self.model_name);
 
         Ok(self
             .client
             .converse()
             .set_system(Some(system_messages.to_vec()))
-            .set_tool_config(self.get_tools_config()?)
+            .set_tool_config(self.get_tools_config(input_messages, guard)?)
             .model_id(replace_version(&self.model_name))
             .set_messages(Some(input_messages.to_vec()))
-            .additional_model_request_fields(Document::deserialize(
-                model_params
-                    .additional_parameters
-                    .clone()
-                    .into_deserializer(),
-            )?)
-            .set_inference_config(Some(inference_config)))
+            .additional_model_request_fields(self.additional_model_request_fields()?)
+            .set_inference_config(Some(self.inference_config()))
+            .set_guardrail_config(self.guardrail_config()?))
     }
 
     async fn execute(
@@ -428,6 +573,7 @@ impl BedrockModel {
             .execution_options
             .max_retries
             .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut guard = ToolLoopGuard::new(self.execution_options.tool_loop.as_ref());
         while let Some(input_messages) = calls.pop() {
             let input = serde_json::json!({
                 "initial_messages": format!("{input_messages:?}"),
@@ -439,17 +585,19 @@ impl BedrockModel {
                 tags,
                 retries_left,
                 input = JsonValue(&input).as_value(),
-                system_prompt = field::Empty
+                system_prompt = field::Empty,
+                guardrail_trace = field::Empty
             );
 
-            let builder = self.build_request(&input_messages, &system_messages)?;
+            let builder = self.build_request(&input_messages, &system_messages, &guard)?;
             let response = self
-                .execute_inner(builder, span.clone(), tx, tags.clone())
+                .execute_inner(builder, span.clone(), tx, tags.clone(), &guard)
                 .await;
 
             match response {
-                Ok(InnerExecutionResult::Finish(message)) => return Ok(message),
+                Ok(InnerExecutionResult::Finish(message)) => return Ok(*message),
                 Ok(InnerExecutionResult::NextCall(messages)) => {
+                    guard.next_iteration()?;
                     calls.push(messages);
                 }
                 Err(e) => {
@@ -472,6 +620,7 @@ impl BedrockModel {
         span: Span,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<InnerExecutionResult> {
         let input_messages = builder.get_messages().clone().unwrap_or_default();
         tx.send(Some(ModelEvent::new(
@@ -497,6 +646,9 @@ impl BedrockModel {
             let span = Span::current();
 
             span.record("output", format!("{response:?}"));
+            if let Some(trace) = response.trace().and_then(|t| t.guardrail()) {
+                span.record("guardrail_trace", format!("{trace:?}"));
+            }
             if let Some(ref usage) = response.usage {
                 span.record(
                     "usage",
@@ -516,17 +668,32 @@ impl BedrockModel {
         match response.stop_reason {
             StopReason::EndTurn | StopReason::StopSequence => match response.output {
                 Some(MessageVariant(message)) => {
-                    let usage = response.usage.as_ref().map(|usage| CompletionModelUsage {
-                        input_tokens: usage.input_tokens as u32,
-                        output_tokens: usage.output_tokens as u32,
-                        total_tokens: usage.total_tokens as u32,
-                        ..Default::default()
-                    });
+                    let usage = Self::map_usage(response.usage.as_ref());
+
+                    let mut text = None;
+                    let mut reasoning_content = None;
+                    let mut reasoning_signature = None;
+                    for block in &message.content {
+                        match block {
+                            ContentBlock::Text(t) => {
+                                text.get_or_insert_with(String::new).push_str(t);
+                            }
+                            ContentBlock::ReasoningContent(
+                                ReasoningContentBlock::ReasoningText(reasoning),
+                            ) => {
+                                reasoning_content = Some(reasoning.text().to_string());
+                                reasoning_signature = reasoning.signature().map(|s| s.to_string());
+                            }
+                            _ => {}
+                        }
+                    }
 
-                    let output = match message.content.first() {
-                        Some(ContentBlock::Text(message)) => Some(message.clone()),
-                        _ => None,
-                    };
+                    if text.is_none() && reasoning_content.is_none() {
+                        return Err(ModelError::FinishError(
+                            ModelFinishError::ContentBlockNotInTextFormat,
+                        )
+                        .into());
+                    }
 
                     tx.send(Some(ModelEvent::new(
                         &span,
@@ -538,7 +705,7 @@ impl BedrockModel {
                                 .clone()
                                 .map(|m| m.to_string())
                                 .unwrap_or_default(),
-                            output,
+                            output: text.clone(),
                             usage,
                             finish_reason: ModelFinishReason::Stop,
                             tool_calls: vec![],
@@ -548,39 +715,20 @@ impl BedrockModel {
                     .await
                     .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                    let message = message.content.first().ok_or(ModelError::CustomError(
-                        "Content Block Not Found".to_string(),
-                    ))?;
-                    match message {
-                        ContentBlock::Text(content) => Ok(InnerExecutionResult::Finish(
-                            ChatCompletionMessageWithFinishReason::new(
-                                ChatCompletionMessage {
-                                    role: "assistant".to_string(),
-                                    content: Some(ChatCompletionContent::Text(content.clone())),
-                                    ..Default::default()
-                                },
-                                ModelFinishReason::Stop,
-                            ),
-                        )),
-                        ContentBlock::ReasoningContent(ReasoningContentBlock::ReasoningText(
-                            content,
-                        )) => Ok(InnerExecutionResult::Finish(
-                            ChatCompletionMessageWithFinishReason::new(
-                                ChatCompletionMessage {
-                                    role: "assistant".to_string(),
-                                    content: Some(ChatCompletionContent::Text(
-                                        content.text().to_string(),
-                                    )),
-                                    ..Default::default()
-                                },
-                                ModelFinishReason::Stop,
-                            ),
-                        )),
-                        _ => Err(ModelError::FinishError(
-                            ModelFinishError::ContentBlockNotInTextFormat,
-                        )
-                        .into()),
-                    }
+                    Ok(InnerExecutionResult::Finish(Box::new(
+                        ChatCompletionMessageWithFinishReason::new(
+                            ChatCompletionMessage {
+                                role: "assistant".to_string(),
+                                content: Some(ChatCompletionContent::Text(
+                                    text.unwrap_or_default(),
+                                )),
+                                reasoning_content,
+                                reasoning_signature,
+                                ..Default::default()
+                            },
+                            ModelFinishReason::Stop,
+                        ),
+                    )))
                 }
                 _ => Err(ModelError::FinishError(ModelFinishError::NoOutputProvided).into()),
             },
@@ -595,6 +743,8 @@ impl BedrockModel {
                             let mut messages = vec![message.clone()];
                             let mut text = String::new();
                             let mut tool_uses = vec![];
+                            let mut reasoning_content = None;
+                            let mut reasoning_signature = None;
 
                             for m in message.content {
                                 match m {
@@ -602,6 +752,13 @@ impl BedrockModel {
                                     ContentBlock::ToolUse(tool_use) => {
                                         tool_uses.push(tool_use);
                                     }
+                                    ContentBlock::ReasoningContent(
+                                        ReasoningContentBlock::ReasoningText(reasoning),
+                                    ) => {
+                                        reasoning_content = Some(reasoning.text().to_string());
+                                        reasoning_signature =
+                                            reasoning.signature().map(|s| s.to_string());
+                                    }
                                     _ => {}
                                 }
                             }
@@ -639,13 +796,7 @@ impl BedrockModel {
                                     .join(","),
                             );
                             if tool.stop_at_call() {
-                                let usage =
-                                    response.usage.as_ref().map(|usage| CompletionModelUsage {
-                                        input_tokens: usage.input_tokens as u32,
-                                        output_tokens: usage.output_tokens as u32,
-                                        total_tokens: usage.total_tokens as u32,
-                                        ..Default::default()
-                                    });
+                                let usage = Self::map_usage(response.usage.as_ref());
 
                                 tx.send(Some(ModelEvent::new(
                                     &span,
@@ -671,23 +822,26 @@ impl BedrockModel {
                                 .await
                                 .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                                Ok(InnerExecutionResult::Finish(
+                                Ok(InnerExecutionResult::Finish(Box::new(
                                     ChatCompletionMessageWithFinishReason::new(
                                         ChatCompletionMessage {
                                             role: "assistant".to_string(),
                                             tool_calls: Some(tool_calls),
                                             content: content.map(ChatCompletionContent::Text),
+                                            reasoning_content,
+                                            reasoning_signature,
                                             ..Default::default()
                                         },
                                         ModelFinishReason::ToolCalls,
                                     ),
-                                ))
+                                )))
                             } else {
                                 let tools_message = Self::handle_tool_calls(
                                     tool_uses,
                                     &self.tools,
                                     tx,
                                     tags.clone(),
+                                    guard,
                                 )
                                 .instrument(tools_span.clone())
                                 .await?;
@@ -727,6 +881,8 @@ impl BedrockModel {
         let mut tool_uses: HashMap<i32, ToolUseBlock> = HashMap::new();
         let mut usage: Option<TokenUsage> = None;
         let mut accumulated_text = String::new();
+        let mut accumulated_reasoning = String::new();
+        let mut reasoning_signature = None;
         let mut first_response_received = false;
         while let Some(result) = stream.recv().await.transpose() {
             let output = result.map_err(|e| ModelError::Bedrock(Box::new(e.into())))?;
@@ -753,6 +909,31 @@ impl BedrockModel {
                             .await
                             .unwrap();
                         }
+                        Some(ContentBlockDelta::ReasoningContent(reasoning)) => {
+                            let event = match reasoning {
+                                ReasoningContentBlockDelta::Text(t) => {
+                                    accumulated_reasoning.push_str(&t);
+                                    LLMReasoningEvent {
+                                        content: t,
+                                        signature: None,
+                                    }
+                                }
+                                ReasoningContentBlockDelta::Signature(signature) => {
+                                    reasoning_signature = Some(signature.clone());
+                                    LLMReasoningEvent {
+                                        content: String::new(),
+                                        signature: Some(signature),
+                                    }
+                                }
+                                _ => continue,
+                            };
+                            tx.send(Some(ModelEvent::new(
+                                &Span::current(),
+                                ModelEventType::LlmReasoning(event),
+                            )))
+                            .await
+                            .map_err(|e| GatewayError::CustomError(e.to_string()))?;
+                        }
                         Some(ContentBlockDelta::ToolUse(tool_use)) => {
                             tool_uses.entry(a.content_block_index).and_modify(|t| {
                                 let Document::String(ref mut s) = t.input else {
@@ -800,10 +981,17 @@ impl BedrockModel {
                 }
                 ConverseStreamOutput::MessageStop(event) => {
                     if let Ok(Some(ConverseStreamOutput::Metadata(m))) = stream.recv().await {
+                        record_guardrail_trace(&m);
                         usage = m.usage;
                     }
                     // Build a ConverseOutput::Message assembled from accumulated content and tool uses
                     let mut content_blocks: Vec<ContentBlock> = Vec::new();
+                    if !accumulated_reasoning.is_empty() {
+                        content_blocks.push(reasoning_block(
+                            &accumulated_reasoning,
+                            reasoning_signature.clone(),
+                        )?);
+                    }
                     if !accumulated_text.is_empty() {
                         content_blocks.push(ContentBlock::Text(accumulated_text.clone()));
                     }
@@ -826,6 +1014,7 @@ impl BedrockModel {
                     ));
                 }
                 ConverseStreamOutput::Metadata(m) => {
+                    record_guardrail_trace(&m);
                     if let Some(u) = m.usage {
                         usage = Some(u);
                     }
@@ -850,12 +1039,25 @@ impl BedrockModel {
             x => ModelFinishReason::Other(format!("{x:?}")),
         }
     }
+    /// Bedrock reports cache reads and writes apart from `input_tokens`,
+    /// they are counted as input here so the cost calculator can split them out again.
     fn map_usage(usage: Option<&TokenUsage>) -> Option<CompletionModelUsage> {
-        usage.map(|u| CompletionModelUsage {
-            input_tokens: u.input_tokens as u32,
-            output_tokens: u.output_tokens as u32,
-            total_tokens: u.total_tokens as u32,
-            ..Default::default()
+        usage.map(|u| {
+            let cache_read = u.cache_read_input_tokens.map(|t| t as u32);
+            let cache_write = u.cache_write_input_tokens.map(|t| t as u32);
+            let input_tokens =
+                u.input_tokens as u32 + cache_read.unwrap_or(0) + cache_write.unwrap_or(0);
+            CompletionModelUsage {
+                input_tokens,
+                output_tokens: u.output_tokens as u32,
+                total_tokens: input_tokens + u.output_tokens as u32,
+                prompt_tokens_details: Some(PromptTokensDetails::new(
+                    cache_read,
+                    cache_write,
+                    None,
+                )),
+                ..Default::default()
+            }
         })
     }
 
@@ -872,6 +1074,7 @@ impl BedrockModel {
             .execution_options
             .max_retries
             .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut guard = ToolLoopGuard::new(self.execution_options.tool_loop.as_ref());
         while let Some(input_messages) = calls.pop() {
             let input = serde_json::json!({
                 "initial_messages": format!("{input_messages:?}"),
@@ -883,7 +1086,8 @@ impl BedrockModel {
                 tags,
                 retries_left,
                 input = JsonValue(&input).as_value(),
-                system_prompt = field::Empty
+                system_prompt = field::Empty,
+                guardrail_trace = field::Empty
             );
 
             tracing::warn!("Bedrock Model name: {}", self.model_name);
@@ -893,16 +1097,20 @@ impl BedrockModel {
                 .converse_stream()
                 .model_id(replace_version(&self.model_name))
                 .set_system(Some(system_messages.clone()))
-                .set_tool_config(self.get_tools_config()?)
-                .set_messages(Some(input_messages.clone()));
+                .set_tool_config(self.get_tools_config(&input_messages, &guard)?)
+                .set_messages(Some(input_messages.clone()))
+                .additional_model_request_fields(self.additional_model_request_fields()?)
+                .set_inference_config(Some(self.inference_config()))
+                .set_guardrail_config(self.guardrail_stream_config()?);
 
             let response = self
-                .execute_stream_inner(builder, span.clone(), tx, tags.clone())
+                .execute_stream_inner(builder, span.clone(), tx, tags.clone(), &guard)
                 .await;
 
             match response {
                 Ok(InnerExecutionResult::Finish(_)) => return Ok(()),
                 Ok(InnerExecutionResult::NextCall(messages)) => {
+                    guard.next_iteration()?;
                     calls.push(messages);
                 }
                 Err(e) => {
@@ -926,6 +1134,7 @@ impl BedrockModel {
         span: Span,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<InnerExecutionResult> {
         let input_messages = builder.get_messages().clone().unwrap_or_default();
 
@@ -995,32 +1204,41 @@ impl BedrockModel {
 
                 let tool = self.tools.get(&tool_calls[0].tool_name).unwrap();
                 if tool.stop_at_call() {
-                    return Ok(InnerExecutionResult::Finish(
+                    return Ok(InnerExecutionResult::Finish(Box::new(
                         ChatCompletionMessageWithFinishReason::new(
                             ChatCompletionMessage {
                                 ..Default::default()
                             },
                             ModelFinishReason::ToolCalls,
                         ),
-                    ));
+                    )));
                 }
 
                 let mut conversational_messages = input_messages.clone();
 
+                // Signed reasoning has to precede the tool uses when the conversation continues
+                let reasoning = match &response_message {
+                    ConverseOutput::Message(message) => message
+                        .content
+                        .iter()
+                        .filter(|b| matches!(b, ContentBlock::ReasoningContent(_)))
+                        .cloned()
+                        .collect::<Vec<_>>(),
+                    _ => vec![],
+                };
                 let message = Message::builder()
                     .role(role.clone())
                     .set_content(Some(
-                        tool_uses
-                            .iter()
-                            .cloned()
-                            .map(ContentBlock::ToolUse)
+                        reasoning
+                            .into_iter()
+                            .chain(tool_uses.iter().cloned().map(ContentBlock::ToolUse))
                             .collect::<Vec<_>>(),
                     ))
                     .build()
                     .map_err(build_err)?;
                 conversational_messages.push(message);
                 let result_tool_calls =
-                    Self::handle_tool_calls(tool_uses, &self.tools, tx, tags.clone())
+                    Self::handle_tool_calls(tool_uses, &self.tools, tx, tags.clone(), guard)
                         .instrument(tools_span.clone())
                         .await?;
                 conversational_messages.push(result_tool_calls);
@@ -1028,12 +1246,12 @@ impl BedrockModel {
                 Ok(InnerExecutionResult::NextCall(conversational_messages))
             }
             StopReason::EndTurn | StopReason::StopSequence => Ok(InnerExecutionResult::Finish(
-                ChatCompletionMessageWithFinishReason::new(
+                Box::new(ChatCompletionMessageWithFinishReason::new(
                     ChatCompletionMessage {
                         ..Default::default()
                     },
                     ModelFinishReason::Stop,
-                ),
+                )),
             )),
             other => Err(Self::handle_stop_reason(other).into()),
         }
@@ -1059,6 +1277,11 @@ impl ModelInstance for BedrockModel {
         previous_messages: Vec<LMessage>,
         tags: HashMap<String, String>,
     ) -> GatewayResult<ChatCompletionMessageWithFinishReason> {
+        let previous_messages = resolve_images(
+            previous_messages,
+            &self.execution_options.images.clone().unwrap_or_default(),
+        )
+        .await?;
         let (initial_messages, system_messages) =
             self.construct_messages(input_vars.clone(), previous_messages)?;
         self.execute(initial_messages.clone(), system_messages.clone(), &tx, tags)
@@ -1072,6 +1295,11 @@ impl ModelInstance for BedrockModel {
         previous_messages: Vec<LMessage>,
         tags: HashMap<String, String>,
     ) -> GatewayResult<()> {
+        let previous_messages = resolve_images(
+            previous_messages,
+            &self.execution_options.images.clone().unwrap_or_default(),
+        )
+        .await?;
         let (initial_messages, system_messages) =
             self.construct_messages(input_vars.clone(), previous_messages)?;
 
@@ -1080,6 +1308,86 @@ impl ModelInstance for BedrockModel {
     }
 }
 
+fn cache_point() -> Result<CachePointBlock, ModelError> {
+    CachePointBlock::builder()
+        .r#type(CachePointType::Default)
+        .build()
+        .map_err(build_err)
+}
+
+fn guardrail_trace(enabled: bool) -> GuardrailTrace {
+    if enabled {
+        GuardrailTrace::Enabled
+    } else {
+        GuardrailTrace::Disabled
+    }
+}
+
+fn record_guardrail_trace(metadata: &ConverseStreamMetadataEvent) {
+    if let Some(trace) = metadata.trace().and_then(|t| t.guardrail()) {
+        Span::current().record("guardrail_trace", format!("{trace:?}"));
+    }
+}
+
+fn image_format(mime_type: &str) -> ImageFormat {
+    match mime_type {
+        "image/jpeg" => ImageFormat::Jpeg,
+        "image/gif" => ImageFormat::Gif,
+        "image/webp" => ImageFormat::Webp,
+        _ => ImageFormat::Png,
+    }
+}
+
+fn document_format(mime_type: &str) -> Option<DocumentFormat> {
+    let format = match mime_type {
+        "application/pdf" => DocumentFormat::Pdf,
+        "text/csv" => DocumentFormat::Csv,
+        "application/msword" => DocumentFormat::Doc,
+        "application/vnd.openxmlformats-officedocument.wordprocessingml.document" => {
+            DocumentFormat::Docx
+        }
+        "application/vnd.ms-excel" => DocumentFormat::Xls,
+        "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet" => DocumentFormat::Xlsx,
+        "text/html" => DocumentFormat::Html,
+        "text/plain" => DocumentFormat::Txt,
+        "text/markdown" => DocumentFormat::Md,
+        _ => return None,
+    };
+    Some(format)
+}
+
+/// Bedrock only accepts alphanumerics, single spaces, hyphens, parentheses
+/// and square brackets in document names.
+fn document_name(name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || matches!(c, ' ' | '-' | '(' | ')' | '[' | ']') {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>();
+    name.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn document_block(part: &MessageContentPart) -> Result<ContentBlock, ModelError> {
+    let file = FilePart::from_message_part(part)
+        .ok_or(ModelError::CustomError("Invalid file content".to_string()))?;
+    let Some(format) = document_format(&file.mime_type) else {
+        return Ok(ContentBlock::Text(file.extract_text()?));
+    };
+
+    let document = DocumentBlock::builder()
+        .format(format)
+        .name(document_name(file.name()))
+        .source(DocumentSource::Bytes(Blob::new(file.bytes()?)))
+        .build()
+        .map_err(build_err)?;
+    Ok(ContentBlock::Document(document))
+}
+
 fn construct_human_message(m: &InnerMessage) -> Result<Message, ModelError> {
     let content_blocks = match &m {
         crate::types::threads::InnerMessage::Text(text) => {
@@ -1093,18 +1401,11 @@ fn construct_human_message(m: &InnerMessage) -> Result<Message, ModelError> {
                         content_blocks.push(ContentBlock::Text(part.value.clone()));
                     }
                     crate::types::threads::MessageContentType::ImageUrl => {
-                        let url = part.value.clone();
-                        let base64_data = url
-                            .split_once(',')
-                            .map_or_else(|| url.as_str(), |(_, data)| data);
-
-                        let image_bytes = base64::engine::general_purpose::STANDARD
-                            .decode(base64_data)
-                            .map_err(|e| ModelError::CustomError(e.to_string()))?;
+                        let image = ImageData::from_url(&part.value)?;
                         let image = ImageBlockBuilder::default()
-                            .format(aws_sdk_bedrockruntime::types::ImageFormat::Png)
+                            .format(image_format(&image.mime_type))
                             .source(aws_sdk_bedrockruntime::types::ImageSource::Bytes(
-                                Blob::new(image_bytes),
+                                Blob::new(image.bytes()?),
                             ))
                             .build()
                             .map_err(build_err)?;
@@ -1114,6 +1415,12 @@ fn construct_human_message(m: &InnerMessage) -> Result<Message, ModelError> {
                     crate::types::threads::MessageContentType::InputAudio => {
                         todo!()
                     }
+                    crate::types::threads::MessageContentType::File => {
+                        content_blocks.push(document_block(part)?);
+                    }
+                }
+                if part.cache_control.is_some() {
+                    content_blocks.push(ContentBlock::CachePoint(cache_point()?));
                 }
             }
             content_blocks
@@ -1302,8 +1609,14 @@ impl ModelProviderInstance for BedrockModelProvider {
                                     .map(|c| ((c * 1000000.0) * 1000.0).round() / 1000.0)
                             })
                             .unwrap_or(0.0),
-                        per_cached_input_token: None,
-                        per_cached_input_write_token: None,
+                        per_cached_input_token: price.and_then(|p| {
+                            p.cache_read_input_token_cost
+                                .map(|c| ((c * 1000000.0) * 1000.0).round() / 1000.0)
+                        }),
+                        per_cached_input_write_token: price.and_then(|p| {
+                            p.cache_creation_input_token_cost
+                                .map(|c| ((c * 1000000.0) * 1000.0).round() / 1000.0)
+                        }),
                         valid_from: None,
                     }),
                     input_formats,
@@ -1359,3 +1672,30 @@ fn replace_version(model: &str) -> String {
         })
         .to_string()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pricing::calculator::calculate_tokens_cost;
+
+    #[test]
+    fn test_cache_usage_cost() {
+        let usage = TokenUsage::builder()
+            .input_tokens(100)
+            .output_tokens(10)
+            .total_tokens(1110)
+            .cache_read_input_tokens(800)
+            .cache_write_input_tokens(200)
+            .build()
+            .unwrap();
+
+        let usage = BedrockModel::map_usage(Some(&usage)).unwrap();
+        assert_eq!(usage.input_tokens, 1100);
+        assert_eq!(usage.total_tokens, 1110);
+
+        // $3 input, $0.3 cache read, $3.75 cache write and $15 output per million tokens
+        let cost = calculate_tokens_cost(&usage, 3.0, Some(0.3), Some(3.75), 15.0);
+        let expected = (100.0 * 3.0 + 800.0 * 0.3 + 200.0 * 3.75 + 10.0 * 15.0) * 1e-6;
+        assert!((cost.cost - expected).abs() < 1e-12);
+    }
+}
@@ -30,6 +30,7 @@ pub struct ModelPricingEntry {
     pub input_cost_per_token_batches: Option<f64>,
     pub output_cost_per_token_batches: Option<f64>,
     pub cache_read_input_token_cost: Option<f64>,
+    pub cache_creation_input_token_cost: Option<f64>,
 
     // Image generation special cases
     pub output_cost_per_image: Option<f64>,
@@ -22,7 +22,7 @@ use crate::{
         CredentialsIdent,
     },
     types::{
-        credentials::ApiKeyCredentials,
+        credentials::{ApiKeyCredentials, ClientOptions},
         embed::EmbeddingResult,
         gateway::{CompletionModelUsage, CreateEmbeddingRequest, EncodingFormat, Input},
     },
@@ -73,7 +73,12 @@ impl OpenAIEmbeddings<AzureConfig> {
             credentials_ident: credentials
                 .map(|_c| CredentialsIdent::Own)
                 .unwrap_or(CredentialsIdent::Langdb),
-            client: azure_openai_client(api_key, endpoint, deployment_id),
+            client: azure_openai_client(
+                api_key,
+                endpoint,
+                deployment_id,
+                &ClientOptions::default(),
+            )?,
         })
     }
 }
@@ -1,5 +1,7 @@
 use async_openai::error::OpenAIError;
 use aws_sdk_bedrock::error::DisplayErrorContext;
+use aws_sdk_bedrockruntime::types::error::ConverseStreamOutputError;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -54,6 +56,64 @@ pub enum ModelError {
 
     #[error("Cannot calculate input tokens")]
     CannotCalculateInputTokens,
+
+    #[error("Tool loop stopped after reaching the maximum of {0} tool iterations")]
+    MaxToolIterationsReached(u32),
+
+    #[error("Tool loop exceeded its deadline of {0}s")]
+    ToolLoopDeadlineExceeded(u64),
+
+    #[error("Tool {0} failed: {1}")]
+    ToolCallFailed(String, String),
+
+    #[error("Provider rate limited the request: {message}")]
+    RateLimited {
+        message: String,
+        retry_after: Option<Duration>,
+    },
+}
+
+/// A provider rejected the call because of rate limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// Delay from the provider's `Retry-After` header, when it sent one
+    pub retry_after: Option<Duration>,
+}
+
+impl ModelError {
+    /// Rate limit signalled by the HTTP status (429) or the typed error of the provider.
+    pub fn rate_limit(&self) -> Option<RateLimit> {
+        match self {
+            ModelError::RateLimited { retry_after, .. } => Some(RateLimit {
+                retry_after: *retry_after,
+            }),
+            // The OpenAI client drops the response, only the error body is left
+            ModelError::OpenAIApi(OpenAIError::ApiError(e))
+                if e.code.as_deref() == Some("rate_limit_exceeded")
+                    || e.message == OPENAI_STREAM_RATE_LIMITED =>
+            {
+                Some(RateLimit { retry_after: None })
+            }
+            ModelError::Bedrock(e) => e.rate_limit(),
+            _ => None,
+        }
+    }
+}
+
+/// Message of the error the OpenAI client returns when a stream is answered with a 429.
+const OPENAI_STREAM_RATE_LIMITED: &str = "Rate limited by provider";
+
+/// Parses a `Retry-After` header, given either in seconds or as an HTTP date.
+pub fn parse_retry_after(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.with_timezone(&chrono::Utc) - chrono::Utc::now())
+        .to_std()
+        .ok()
 }
 
 impl From<BedrockError> for ModelError {
@@ -110,6 +170,30 @@ pub enum AnthropicError {
     RequestError(String),
 }
 
+impl BedrockError {
+    fn rate_limit(&self) -> Option<RateLimit> {
+        let response = match self {
+            BedrockError::ConverseError(e) => e.raw_response(),
+            BedrockError::ResponseError(e) => e.raw_response(),
+            BedrockError::SmithyError(e) => {
+                return matches!(
+                    e.as_service_error(),
+                    Some(ConverseStreamOutputError::ThrottlingException(_))
+                )
+                .then_some(RateLimit { retry_after: None })
+            }
+            _ => None,
+        }?;
+
+        (response.status().as_u16() == 429).then(|| RateLimit {
+            retry_after: response
+                .headers()
+                .get("retry-after")
+                .and_then(parse_retry_after),
+        })
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum BedrockError {
     #[error("Custom Error: {0}")]
@@ -151,3 +235,33 @@ pub enum BedrockError {
         >,
     ),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_openai::error::ApiError;
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after("30"), Some(Duration::from_secs(30)));
+        assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT"), None);
+        let date = (chrono::Utc::now() + chrono::Duration::seconds(120)).to_rfc2822();
+        assert!(parse_retry_after(&date).is_some_and(|d| d > Duration::from_secs(100)));
+        assert_eq!(parse_retry_after("soon"), None);
+    }
+
+    #[test]
+    fn test_rate_limit() {
+        let error = ModelError::OpenAIApi(OpenAIError::ApiError(ApiError {
+            message: "Rate limit reached for requests".to_string(),
+            r#type: Some("requests".to_string()),
+            param: None,
+            code: Some("rate_limit_exceeded".to_string()),
+        }));
+        assert_eq!(error.rate_limit(), Some(RateLimit { retry_after: None }));
+
+        // Mentioning 429 or rate limits is not enough
+        let error = ModelError::CustomError("Order 429 exceeds the rate limit".to_string());
+        assert_eq!(error.rate_limit(), None);
+    }
+}
@@ -0,0 +1,250 @@
+use base64::Engine;
+
+use super::error::ModelError;
+use crate::types::threads::{Message, MessageContentPart, MessageContentType};
+
+const DEFAULT_FILE_NAME: &str = "document";
+const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+/// Largest PDF whose text is extracted for providers without native document support.
+pub const MAX_PDF_BYTES: usize = 32 * 1024 * 1024;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FileSource<'a> {
+    Base64(&'a str),
+    Url(&'a str),
+}
+
+/// File content part of a message, resolved to its source and mime type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilePart<'a> {
+    pub filename: Option<&'a str>,
+    pub mime_type: String,
+    pub source: FileSource<'a>,
+}
+
+impl<'a> FilePart<'a> {
+    pub fn from_message_part(part: &'a MessageContentPart) -> Option<Self> {
+        if part.r#type != MessageContentType::File {
+            return None;
+        }
+
+        let detail = part.additional_options.as_ref().and_then(|o| o.as_file());
+        let filename = detail.and_then(|d| d.filename.as_deref());
+
+        let (data_url_mime_type, source) = match parse_data_url(&part.value) {
+            Some((mime_type, data)) => (Some(mime_type), FileSource::Base64(data)),
+            None => (None, FileSource::Url(part.value.as_str())),
+        };
+
+        let mime_type = detail
+            .and_then(|d| d.mime_type.clone())
+            .or(data_url_mime_type.map(|m| m.to_string()))
+            .or(filename.and_then(mime_type_from_name))
+            .or(match source {
+                FileSource::Url(url) => mime_type_from_name(url),
+                FileSource::Base64(_) => None,
+            })
+            .unwrap_or(DEFAULT_MIME_TYPE.to_string());
+
+        Some(Self {
+            filename,
+            mime_type,
+            source,
+        })
+    }
+
+    pub fn name(&self) -> &str {
+        self.filename.unwrap_or(DEFAULT_FILE_NAME)
+    }
+
+    pub fn is_pdf(&self) -> bool {
+        self.mime_type == "application/pdf"
+    }
+
+    pub fn is_text(&self) -> bool {
+        self.mime_type.starts_with("text/")
+            || matches!(
+                self.mime_type.as_str(),
+                "application/json" | "application/xml" | "application/x-yaml"
+            )
+    }
+
+    pub fn bytes(&self) -> Result<Vec<u8>, ModelError> {
+        match self.source {
+            FileSource::Base64(data) => base64::engine::general_purpose::STANDARD
+                .decode(data)
+                .map_err(|e| ModelError::CustomError(format!("Invalid file data: {e}"))),
+            FileSource::Url(_) => Err(ModelError::CustomError(format!(
+                "File {} must be sent as base64 data for this provider",
+                self.name()
+            ))),
+        }
+    }
+
+    /// Text fallback for providers without native document support.
+    /// PDFs have to be converted with [`extract_documents`] first.
+    pub fn extract_text(&self) -> Result<String, ModelError> {
+        if !self.is_text() {
+            return Err(ModelError::CustomError(format!(
+                "Unsupported file type {} for {}",
+                self.mime_type,
+                self.name()
+            )));
+        }
+
+        let bytes = self.bytes()?;
+        Ok(self.text_content(&String::from_utf8_lossy(&bytes)))
+    }
+
+    /// Extracts the text of a PDF on the blocking pool. The parser panics on some
+    /// malformed documents, which is reported as an error of this file.
+    pub async fn extract_pdf_text(&self) -> Result<String, ModelError> {
+        let bytes = self.bytes()?;
+        if bytes.len() > MAX_PDF_BYTES {
+            return Err(ModelError::CustomError(format!(
+                "File {} exceeds the limit of {MAX_PDF_BYTES} bytes",
+                self.name()
+            )));
+        }
+
+        let text = tokio::task::spawn_blocking(move || pdf_extract::extract_text_from_mem(&bytes))
+            .await
+            .map_err(|e| {
+                let reason = if e.is_panic() {
+                    "the document could not be parsed".to_string()
+                } else {
+                    e.to_string()
+                };
+                ModelError::CustomError(format!(
+                    "Failed to extract text from {}: {reason}",
+                    self.name()
+                ))
+            })?
+            .map_err(|e| {
+                ModelError::CustomError(format!("Failed to extract text from {}: {e}", self.name()))
+            })?;
+
+        Ok(self.text_content(&text))
+    }
+
+    fn text_content(&self, text: &str) -> String {
+        format!("File: {}\n\n{}", self.name(), text.trim())
+    }
+}
+
+/// Replaces PDF files with their extracted text, for providers that take no documents.
+pub async fn extract_documents(mut messages: Vec<Message>) -> Result<Vec<Message>, ModelError> {
+    for message in messages.iter_mut() {
+        for part in message.content_array.iter_mut() {
+            let Some(file) = FilePart::from_message_part(part).filter(FilePart::is_pdf) else {
+                continue;
+            };
+
+            let text = file.extract_pdf_text().await?;
+            *part = MessageContentPart {
+                r#type: MessageContentType::Text,
+                value: text,
+                additional_options: None,
+                cache_control: part.cache_control.clone(),
+            };
+        }
+    }
+
+    Ok(messages)
+}
+
+/// Splits a `data:<mime>;base64,<data>` url into mime type and data.
+pub fn parse_data_url(url: &str) -> Option<(&str, &str)> {
+    let (header, data) = url.strip_prefix("data:")?.split_once(',')?;
+    let mime_type = header.strip_suffix(";base64")?;
+    Some((mime_type, data))
+}
+
+pub fn mime_type_from_name(name: &str) -> Option<String> {
+    let extension = name
+        .split(['?', '#'])
+        .next()?
+        .rsplit_once('.')?
+        .1
+        .to_lowercase();
+    let mime_type = match extension.as_str() {
+        "pdf" => "application/pdf",
+        "txt" => "text/plain",
+        "md" => "text/markdown",
+        "csv" => "text/csv",
+        "html" | "htm" => "text/html",
+        "json" => "application/json",
+        "xml" => "application/xml",
+        "doc" => "application/msword",
+        "docx" => "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+        "xls" => "application/vnd.ms-excel",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        _ => return None,
+    };
+    Some(mime_type.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::threads::{FileDetail, MessageContentPartOptions};
+
+    fn file_part(value: &str, detail: FileDetail) -> MessageContentPart {
+        MessageContentPart {
+            r#type: MessageContentType::File,
+            value: value.to_string(),
+            additional_options: Some(MessageContentPartOptions::File(detail)),
+            cache_control: None,
+        }
+    }
+
+    #[test]
+    fn test_file_part() {
+        let part = file_part(
+            "data:text/plain;base64,aGVsbG8gd29ybGQ=",
+            FileDetail {
+                filename: Some("notes.txt".to_string()),
+                mime_type: None,
+            },
+        );
+        let file = FilePart::from_message_part(&part).unwrap();
+        assert_eq!(file.mime_type, "text/plain");
+        assert_eq!(file.source, FileSource::Base64("aGVsbG8gd29ybGQ="));
+        assert_eq!(
+            file.extract_text().unwrap(),
+            "File: notes.txt\n\nhello world"
+        );
+
+        let part = file_part("https://example.com/report.pdf?v=1", FileDetail::default());
+        let file = FilePart::from_message_part(&part).unwrap();
+        assert!(file.is_pdf());
+        assert_eq!(
+            file.source,
+            FileSource::Url("https://example.com/report.pdf?v=1")
+        );
+        assert!(file.bytes().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_extract_pdf_text() {
+        let detail = FileDetail {
+            filename: Some("report.pdf".to_string()),
+            mime_type: None,
+        };
+        let broken = base64::engine::general_purpose::STANDARD.encode(b"%PDF-1.4 not a document");
+        let part = file_part(
+            &format!("data:application/pdf;base64,{broken}"),
+            detail.clone(),
+        );
+        let file = FilePart::from_message_part(&part).unwrap();
+        assert!(file.extract_pdf_text().await.is_err());
+        assert!(file.extract_text().is_err());
+
+        let oversized =
+            base64::engine::general_purpose::STANDARD.encode(vec![0; MAX_PDF_BYTES + 1]);
+        let part = file_part(&format!("data:application/pdf;base64,{oversized}"), detail);
+        let file = FilePart::from_message_part(&part).unwrap();
+        let err = file.extract_pdf_text().await.unwrap_err();
+        assert!(err.to_string().contains("exceeds the limit"));
+    }
+}
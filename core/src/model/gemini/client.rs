@@ -4,6 +4,8 @@ use crate::{
     GatewayResult,
 };
 
+use crate::model::error::{parse_retry_after, ModelError};
+
 use super::types::{
     CountTokensRequest, CountTokensResponse, GenerateContentRequest, GenerateContentResponse,
     ModelsResponse,
@@ -63,6 +65,9 @@ impl Client {
             .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
         let status = resp.status();
+        if status == StatusCode::TOO_MANY_REQUESTS {
+            return Err(rate_limited(&resp).into());
+        }
         if !status.is_success() {
             let msg = resp.text().await?;
             let p = if let Some(p) = payload {
@@ -176,6 +181,11 @@ impl Client {
                     Some(Err(e)) => {
                         let err_str = e.to_string();
                         let err_str = match e {
+                            reqwest_eventsource::Error::InvalidStatusCode(status, r)
+                                if status == StatusCode::TOO_MANY_REQUESTS =>
+                            {
+                                return Some((Err(rate_limited(&r).into()), event_source));
+                            }
                             reqwest_eventsource::Error::InvalidStatusCode(_, r) => {
                                 let status = r.status();
                                 let error = r.text().await.unwrap_or(err_str);
@@ -200,6 +210,17 @@ impl Client {
     }
 }
 
+fn rate_limited(response: &reqwest::Response) -> ModelError {
+    ModelError::RateLimited {
+        message: format!("Gemini responded with {}", response.status()),
+        retry_after: response
+            .headers()
+            .get(reqwest::header::RETRY_AFTER)
+            .and_then(|value| value.to_str().ok())
+            .and_then(parse_retry_after),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::model::gemini::types::FinishReason;
@@ -1,7 +1,7 @@
 use super::super::error::ModelError;
 use super::super::types::{
-    LLMContentEvent, LLMFinishEvent, LLMStartEvent, ModelEvent, ModelEventType, ModelFinishReason,
-    ModelToolCall,
+    LLMContentEvent, LLMFinishEvent, LLMMetadataEvent, LLMReasoningEvent, LLMStartEvent,
+    ModelEvent, ModelEventType, ModelFinishReason, ModelToolCall,
 };
 use super::super::ModelInstance;
 use super::super::Tool;
@@ -15,17 +15,21 @@ use crate::events::JsonValue;
 use crate::events::SPAN_GEMINI;
 use crate::events::{self, RecordResult};
 use crate::model::error::{AuthorizationError, ModelFinishError};
+use crate::model::files::{FilePart, FileSource};
 use crate::model::gemini::types::{
-    Candidate, FunctionDeclaration, GenerationConfig, PartWithThought, Role, Tools,
+    Candidate, CodeExecution, FunctionCallingConfig, FunctionCallingMode, FunctionDeclaration,
+    GenerationConfig, GoogleSearch, GroundingMetadata, PartWithThought, Role, SafetyRating,
+    ThinkingConfig, ToolConfig, Tools,
 };
-use crate::model::handler::handle_tool_call;
+use crate::model::handler::{handle_tool_call, ToolLoopGuard};
+use crate::model::images::{resolve_images, ImageData};
 use crate::model::types::LLMFirstToken;
 use crate::model::{async_trait, CredentialsIdent, DEFAULT_MAX_RETRIES};
 use crate::types::credentials::ApiKeyCredentials;
 use crate::types::engine::{ExecutionOptions, GeminiModelParams, Prompt};
 use crate::types::gateway::{
-    ChatCompletionContent, ChatCompletionMessage, ChatCompletionMessageWithFinishReason,
-    CompletionModelUsage, ToolCall,
+    self, ChatCompletionContent, ChatCompletionMessage, ChatCompletionMessageWithFinishReason,
+    CompletionModelUsage, ToolCall, ToolChoice, ToolChoiceMode,
 };
 use crate::types::message::{MessageType, PromptMessage};
 use crate::types::threads::{AudioFormat, InnerMessage, Message, MessageContentPartOptions};
@@ -54,6 +58,43 @@ fn custom_err(e: impl ToString) -> ModelError {
     ModelError::CustomError(e.to_string())
 }
 
+/// Renders built-in code execution parts as markdown so they are kept in the text output.
+fn code_execution_text(part: &Part) -> String {
+    match part {
+        Part::ExecutableCode { language, code } => {
+            format!("\n```{}\n{code}\n```\n", language.to_lowercase())
+        }
+        Part::CodeExecutionResult { output, .. } => format!("\n```output\n{output}\n```\n"),
+        _ => String::new(),
+    }
+}
+
+/// Grounding of a candidate together with its citations.
+fn grounding_metadata(candidate: &Candidate) -> Option<GroundingMetadata> {
+    let citations = candidate
+        .citation_metadata
+        .as_ref()
+        .map(|c| c.citations.clone())
+        .unwrap_or_default();
+    match candidate.grounding_metadata.clone() {
+        Some(grounding) => Some(GroundingMetadata {
+            citations,
+            ..grounding
+        }),
+        None if !citations.is_empty() => Some(GroundingMetadata {
+            citations,
+            ..Default::default()
+        }),
+        None => None,
+    }
+}
+
+fn gateway_safety_ratings(
+    ratings: Option<Vec<SafetyRating>>,
+) -> Option<Vec<gateway::SafetyRating>> {
+    ratings.map(|ratings| ratings.into_iter().map(Into::into).collect())
+}
+
 fn map_calls_to_tool_label(calls: &[(String, HashMap<String, Value>)]) -> String {
     calls
         .iter()
@@ -64,6 +105,27 @@ fn map_calls_to_tool_label(calls: &[(String, HashMap<String, Value>)]) -> String
         .join(",")
 }
 
+/// Maps the OpenAI style tool choice onto Gemini's function calling config.
+/// Gemini has no switch for parallel function calls, so `parallel_tool_calls` is not mapped.
+pub(crate) fn gemini_tool_config(tool_choice: Option<&ToolChoice>) -> Option<ToolConfig> {
+    let (mode, allowed_function_names) = match tool_choice? {
+        ToolChoice::Mode(ToolChoiceMode::Auto) => (FunctionCallingMode::Auto, None),
+        ToolChoice::Mode(ToolChoiceMode::None) => (FunctionCallingMode::None, None),
+        ToolChoice::Mode(ToolChoiceMode::Required) => (FunctionCallingMode::Any, None),
+        ToolChoice::Named(named) => (
+            FunctionCallingMode::Any,
+            Some(vec![named.function.name.clone()]),
+        ),
+    };
+
+    Some(ToolConfig {
+        function_calling_config: FunctionCallingConfig {
+            mode,
+            allowed_function_names,
+        },
+    })
+}
+
 pub fn gemini_client(credentials: Option<&ApiKeyCredentials>) -> Result<Client, ModelError> {
     let api_key = if let Some(credentials) = credentials {
         credentials.api_key.clone()
@@ -74,7 +136,7 @@ pub fn gemini_client(credentials: Option<&ApiKeyCredentials>) -> Result<Client,
 }
 
 enum InnerExecutionResult {
-    Finish(ChatCompletionMessageWithFinishReason),
+    Finish(Box<ChatCompletionMessageWithFinishReason>),
     NextCall(Vec<Content>),
 }
 
@@ -113,24 +175,39 @@ impl GeminiModel {
         tools: &HashMap<String, Box<dyn Tool>>,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> Vec<PartWithThought> {
-        futures::future::join_all(function_calls.map(|(name, args)| {
-            let tags = tags.clone();
-            async move {
-                tracing::trace!("Calling tool  {name:?}");
-                let tool_call = Self::map_tool_call(&(name.to_string(), args.clone()));
-                let result = handle_tool_call(&tool_call, tools, tx, tags.clone()).await;
-                tracing::trace!("Result ({name}): {result:?}");
-                let content = result
-                    .map(|r| r.to_string())
-                    .unwrap_or_else(|err| err.to_string());
-                Part::Text(content).into()
-            }
-        }))
-        .await
+        guard
+            .run_tools(function_calls.map(|(name, args)| {
+                let tags = tags.clone();
+                async move {
+                    tracing::trace!("Calling tool  {name:?}");
+                    let tool_call = Self::map_tool_call(&(name.to_string(), args.clone()));
+                    let result = handle_tool_call(&tool_call, tools, tx, tags.clone(), guard).await;
+                    tracing::trace!("Result ({name}): {result:?}");
+                    match result {
+                        Ok(output) => std::iter::once(Part::Text(output.text))
+                            .chain(output.images.into_iter().map(|image| Part::InlineData {
+                                mime_type: image.mime_type,
+                                data: image.data,
+                            }))
+                            .map(PartWithThought::from)
+                            .collect(),
+                        Err(err) => vec![Part::Text(err.to_string()).into()],
+                    }
+                }
+            }))
+            .await
+            .into_iter()
+            .flatten()
+            .collect()
     }
 
-    fn build_request(&self, messages: Vec<Content>) -> GatewayResult<GenerateContentRequest> {
+    pub(crate) fn build_request(
+        &self,
+        messages: Vec<Content>,
+        guard: &ToolLoopGuard,
+    ) -> GatewayResult<GenerateContentRequest> {
         let model_params = &self.params;
         let response_schema = match &model_params.response_format {
             Some(ResponseFormat::JsonSchema { json_schema }) => {
@@ -165,6 +242,12 @@ impl GeminiModel {
                 None
             },
             response_schema,
+            thinking_config: model_params.thinking_config.clone().or(model_params
+                .thinking_budget
+                .map(|thinking_budget| ThinkingConfig {
+                    thinking_budget: Some(thinking_budget),
+                    include_thoughts: Some(true),
+                })),
         };
 
         let tools = if self.tools.is_empty() {
@@ -191,13 +274,38 @@ impl GeminiModel {
 
             Some(vec![Tools {
                 function_declarations: Some(defs),
+                ..Default::default()
             }])
         };
 
+        let tool_config = tools.as_ref().and_then(|_| {
+            gemini_tool_config(
+                guard
+                    .tool_choice(model_params.tool_choice.as_ref())
+                    .as_ref(),
+            )
+        });
+
+        let mut tools = tools.unwrap_or_default();
+        if model_params.google_search == Some(true) {
+            tools.push(Tools {
+                google_search: Some(GoogleSearch {}),
+                ..Default::default()
+            });
+        }
+        if model_params.code_execution == Some(true) {
+            tools.push(Tools {
+                code_execution: Some(CodeExecution {}),
+                ..Default::default()
+            });
+        }
+
         let request = GenerateContentRequest {
             contents: messages,
             generation_config: Some(config),
-            tools,
+            tools: if tools.is_empty() { None } else { Some(tools) },
+            tool_config,
+            safety_settings: model_params.safety_settings.clone(),
         };
 
         Ok(request)
@@ -219,8 +327,11 @@ impl GeminiModel {
         let mut finish_reason = None;
         let mut first_response_received = false;
         let mut content = String::new();
+        let mut reasoning = String::new();
         let mut model_version = String::new();
         let mut response_id = String::new();
+        let mut safety_ratings = None;
+        let mut grounding = None;
         let mut last_chunk = None;
         while let Some(res) = stream.next().await {
             last_chunk = Some(res.as_ref().map_or_else(
@@ -243,8 +354,23 @@ impl GeminiModel {
                             Span::current().record("ttft", started_at.elapsed().as_micros());
                         }
                         for candidate in res.candidates {
+                            grounding = grounding_metadata(&candidate).or(grounding);
+                            safety_ratings = candidate.safety_ratings.or(safety_ratings);
                             for part in candidate.content.parts {
+                                let is_thought = part.is_thought();
                                 match part.part {
+                                    Part::Text(text) if is_thought => {
+                                        reasoning.push_str(&text);
+                                        let _ = tx
+                                            .send(Some(ModelEvent::new(
+                                                &Span::current(),
+                                                ModelEventType::LlmReasoning(LLMReasoningEvent {
+                                                    content: text,
+                                                    signature: part.thought_signature,
+                                                }),
+                                            )))
+                                            .await;
+                                    }
                                     Part::Text(text) => {
                                         content.push_str(&text);
                                         let _ = tx
@@ -259,6 +385,19 @@ impl GeminiModel {
                                     Part::FunctionCall { name, args } => {
                                         calls.push((name.to_string(), args));
                                     }
+                                    x @ (Part::ExecutableCode { .. }
+                                    | Part::CodeExecutionResult { .. }) => {
+                                        let text = code_execution_text(&x);
+                                        content.push_str(&text);
+                                        let _ = tx
+                                            .send(Some(ModelEvent::new(
+                                                &Span::current(),
+                                                ModelEventType::LlmContent(LLMContentEvent {
+                                                    content: text,
+                                                }),
+                                            )))
+                                            .await;
+                                    }
 
                                     x => {
                                         return Err(ModelError::StreamError(format!(
@@ -284,7 +423,25 @@ impl GeminiModel {
         }
 
         if let Some(reason) = finish_reason {
+            if safety_ratings.is_some() || grounding.is_some() {
+                let _ = tx
+                    .send(Some(ModelEvent::new(
+                        &Span::current(),
+                        ModelEventType::LlmMetadata(LLMMetadataEvent {
+                            safety_ratings: gateway_safety_ratings(safety_ratings.clone()),
+                            grounding_metadata: grounding.clone().map(Into::into),
+                        }),
+                    )))
+                    .await;
+            }
+
             let mut parts: Vec<PartWithThought> = vec![];
+            if !reasoning.is_empty() {
+                parts.push(PartWithThought {
+                    thought: Some(true),
+                    ..Part::Text(reasoning).into()
+                });
+            }
             if !content.is_empty() {
                 parts.push(Part::Text(content).into());
             }
@@ -307,7 +464,8 @@ impl GeminiModel {
                     },
                     citation_metadata: None,
                     finish_reason: Some(reason.clone()),
-                    safety_ratings: None,
+                    safety_ratings,
+                    grounding_metadata: grounding,
                 }],
                 response_id,
                 model_version,
@@ -328,6 +486,7 @@ impl GeminiModel {
         span: Span,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<InnerExecutionResult> {
         let model_name = self.params.model.as_ref().unwrap();
         let input_messages = call.contents.clone();
@@ -371,18 +530,32 @@ impl GeminiModel {
         let mut finish_reason = None;
         let mut calls: Vec<(String, HashMap<String, Value>)> = vec![];
         let mut text = String::new();
+        let mut reasoning_content: Option<String> = None;
+        let mut safety_ratings = None;
+        let mut grounding = None;
         for candidate in response.candidates {
-            if let Some(reason) = candidate.finish_reason {
+            if let Some(reason) = candidate.finish_reason.clone() {
                 finish_reason = Some(reason);
             }
+            grounding = grounding_metadata(&candidate).or(grounding);
+            safety_ratings = candidate.safety_ratings.or(safety_ratings);
             for part in candidate.content.parts {
+                let is_thought = part.is_thought();
                 match part.part {
+                    Part::Text(t) if is_thought => {
+                        reasoning_content
+                            .get_or_insert_with(String::new)
+                            .push_str(&t);
+                    }
                     Part::Text(t) => {
                         text.push_str(&t);
                     }
                     Part::FunctionCall { name, args } => {
                         calls.push((name.to_string(), args));
                     }
+                    x @ (Part::ExecutableCode { .. } | Part::CodeExecutionResult { .. }) => {
+                        text.push_str(&code_execution_text(&x));
+                    }
 
                     x => {
                         return Err(ModelError::StreamError(format!(
@@ -465,7 +638,7 @@ impl GeminiModel {
                     .await
                     .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                    return Ok(InnerExecutionResult::Finish(
+                    return Ok(InnerExecutionResult::Finish(Box::new(
                         ChatCompletionMessageWithFinishReason::new(
                             ChatCompletionMessage {
                                 role: "assistant".to_string(),
@@ -491,16 +664,19 @@ impl GeminiModel {
                                         })
                                         .collect::<Result<Vec<ToolCall>, GatewayError>>()?,
                                 ),
+                                reasoning_content,
+                                safety_ratings: gateway_safety_ratings(safety_ratings),
+                                grounding_metadata: grounding.map(Into::into),
                                 ..Default::default()
                             },
                             ModelFinishReason::ToolCalls,
                         ),
-                    ));
+                    )));
                 }
             }
             tools_span.follows_from(span.id());
             let tool_call_parts =
-                Self::handle_tool_calls(calls.iter(), &self.tools, tx, tags.clone())
+                Self::handle_tool_calls(calls.iter(), &self.tools, tx, tags.clone(), guard)
                     .instrument(tools_span.clone())
                     .await;
             let tools_messages = vec![Content {
@@ -549,16 +725,19 @@ impl GeminiModel {
                 .await
                 .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                Ok(InnerExecutionResult::Finish(
+                Ok(InnerExecutionResult::Finish(Box::new(
                     ChatCompletionMessageWithFinishReason::new(
                         ChatCompletionMessage {
                             role: "assistant".to_string(),
                             content: Some(ChatCompletionContent::Text(text)),
+                            reasoning_content,
+                            safety_ratings: gateway_safety_ratings(safety_ratings),
+                            grounding_metadata: grounding.map(Into::into),
                             ..Default::default()
                         },
                         finish_reason,
                     ),
-                ))
+                )))
             }
             _ => {
                 let err = Self::handle_finish_reason(finish_reason);
@@ -579,21 +758,23 @@ impl GeminiModel {
             .execution_options
             .max_retries
             .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut guard = ToolLoopGuard::new(self.execution_options.tool_loop.as_ref());
         while let Some(call) = gemini_calls.pop() {
             let span = create_model_span!(SPAN_GEMINI, target!("chat"), &tags, retries_left);
 
-            let request = self.build_request(call.clone())?;
+            let request = self.build_request(call.clone(), &guard)?;
 
             span.record("input", serde_json::to_string(&request)?);
             span.record("request", serde_json::to_string(&request)?);
 
             let result = self
-                .execute_inner(request, span.clone(), tx, tags.clone())
+                .execute_inner(request, span.clone(), tx, tags.clone(), &guard)
                 .await;
 
             match result.map_err(|e| record_map_err(e, span.clone())) {
-                Ok(InnerExecutionResult::Finish(message)) => return Ok(message),
+                Ok(InnerExecutionResult::Finish(message)) => return Ok(*message),
                 Ok(InnerExecutionResult::NextCall(messages)) => {
+                    guard.next_iteration()?;
                     gemini_calls.push(messages);
                     continue;
                 }
@@ -673,6 +854,7 @@ impl GeminiModel {
         tx: tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         call_span: Span,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<InnerExecutionResult> {
         let model_name = self.params.model.as_ref().unwrap();
         let input_messages = call.contents.clone();
@@ -766,20 +948,20 @@ impl GeminiModel {
             let tool = self.tools.get(&tool_calls[0].0);
             if let Some(tool) = tool {
                 if tool.stop_at_call() {
-                    return Ok(InnerExecutionResult::Finish(
+                    return Ok(InnerExecutionResult::Finish(Box::new(
                         ChatCompletionMessageWithFinishReason::new(
                             ChatCompletionMessage {
                                 ..Default::default()
                             },
                             ModelFinishReason::ToolCalls,
                         ),
-                    ));
+                    )));
                 }
             }
 
             tools_span.follows_from(call_span.id());
             let tool_call_parts =
-                Self::handle_tool_calls(tool_calls.iter(), &self.tools, &tx, tags.clone())
+                Self::handle_tool_calls(tool_calls.iter(), &self.tools, &tx, tags.clone(), guard)
                     .instrument(tools_span.clone())
                     .await;
             let tools_messages = vec![Content {
@@ -794,12 +976,12 @@ impl GeminiModel {
 
         match finish_reason {
             FinishReason::Stop | FinishReason::MaxTokens => Ok(InnerExecutionResult::Finish(
-                ChatCompletionMessageWithFinishReason::new(
+                Box::new(ChatCompletionMessageWithFinishReason::new(
                     ChatCompletionMessage {
                         ..Default::default()
                     },
                     Self::map_finish_reason(&finish_reason, !tool_calls.is_empty()),
-                ),
+                )),
             )),
             other => Err(Self::handle_finish_reason(Some(other))),
         }
@@ -817,21 +999,23 @@ impl GeminiModel {
             .execution_options
             .max_retries
             .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut guard = ToolLoopGuard::new(self.execution_options.tool_loop.as_ref());
         while let Some(call) = gemini_calls.pop() {
             let span = create_model_span!(SPAN_GEMINI, target!("chat"), &tags, retries_left);
 
-            let request = self.build_request(call.clone())?;
+            let request = self.build_request(call.clone(), &guard)?;
 
             span.record("input", serde_json::to_string(&request)?);
             span.record("request", serde_json::to_string(&request)?);
 
             let result = self
-                .execute_stream_inner(request, tx.clone(), span.clone(), tags.clone())
+                .execute_stream_inner(request, tx.clone(), span.clone(), tags.clone(), &guard)
                 .await;
 
             match result.map_err(|e| record_map_err(e, span.clone())) {
                 Ok(InnerExecutionResult::Finish(_)) => return Ok(()),
                 Ok(InnerExecutionResult::NextCall(messages)) => {
+                    guard.next_iteration()?;
                     gemini_calls.push(messages);
                     continue;
                 }
@@ -893,7 +1077,7 @@ impl GeminiModel {
                             }
                         }
                     }
-                    MessageType::HumanMessage => Some(construct_user_message(&m.clone().into())),
+                    MessageType::HumanMessage => Some(construct_user_message(&m.clone().into())?),
                     MessageType::ToolResult => {
                         tool_results_remaining -= 1;
                         let content =
@@ -942,6 +1126,11 @@ impl ModelInstance for GeminiModel {
         previous_messages: Vec<Message>,
         tags: HashMap<String, String>,
     ) -> GatewayResult<ChatCompletionMessageWithFinishReason> {
+        let previous_messages = resolve_images(
+            previous_messages,
+            &self.execution_options.images.clone().unwrap_or_default(),
+        )
+        .await?;
         let conversational_messages =
             self.construct_messages(input_variables, previous_messages)?;
         self.execute(conversational_messages, &tx, tags).await
@@ -954,6 +1143,11 @@ impl ModelInstance for GeminiModel {
         previous_messages: Vec<Message>,
         tags: HashMap<String, String>,
     ) -> GatewayResult<()> {
+        let previous_messages = resolve_images(
+            previous_messages,
+            &self.execution_options.images.clone().unwrap_or_default(),
+        )
+        .await?;
         let conversational_messages =
             self.construct_messages(input_variables, previous_messages)?;
         self.execute_stream(conversational_messages, tx, tags).await
@@ -1015,7 +1209,7 @@ fn map_chat_messages(
             } else {
                 InnerMessage::Text(Prompt::render(msg.clone(), variables))
             };
-            construct_user_message(&inner_message)
+            construct_user_message(&inner_message)?
         }
         MessageType::ToolResult => {
             todo!()
@@ -1024,8 +1218,8 @@ fn map_chat_messages(
     Ok(message)
 }
 
-fn construct_user_message(m: &InnerMessage) -> Content {
-    match m {
+fn construct_user_message(m: &InnerMessage) -> GatewayResult<Content> {
+    let content = match m {
         crate::types::threads::InnerMessage::Text(text) => Content::user(text.to_string()),
         crate::types::threads::InnerMessage::Array(content_array) => {
             let mut parts = vec![];
@@ -1033,13 +1227,10 @@ fn construct_user_message(m: &InnerMessage) -> Content {
                 let msg: Part = match m.r#type {
                     crate::types::threads::MessageContentType::Text => Part::Text(m.value.clone()),
                     crate::types::threads::MessageContentType::ImageUrl => {
-                        let url = m.value.clone();
-                        let base64_data = url
-                            .split_once(',')
-                            .map_or_else(|| url.as_str(), |(_, data)| data);
+                        let image = ImageData::from_url(&m.value)?;
                         Part::InlineData {
-                            mime_type: "image/png".to_string(),
-                            data: base64_data.to_string(),
+                            mime_type: image.mime_type,
+                            data: image.data,
                         }
                     }
                     crate::types::threads::MessageContentType::InputAudio => {
@@ -1057,6 +1248,27 @@ fn construct_user_message(m: &InnerMessage) -> Content {
                             data: m.value.to_string(),
                         }
                     }
+                    crate::types::threads::MessageContentType::File => {
+                        match FilePart::from_message_part(m) {
+                            Some(FilePart {
+                                mime_type,
+                                source: FileSource::Base64(data),
+                                ..
+                            }) => Part::InlineData {
+                                mime_type,
+                                data: data.to_string(),
+                            },
+                            Some(FilePart {
+                                mime_type,
+                                source: FileSource::Url(url),
+                                ..
+                            }) => Part::FileData {
+                                mime_type,
+                                file_uri: url.to_string(),
+                            },
+                            None => continue,
+                        }
+                    }
                 };
                 parts.push(msg.into())
             }
@@ -1065,7 +1277,8 @@ fn construct_user_message(m: &InnerMessage) -> Content {
                 parts,
             }
         }
-    }
+    };
+    Ok(content)
 }
 
 pub fn record_map_err(e: impl Into<GatewayError> + ToString, span: tracing::Span) -> GatewayError {
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 
+use crate::types::gateway;
 use crate::types::gateway::FunctionParameters as FP;
+pub use crate::types::gateway::{SafetySetting, ThinkingConfig};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -19,13 +21,50 @@ pub struct GenerateContentRequest {
     pub contents: Vec<Content>,
     pub generation_config: Option<GenerationConfig>,
     pub tools: Option<Vec<Tools>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_config: Option<ToolConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_settings: Option<Vec<SafetySetting>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolConfig {
+    pub function_calling_config: FunctionCallingConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FunctionCallingConfig {
+    pub mode: FunctionCallingMode,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub allowed_function_names: Option<Vec<String>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum FunctionCallingMode {
+    Auto,
+    Any,
+    None,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Tools {
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub function_declarations: Option<Vec<FunctionDeclaration>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub google_search: Option<GoogleSearch>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_execution: Option<CodeExecution>,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct GoogleSearch {}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct CodeExecution {}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Content {
     pub role: Role,
@@ -46,6 +85,7 @@ impl Content {
             parts: vec![PartWithThought {
                 part: part.into(),
                 thought_signature: None,
+                thought: None,
             }],
         }
     }
@@ -55,6 +95,7 @@ impl Content {
             parts: vec![PartWithThought {
                 part: part.into(),
                 thought_signature: None,
+                thought: None,
             }],
         }
     }
@@ -89,6 +130,8 @@ pub struct GenerationConfig {
     pub logprobs: Option<i32>,
     pub response_mime_type: Option<String>,
     pub response_schema: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_config: Option<ThinkingConfig>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -97,6 +140,15 @@ pub struct PartWithThought {
     #[serde(flatten)]
     pub part: Part,
     pub thought_signature: Option<String>,
+    /// Set on parts carrying the model's thought summary
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thought: Option<bool>,
+}
+
+impl PartWithThought {
+    pub fn is_thought(&self) -> bool {
+        self.thought.unwrap_or(false)
+    }
 }
 
 impl From<Part> for PartWithThought {
@@ -104,6 +156,7 @@ impl From<Part> for PartWithThought {
         Self {
             part,
             thought_signature: None,
+            thought: None,
         }
     }
 }
@@ -128,6 +181,15 @@ pub enum Part {
         name: String,
         response: Option<PartFunctionResponse>,
     },
+    ExecutableCode {
+        language: String,
+        code: String,
+    },
+    CodeExecutionResult {
+        outcome: String,
+        #[serde(default)]
+        output: String,
+    },
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -153,12 +215,64 @@ pub struct Candidate {
     pub citation_metadata: Option<CitationMetadata>,
     pub safety_ratings: Option<Vec<SafetyRating>>,
     pub finish_reason: Option<FinishReason>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grounding_metadata: Option<GroundingMetadata>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub struct SafetyRating {
     pub category: String,
     pub probability: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<bool>,
+}
+
+/// Search grounding returned with a candidate, see [`gateway::GroundingMetadata`].
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundingMetadata {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub web_search_queries: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grounding_chunks: Vec<GroundingChunk>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grounding_supports: Vec<GroundingSupport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct GroundingChunk {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web: Option<WebChunk>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WebChunk {
+    #[serde(default)]
+    pub uri: String,
+    #[serde(default)]
+    pub title: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct GroundingSupport {
+    pub segment: Segment,
+    #[serde(default)]
+    pub grounding_chunk_indices: Vec<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Segment {
+    #[serde(default)]
+    pub start_index: i32,
+    #[serde(default)]
+    pub end_index: i32,
+    #[serde(default)]
+    pub text: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -180,12 +294,15 @@ pub enum FinishReason {
     TooManyToolCalls, // Token generation stopped because too many tool calls were generated.
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
 #[serde(rename_all = "camelCase")]
 pub struct Citation {
-    start_index: i32,
-    end_index: i32,
-    uri: String,
+    #[serde(default)]
+    pub start_index: i32,
+    #[serde(default)]
+    pub end_index: i32,
+    #[serde(default)]
+    pub uri: String,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -194,6 +311,55 @@ pub struct CitationMetadata {
     pub citations: Vec<Citation>,
 }
 
+impl From<SafetyRating> for gateway::SafetyRating {
+    fn from(rating: SafetyRating) -> Self {
+        Self {
+            category: rating.category,
+            probability: rating.probability,
+            blocked: rating.blocked,
+        }
+    }
+}
+
+impl From<GroundingMetadata> for gateway::GroundingMetadata {
+    fn from(grounding: GroundingMetadata) -> Self {
+        Self {
+            web_search_queries: grounding.web_search_queries,
+            grounding_chunks: grounding
+                .grounding_chunks
+                .into_iter()
+                .map(|chunk| gateway::GroundingChunk {
+                    web: chunk.web.map(|web| gateway::WebChunk {
+                        uri: web.uri,
+                        title: web.title,
+                    }),
+                })
+                .collect(),
+            grounding_supports: grounding
+                .grounding_supports
+                .into_iter()
+                .map(|support| gateway::GroundingSupport {
+                    segment: gateway::TextSegment {
+                        start_index: support.segment.start_index,
+                        end_index: support.segment.end_index,
+                        text: support.segment.text,
+                    },
+                    grounding_chunk_indices: support.grounding_chunk_indices,
+                })
+                .collect(),
+            citations: grounding
+                .citations
+                .into_iter()
+                .map(|citation| gateway::Citation {
+                    start_index: citation.start_index,
+                    end_index: citation.end_index,
+                    uri: citation.uri,
+                })
+                .collect(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct UsageMetadata {
@@ -393,4 +559,54 @@ mod tests {
             FinishReason::MaxTokens
         );
     }
+
+    #[test]
+    fn test_grounding_and_safety_ratings() {
+        let candidate = r#"
+        {
+            "content": { "role": "model", "parts": [{ "text": "Spain won Euro 2024." }] },
+            "finishReason": "STOP",
+            "safetyRatings": [
+                { "category": "HARM_CATEGORY_HATE_SPEECH", "probability": "NEGLIGIBLE" }
+            ],
+            "groundingMetadata": {
+                "webSearchQueries": ["who won euro 2024"],
+                "groundingChunks": [{ "web": { "uri": "https://example.com", "title": "example.com" } }],
+                "groundingSupports": [
+                    {
+                        "segment": { "endIndex": 20, "text": "Spain won Euro 2024." },
+                        "groundingChunkIndices": [0],
+                        "confidenceScores": [0.98]
+                    }
+                ]
+            }
+        }
+        "#;
+
+        let candidate = serde_json::from_str::<Candidate>(candidate).unwrap();
+        let ratings = candidate.safety_ratings.unwrap();
+        assert_eq!(ratings[0].probability, "NEGLIGIBLE");
+
+        let grounding = gateway::GroundingMetadata::from(candidate.grounding_metadata.unwrap());
+        assert_eq!(grounding.grounding_supports[0].segment.end_index, 20);
+        assert_eq!(
+            serde_json::to_value(&grounding).unwrap()["grounding_chunks"][0]["web"]["uri"],
+            "https://example.com"
+        );
+    }
+
+    #[test]
+    fn test_code_execution_parts() {
+        let part = serde_json::from_str::<PartWithThought>(
+            r#"{ "codeExecutionResult": { "outcome": "OUTCOME_OK", "output": "42" } }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            part.part,
+            Part::CodeExecutionResult {
+                outcome: "OUTCOME_OK".to_string(),
+                output: "42".to_string()
+            }
+        );
+    }
 }
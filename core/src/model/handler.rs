@@ -1,28 +1,154 @@
 use std::collections::HashMap;
+use std::future::Future;
+use std::time::{Duration, Instant};
 
 use crate::{
     error::GatewayError,
-    events::{JsonValue, RecordResult},
+    events::RecordResult,
+    types::gateway::{ToolChoice, ToolChoiceMode, ToolErrorPolicy, ToolLoopOptions},
     GatewayResult,
 };
 
 use super::{
-    types::{ModelEvent, ModelEventType, ModelToolCall, ToolResultEvent, ToolStartEvent},
+    error::ModelError,
+    types::{
+        ModelEvent, ModelEventType, ModelToolCall, ToolOutput, ToolResultEvent, ToolStartEvent,
+    },
     Tool,
 };
 use opentelemetry::propagation::Injector;
+use parking_lot::Mutex;
 use serde_json::Value;
-use tracing::Span;
+use tracing::{Instrument, Span};
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
-// macro_rules! target {
-//     () => {
-//         "langdb::user_tracing::models"
-//     };
-//     ($subtgt:literal) => {
-//         concat!("langdb::user_tracing::models::", $subtgt)
-//     };
-// }
+macro_rules! target {
+    () => {
+        "langdb::user_tracing::models"
+    };
+    ($subtgt:literal) => {
+        concat!("langdb::user_tracing::models::", $subtgt)
+    };
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum ToolLoopStop {
+    MaxIterations(u32),
+    Deadline(u64),
+    ToolFailed(String, String),
+}
+
+impl From<ToolLoopStop> for ModelError {
+    fn from(stop: ToolLoopStop) -> Self {
+        match stop {
+            ToolLoopStop::MaxIterations(max) => ModelError::MaxToolIterationsReached(max),
+            ToolLoopStop::Deadline(secs) => ModelError::ToolLoopDeadlineExceeded(secs),
+            ToolLoopStop::ToolFailed(tool, error) => ModelError::ToolCallFailed(tool, error),
+        }
+    }
+}
+
+/// Enforces the request's tool loop limits over all model calls of a single execution.
+/// Providers create one per `execute`/`execute_stream` and call [`ToolLoopGuard::next_iteration`]
+/// before sending tool results back to the model.
+pub(crate) struct ToolLoopGuard {
+    options: ToolLoopOptions,
+    started_at: Instant,
+    iteration: u32,
+    stop: Mutex<Option<ToolLoopStop>>,
+}
+
+impl ToolLoopGuard {
+    pub(crate) fn new(options: Option<&ToolLoopOptions>) -> Self {
+        Self {
+            options: options.cloned().unwrap_or_default(),
+            started_at: Instant::now(),
+            iteration: 0,
+            stop: Mutex::new(None),
+        }
+    }
+
+    /// Number of completed tool iterations.
+    pub(crate) fn iteration(&self) -> u32 {
+        self.iteration
+    }
+
+    pub(crate) fn next_iteration(&mut self) -> Result<(), ModelError> {
+        self.iteration += 1;
+        if let Some(stop) = self.stop.lock().take() {
+            return Err(stop.into());
+        }
+        self.check_deadline().map_err(ModelError::from)
+    }
+
+    /// Tool choice for the next model call. A forced choice only applies to the first call,
+    /// forcing it again after the tool results would loop until `max_iterations`.
+    pub(crate) fn tool_choice(&self, requested: Option<&ToolChoice>) -> Option<ToolChoice> {
+        match requested {
+            Some(ToolChoice::Mode(ToolChoiceMode::Required) | ToolChoice::Named(_))
+                if self.iteration > 0 =>
+            {
+                Some(ToolChoice::Mode(ToolChoiceMode::Auto))
+            }
+            requested => requested.cloned(),
+        }
+    }
+
+    /// Runs the tool calls of one iteration, concurrently unless the request disabled it.
+    pub(crate) async fn run_tools<F: Future>(
+        &self,
+        calls: impl IntoIterator<Item = F>,
+    ) -> Vec<F::Output> {
+        // Collecting first keeps the borrowing closures out of the awaited future,
+        // which otherwise isn't `Send` for every lifetime
+        let calls: Vec<F> = calls.into_iter().collect();
+        if self.options.parallel {
+            futures::future::join_all(calls).await
+        } else {
+            let mut results = vec![];
+            for call in calls {
+                results.push(call.await);
+            }
+            results
+        }
+    }
+
+    fn remaining(&self) -> Option<Duration> {
+        self.options
+            .deadline_secs
+            .map(|secs| Duration::from_secs(secs).saturating_sub(self.started_at.elapsed()))
+    }
+
+    fn check_deadline(&self) -> Result<(), ToolLoopStop> {
+        match (self.remaining(), self.options.deadline_secs) {
+            (Some(remaining), Some(secs)) if remaining.is_zero() => {
+                Err(ToolLoopStop::Deadline(secs))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Checks whether another tool call may start in the current iteration.
+    fn check_limits(&self) -> Result<(), ToolLoopStop> {
+        if self.iteration >= self.options.max_iterations {
+            return Err(ToolLoopStop::MaxIterations(self.options.max_iterations));
+        }
+        self.check_deadline()
+    }
+
+    /// Time a single tool call may take, bounded by the overall deadline.
+    fn tool_timeout(&self) -> Option<Duration> {
+        let per_tool = self.options.tool_timeout_secs.map(Duration::from_secs);
+        match (per_tool, self.remaining()) {
+            (Some(per_tool), Some(remaining)) => Some(per_tool.min(remaining)),
+            (per_tool, remaining) => per_tool.or(remaining),
+        }
+    }
+
+    fn stop_with(&self, stop: ToolLoopStop) {
+        self.stop.lock().get_or_insert(stop);
+    }
+}
 
 pub(crate) struct LlmToolCallCarrier<'a> {
     properties: &'a mut HashMap<String, String>,
@@ -45,18 +171,24 @@ pub(crate) async fn handle_tool_call(
     tools: &HashMap<String, Box<dyn Tool>>,
     tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
     mut tags: HashMap<String, String>,
-) -> GatewayResult<String> {
+    guard: &ToolLoopGuard,
+) -> GatewayResult<ToolOutput> {
     let tool_name = tool_use.tool_name.clone();
     let arguments = tool_use.input.clone();
+    if let Err(stop) = guard.check_limits() {
+        guard.stop_with(stop.clone());
+        return Err(ModelError::from(stop).into());
+    }
     let arguments_value = serde_json::from_str::<HashMap<String, Value>>(&arguments)?;
-    // let span = tracing::info_span!(
-    //     target: target!("tool"),
-    //     crate::events::SPAN_TOOL,
-    //     tool_name = tool_name,
-    //     arguments = arguments.to_string(),
-    //     output = tracing::field::Empty,
-    //     error = tracing::field::Empty,
-    // );
+    let span = tracing::info_span!(
+        target: target!("tool"),
+        crate::events::SPAN_TOOL,
+        tool_name = tool_name,
+        iteration = guard.iteration() + 1,
+        arguments = arguments.to_string(),
+        output = tracing::field::Empty,
+        error = tracing::field::Empty,
+    );
     let tool = tools
         .get(&tool_name)
         .ok_or(GatewayError::CustomError(format!(
@@ -79,9 +211,26 @@ pub(crate) async fn handle_tool_call(
             propagator.inject_context(&span_context, &mut LlmToolCallCarrier::new(&mut tags))
         });
 
-        let result = tool.run(arguments_value, tags).await;
-        let _ = result.as_ref().map(JsonValue).record();
-        let result = result.map(|v| v.to_string());
+        let result = match guard.tool_timeout() {
+            Some(timeout) => {
+                tokio::time::timeout(timeout, tool.run_with_output(arguments_value, tags))
+                    .await
+                    .unwrap_or_else(|_| {
+                        Err(GatewayError::CustomError(format!(
+                            "Tool {tool_name} timed out after {}s",
+                            timeout.as_secs_f32()
+                        )))
+                    })
+            }
+            None => tool.run_with_output(arguments_value, tags).await,
+        };
+        let _ = result.as_ref().map(|output| output.text.as_str()).record();
+        if let Err(e) = &result {
+            Span::current().record("error", e.to_string());
+            if guard.options.on_tool_error == ToolErrorPolicy::Stop {
+                guard.stop_with(ToolLoopStop::ToolFailed(tool_name.clone(), e.to_string()));
+            }
+        }
         tx.send(Some(ModelEvent::new(
             &Span::current(),
             ModelEventType::ToolResult(ToolResultEvent {
@@ -90,7 +239,7 @@ pub(crate) async fn handle_tool_call(
                 is_error: result.is_err(),
                 output: result
                     .as_ref()
-                    .map(|r| r.to_string())
+                    .map(|r| r.text.clone())
                     .unwrap_or_else(|err| err.to_string()),
             }),
         )))
@@ -98,6 +247,45 @@ pub(crate) async fn handle_tool_call(
         .map_err(|e| GatewayError::CustomError(e.to_string()))?;
         result
     }
-    // .instrument(span.or_current())
+    .instrument(span)
     .await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tool_loop_guard_limits() {
+        let options = ToolLoopOptions {
+            max_iterations: 1,
+            ..Default::default()
+        };
+        let mut guard = ToolLoopGuard::new(Some(&options));
+        assert!(guard.check_limits().is_ok());
+        assert!(guard.next_iteration().is_ok());
+        assert_eq!(guard.check_limits(), Err(ToolLoopStop::MaxIterations(1)));
+
+        guard.stop_with(ToolLoopStop::ToolFailed("search".into(), "boom".into()));
+        assert!(matches!(
+            guard.next_iteration(),
+            Err(ModelError::ToolCallFailed(tool, _)) if tool == "search"
+        ));
+    }
+
+    #[test]
+    fn test_tool_loop_is_bounded_by_default() {
+        let options: ToolLoopOptions = serde_json::from_str("{}").unwrap();
+        assert_eq!(options, ToolLoopOptions::default());
+
+        let mut guard = ToolLoopGuard::new(None);
+        for _ in 0..options.max_iterations {
+            assert!(guard.check_limits().is_ok());
+            guard.next_iteration().unwrap();
+        }
+        assert_eq!(
+            guard.check_limits(),
+            Err(ToolLoopStop::MaxIterations(options.max_iterations))
+        );
+    }
+}
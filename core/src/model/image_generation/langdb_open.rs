@@ -2,7 +2,7 @@ use crate::model::async_trait;
 use crate::model::error::ModelError;
 use crate::model::openai_spec_client::openai_spec_client;
 use crate::model::types::ModelEvent;
-use crate::types::credentials::ApiKeyCredentials;
+use crate::types::credentials::{ApiKeyCredentials, ClientOptions};
 use crate::types::gateway::CreateImageRequest;
 use crate::types::image::ImagesResponse;
 use crate::GatewayResult;
@@ -24,8 +24,12 @@ impl OpenAISpecModel {
         endpoint: Option<&str>,
         provider_name: &str,
     ) -> Result<Self, ModelError> {
-        let client: Client<OpenAIConfig> =
-            openai_spec_client(credentials, endpoint, provider_name)?;
+        let client: Client<OpenAIConfig> = openai_spec_client(
+            credentials,
+            endpoint,
+            provider_name,
+            &ClientOptions::default(),
+        )?;
         let openai_model = OpenAIImageGeneration::new(credentials, Some(client), None)?;
 
         Ok(Self { openai_model })
@@ -0,0 +1,490 @@
+use std::io::Cursor;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+
+use super::error::ModelError;
+use super::files::parse_data_url;
+use crate::types::threads::{Message, MessageContentType};
+
+pub const DEFAULT_MAX_IMAGE_BYTES: usize = 20 * 1024 * 1024;
+pub const DEFAULT_FETCH_TIMEOUT_SECS: u64 = 10;
+const ALLOWED_HOSTS_ENV: &str = "LANGDB_IMAGE_ALLOWED_HOSTS";
+const MAX_REDIRECTS: usize = 5;
+/// Bounds for decoding untrusted images before they are downscaled.
+const MAX_DECODED_DIMENSION: u32 = 16_384;
+const MAX_DECODE_ALLOC: u64 = 256 * 1024 * 1024;
+
+/// Limits applied when image urls are ingested for providers that only take inline images.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ImageOptions {
+    #[serde(default = "default_max_bytes")]
+    pub max_bytes: usize,
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Hosts remote images may be downloaded from, subdomains included.
+    /// Any public host is allowed when unset.
+    #[serde(default = "default_allowed_hosts")]
+    pub allowed_hosts: Option<Vec<String>>,
+    /// Images with a larger width or height are downscaled to fit.
+    pub max_dimension: Option<u32>,
+}
+
+fn default_max_bytes() -> usize {
+    DEFAULT_MAX_IMAGE_BYTES
+}
+
+fn default_timeout_secs() -> u64 {
+    DEFAULT_FETCH_TIMEOUT_SECS
+}
+
+fn default_allowed_hosts() -> Option<Vec<String>> {
+    std::env::var(ALLOWED_HOSTS_ENV).ok().map(|hosts| {
+        hosts
+            .split(',')
+            .map(|h| h.trim().to_lowercase())
+            .filter(|h| !h.is_empty())
+            .collect()
+    })
+}
+
+impl Default for ImageOptions {
+    fn default() -> Self {
+        Self {
+            max_bytes: DEFAULT_MAX_IMAGE_BYTES,
+            timeout_secs: DEFAULT_FETCH_TIMEOUT_SECS,
+            allowed_hosts: default_allowed_hosts(),
+            max_dimension: None,
+        }
+    }
+}
+
+impl ImageOptions {
+    pub fn is_host_allowed(&self, host: &str) -> bool {
+        let host = host.to_lowercase();
+        match &self.allowed_hosts {
+            None => true,
+            Some(allowed) => allowed
+                .iter()
+                .any(|a| host == *a || host.ends_with(&format!(".{a}"))),
+        }
+    }
+}
+
+/// Base64 encoded image with its detected mime type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ImageData {
+    pub mime_type: String,
+    pub data: String,
+}
+
+impl ImageData {
+    pub fn from_bytes(bytes: &[u8], mime_type: Option<&str>) -> Result<Self, ModelError> {
+        let mime_type = sniff_mime_type(bytes)
+            .map(|m| m.to_string())
+            .or(mime_type
+                .filter(|m| m.starts_with("image/"))
+                .map(|m| m.to_string()))
+            .ok_or(ModelError::CustomError(
+                "Unsupported image format".to_string(),
+            ))?;
+
+        Ok(Self {
+            mime_type,
+            data: base64::engine::general_purpose::STANDARD.encode(bytes),
+        })
+    }
+
+    /// Reads an inline image, either a data url or raw base64 data.
+    /// Remote urls have to be resolved with [`resolve_images`] first.
+    pub fn from_url(url: &str) -> Result<Self, ModelError> {
+        if is_remote_url(url) {
+            return Err(ModelError::CustomError(format!(
+                "Remote image {url} was not downloaded"
+            )));
+        }
+
+        let (declared, data) = match parse_data_url(url) {
+            Some((mime_type, data)) => (Some(mime_type), data),
+            None => (None, url),
+        };
+
+        // The first 24 base64 characters decode to enough bytes for every signature
+        let prefix = data.as_bytes().get(..24).unwrap_or(data.as_bytes());
+        let sniffed = base64::engine::general_purpose::STANDARD
+            .decode(prefix)
+            .ok()
+            .and_then(|bytes| sniff_mime_type(&bytes));
+
+        let mime_type = sniffed
+            .or(declared.filter(|m| m.starts_with("image/")))
+            .unwrap_or("image/png");
+
+        Ok(Self {
+            mime_type: mime_type.to_string(),
+            data: data.to_string(),
+        })
+    }
+
+    pub fn bytes(&self) -> Result<Vec<u8>, ModelError> {
+        base64::engine::general_purpose::STANDARD
+            .decode(&self.data)
+            .map_err(|e| ModelError::CustomError(format!("Invalid image data: {e}")))
+    }
+
+    pub fn to_data_url(&self) -> String {
+        format!("data:{};base64,{}", self.mime_type, self.data)
+    }
+
+    /// Downscales the image so that neither side exceeds `max_dimension`.
+    /// Decoding runs on the blocking pool since images can be large.
+    pub async fn downscale(self, max_dimension: u32) -> Result<Self, ModelError> {
+        tokio::task::spawn_blocking(move || self.downscale_blocking(max_dimension))
+            .await
+            .map_err(|e| ModelError::CustomError(format!("Failed to resize image: {e}")))?
+    }
+
+    fn downscale_blocking(self, max_dimension: u32) -> Result<Self, ModelError> {
+        let bytes = self.bytes()?;
+        let mut limits = image::Limits::default();
+        limits.max_image_width = Some(MAX_DECODED_DIMENSION);
+        limits.max_image_height = Some(MAX_DECODED_DIMENSION);
+        limits.max_alloc = Some(MAX_DECODE_ALLOC);
+
+        let mut reader = image::ImageReader::new(Cursor::new(bytes))
+            .with_guessed_format()
+            .map_err(|e| ModelError::CustomError(format!("Failed to read image: {e}")))?;
+        reader.limits(limits);
+        let image = reader
+            .decode()
+            .map_err(|e| ModelError::CustomError(format!("Failed to decode image: {e}")))?;
+        if image.width() <= max_dimension && image.height() <= max_dimension {
+            return Ok(self);
+        }
+
+        let (format, mime_type) = match self.mime_type.as_str() {
+            "image/jpeg" => (image::ImageFormat::Jpeg, "image/jpeg"),
+            _ => (image::ImageFormat::Png, "image/png"),
+        };
+
+        let mut resized = Vec::new();
+        image
+            .resize(
+                max_dimension,
+                max_dimension,
+                image::imageops::FilterType::Triangle,
+            )
+            .write_to(&mut Cursor::new(&mut resized), format)
+            .map_err(|e| ModelError::CustomError(format!("Failed to encode image: {e}")))?;
+
+        Ok(Self {
+            mime_type: mime_type.to_string(),
+            data: base64::engine::general_purpose::STANDARD.encode(resized),
+        })
+    }
+}
+
+pub fn sniff_mime_type(bytes: &[u8]) -> Option<&'static str> {
+    match bytes {
+        [0x89, b'P', b'N', b'G', ..] => Some("image/png"),
+        [0xFF, 0xD8, 0xFF, ..] => Some("image/jpeg"),
+        [b'G', b'I', b'F', b'8', ..] => Some("image/gif"),
+        [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'E', b'B', b'P', ..] => Some("image/webp"),
+        _ => None,
+    }
+}
+
+fn is_remote_url(url: &str) -> bool {
+    url.starts_with("http://") || url.starts_with("https://")
+}
+
+/// Whether the address is routable on the public internet. Remote image urls
+/// must not reach loopback, private or link-local services of the gateway host.
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [a, b, ..] = ip.octets();
+            !(ip.is_private()
+                || ip.is_loopback()
+                || ip.is_link_local()
+                || ip.is_unspecified()
+                || ip.is_broadcast()
+                || ip.is_multicast()
+                || ip.is_documentation()
+                || a == 0
+                // Carrier-grade NAT, 100.64.0.0/10
+                || (a == 100 && (b & 0xC0) == 64))
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_public_ip(IpAddr::V4(ip)),
+            None => {
+                let first = ip.segments()[0];
+                !(ip.is_loopback()
+                    || ip.is_unspecified()
+                    || ip.is_multicast()
+                    // Unique local, fc00::/7
+                    || (first & 0xFE00) == 0xFC00
+                    // Link-local, fe80::/10
+                    || (first & 0xFFC0) == 0xFE80)
+            }
+        },
+    }
+}
+
+/// Builds a client for a single hop, pinned to an address of the url's host
+/// that was checked to be public, so a second DNS lookup can't swap it out.
+async fn pinned_client(
+    url: &reqwest::Url,
+    options: &ImageOptions,
+) -> Result<reqwest::Client, ModelError> {
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(ModelError::CustomError(format!(
+            "Unsupported image url scheme {}",
+            url.scheme()
+        )));
+    }
+
+    let host = url.host_str().unwrap_or_default();
+    if !options.is_host_allowed(host) {
+        return Err(ModelError::CustomError(format!(
+            "Image host {host} is not allowed"
+        )));
+    }
+
+    let port = url.port_or_known_default().unwrap_or(443);
+    // Ip literals are checked as they are, only domains go through DNS
+    let literal = host.trim_start_matches('[').trim_end_matches(']');
+    let (domain, addrs): (Option<&str>, Vec<SocketAddr>) = match literal.parse::<IpAddr>() {
+        Ok(ip) => (None, vec![SocketAddr::new(ip, port)]),
+        Err(_) => {
+            let addrs = tokio::time::timeout(
+                Duration::from_secs(options.timeout_secs),
+                tokio::net::lookup_host((host, port)),
+            )
+            .await
+            .map_err(|_| ModelError::CustomError(format!("Timed out resolving {host}")))?
+            .map_err(|e| ModelError::CustomError(format!("Failed to resolve {host}: {e}")))?
+            .collect();
+            (Some(host), addrs)
+        }
+    };
+
+    let addr = match addrs.first() {
+        Some(addr) if addrs.iter().all(|a| is_public_ip(a.ip())) => *addr,
+        Some(_) => {
+            return Err(ModelError::CustomError(format!(
+                "Image host {host} resolves to a non-public address"
+            )))
+        }
+        None => {
+            return Err(ModelError::CustomError(format!(
+                "Image host {host} could not be resolved"
+            )))
+        }
+    };
+
+    let mut builder = reqwest::Client::builder()
+        .timeout(Duration::from_secs(options.timeout_secs))
+        .redirect(reqwest::redirect::Policy::none());
+    if let Some(domain) = domain {
+        builder = builder.resolve(domain, addr);
+    }
+    builder
+        .build()
+        .map_err(|e| ModelError::CustomError(e.to_string()))
+}
+
+pub async fn fetch_image(url: &str, options: &ImageOptions) -> Result<ImageData, ModelError> {
+    let mut current = reqwest::Url::parse(url)
+        .map_err(|e| ModelError::CustomError(format!("Invalid image url {url}: {e}")))?;
+
+    // Redirects are followed by hand so every hop goes through the same checks
+    let mut hops = 0;
+    let mut response = loop {
+        let client = pinned_client(&current, options).await?;
+        let response = client
+            .get(current.clone())
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ModelError::CustomError(format!("Failed to download image {url}: {e}")))?;
+        if !response.status().is_redirection() {
+            break response;
+        }
+
+        hops += 1;
+        if hops > MAX_REDIRECTS {
+            return Err(ModelError::CustomError(format!(
+                "Image {url} redirected too many times"
+            )));
+        }
+        let location = response
+            .headers()
+            .get(reqwest::header::LOCATION)
+            .and_then(|v| v.to_str().ok())
+            .ok_or(ModelError::CustomError(format!(
+                "Image {url} redirected without a location"
+            )))?;
+        current = current
+            .join(location)
+            .map_err(|e| ModelError::CustomError(format!("Invalid redirect for {url}: {e}")))?;
+    };
+
+    let too_large =
+        || ModelError::CustomError(format!("Image {url} exceeds {} bytes", options.max_bytes));
+    if response
+        .content_length()
+        .is_some_and(|l| l as usize > options.max_bytes)
+    {
+        return Err(too_large());
+    }
+
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or(v).trim().to_string());
+
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| ModelError::CustomError(format!("Failed to download image {url}: {e}")))?
+    {
+        if bytes.len() + chunk.len() > options.max_bytes {
+            return Err(too_large());
+        }
+        bytes.extend_from_slice(&chunk);
+    }
+
+    ImageData::from_bytes(&bytes, content_type.as_deref())
+}
+
+/// Replaces remote image urls with inline data urls and applies downscaling,
+/// for providers that only accept base64 images.
+pub async fn resolve_images(
+    mut messages: Vec<Message>,
+    options: &ImageOptions,
+) -> Result<Vec<Message>, ModelError> {
+    for message in messages.iter_mut() {
+        for part in message.content_array.iter_mut() {
+            if part.r#type != MessageContentType::ImageUrl {
+                continue;
+            }
+
+            let image = if is_remote_url(&part.value) {
+                fetch_image(&part.value, options).await?
+            } else if options.max_dimension.is_some() {
+                ImageData::from_url(&part.value)?
+            } else {
+                continue;
+            };
+
+            let image = match options.max_dimension {
+                Some(max_dimension) => image.downscale(max_dimension).await?,
+                None => image,
+            };
+            part.value = image.to_data_url();
+        }
+    }
+
+    Ok(messages)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_image_mime_type() {
+        let jpeg = base64::engine::general_purpose::STANDARD.encode([0xFF, 0xD8, 0xFF, 0xE0, 0]);
+        let image = ImageData::from_url(&format!("data:image/png;base64,{jpeg}")).unwrap();
+        assert_eq!(image.mime_type, "image/jpeg");
+        assert_eq!(image.data, jpeg);
+
+        let webp = base64::engine::general_purpose::STANDARD.encode(b"RIFF\0\0\0\0WEBPVP8 ");
+        assert_eq!(ImageData::from_url(&webp).unwrap().mime_type, "image/webp");
+
+        let image = ImageData::from_url("data:image/gif;base64,AAAA").unwrap();
+        assert_eq!(image.mime_type, "image/gif");
+
+        assert!(ImageData::from_url("https://example.com/cat.png").is_err());
+        assert!(ImageData::from_bytes(b"not an image", Some("text/html")).is_err());
+
+        // Multi-byte characters around the sniffed prefix must not panic
+        let text = "ééééééééééééé";
+        assert_eq!(ImageData::from_url(text).unwrap().mime_type, "image/png");
+    }
+
+    #[tokio::test]
+    async fn test_downscale() {
+        let mut png = Vec::new();
+        image::DynamicImage::new_rgb8(64, 32)
+            .write_to(&mut Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        let image = ImageData::from_bytes(&png, None).unwrap();
+
+        let resized = image.downscale(16).await.unwrap();
+        let bytes = resized.bytes().unwrap();
+        let decoded = image::load_from_memory(&bytes).unwrap();
+        assert_eq!((decoded.width(), decoded.height()), (16, 8));
+
+        // Images whose header exceeds the decode limits are rejected
+        let mut gif = Vec::new();
+        image::DynamicImage::new_rgb8(64, 32)
+            .write_to(&mut Cursor::new(&mut gif), image::ImageFormat::Gif)
+            .unwrap();
+        gif[6..8].copy_from_slice(&u16::MAX.to_le_bytes());
+        let oversized = ImageData::from_bytes(&gif, None).unwrap();
+        let err = oversized.downscale(16).await.unwrap_err();
+        assert!(err.to_string().contains("exceeds limit"));
+    }
+
+    #[test]
+    fn test_allowed_hosts() {
+        let options = ImageOptions {
+            allowed_hosts: Some(vec!["example.com".to_string()]),
+            ..ImageOptions::default()
+        };
+        assert!(options.is_host_allowed("example.com"));
+        assert!(options.is_host_allowed("cdn.Example.com"));
+        assert!(!options.is_host_allowed("badexample.com"));
+    }
+
+    #[test]
+    fn test_non_public_ips() {
+        for ip in [
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "::1",
+            "fe80::1",
+            "fd00::1",
+            "::ffff:127.0.0.1",
+        ] {
+            assert!(!is_public_ip(ip.parse().unwrap()), "{ip}");
+        }
+        assert!(is_public_ip("93.184.216.34".parse().unwrap()));
+        assert!(is_public_ip("2606:2800:220:1::".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_fetch_rejects_private_hosts() {
+        let options = ImageOptions::default();
+        for url in [
+            "http://127.0.0.1/cat.png",
+            "http://169.254.169.254/latest/meta-data",
+            "http://[::1]:8080/cat.png",
+            "file:///etc/passwd",
+        ] {
+            assert!(fetch_image(url, &options).await.is_err(), "{url}");
+        }
+    }
+}
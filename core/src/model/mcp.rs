@@ -1,21 +1,30 @@
 use std::collections::HashMap;
 
+use futures::{SinkExt, StreamExt};
 use regex::Regex;
 use reqwest::header::HeaderMap;
 use rmcp::model::{
     CallToolRequest, CallToolRequestMethod, ClientRequest, Extensions, GetMeta, ServerResult,
 };
+use rmcp::model::{CallToolResult, RawContent, ResourceContents};
+use rmcp::transport::sink_stream::TransportAdapterSinkStream;
 use rmcp::transport::sse_client::SseClientConfig;
 use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
-use rmcp::transport::StreamableHttpClientTransport;
-use rmcp::ServiceError;
+use rmcp::transport::{IntoTransport, StreamableHttpClientTransport, TokioChildProcess};
 use rmcp::{model::CallToolRequestParam, transport::SseClientTransport, RoleClient};
+use rmcp::{ClientHandler, ServiceError};
+use tokio_tungstenite::tungstenite;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use tracing::debug;
 
+use super::images::ImageData;
+use super::mcp_pool::McpConnectionManager;
+use super::types::ToolOutput;
 use crate::types::gateway::{McpDefinition, McpTool, McpTransportType, ServerTools, ToolsFilter};
 use rmcp::service::DynService;
 use rmcp::service::RunningService;
 use rmcp::service::ServiceExt;
+use rmcp::service::{RxJsonRpcMessage, TxJsonRpcMessage};
 
 #[derive(Debug, thiserror::Error)]
 pub enum McpServerError {
@@ -52,8 +61,23 @@ pub enum McpServerError {
     #[error("No text content in tool {0} result")]
     NoTextInToolResult(String),
 
+    #[error("Tool {0} returned an error: {1}")]
+    ToolError(String, String),
+
     #[error("Join error: {0}")]
     JoinError(#[from] tokio::task::JoinError),
+
+    #[error("WebSocket error: {0}")]
+    WebSocketError(String),
+
+    #[error("Command {0} is not allowed for stdio MCP servers")]
+    CommandNotAllowed(String),
+
+    #[error("env and cwd of stdio MCP servers can only be set in the allowlist")]
+    StdioSettingsNotAllowed,
+
+    #[error("Invalid image in tool {0} result: {1}")]
+    InvalidImage(String, String),
 }
 
 impl From<rmcp::service::ClientInitializeError> for McpServerError {
@@ -68,6 +92,8 @@ impl From<rmcp::service::ServerInitializeError> for McpServerError {
     }
 }
 
+const ALLOWED_COMMANDS_ENV: &str = "LANGDB_MCP_ALLOWED_COMMANDS";
+
 fn validate_server_name(name: &str) -> Result<(), McpServerError> {
     match name {
         "websearch" | "Web Search" => Ok(()),
@@ -106,6 +132,14 @@ fn create_reqwest_client_with_headers(
 
 pub async fn get_transport(
     definition: &McpDefinition,
+) -> Result<RunningService<RoleClient, Box<dyn DynService<RoleClient>>>, McpServerError> {
+    connect(definition, ()).await
+}
+
+/// Starts a client session for the server with the given handler.
+pub async fn connect<H: ClientHandler>(
+    definition: &McpDefinition,
+    handler: H,
 ) -> Result<RunningService<RoleClient, Box<dyn DynService<RoleClient>>>, McpServerError> {
     match &definition.r#type {
         McpTransportType::Sse {
@@ -123,7 +157,7 @@ pub async fn get_transport(
             )
             .await?;
 
-            Ok(()
+            Ok(handler
                 .into_dyn()
                 .serve(transport)
                 .await
@@ -140,7 +174,7 @@ pub async fn get_transport(
                 StreamableHttpClientTransportConfig::with_uri(server_url.clone()),
             );
 
-            Ok(()
+            Ok(handler
                 .into_dyn()
                 .serve(transport)
                 .await
@@ -154,16 +188,158 @@ pub async fn get_transport(
             )
             .await?;
 
-            Ok(()
+            Ok(handler
+                .into_dyn()
+                .serve(transport)
+                .await
+                .map_err(|e| McpServerError::ClientStartError(e.to_string()))?)
+        }
+        McpTransportType::Ws {
+            server_url,
+            headers,
+            ..
+        } => {
+            let transport = websocket_transport(server_url, headers).await?;
+
+            Ok(handler
                 .into_dyn()
                 .serve(transport)
                 .await
                 .map_err(|e| McpServerError::ClientStartError(e.to_string()))?)
         }
-        _ => Err(McpServerError::InvalidServerName(
-            "Invalid or unsupported server type".to_string(),
-        )),
+        McpTransportType::Stdio {
+            command,
+            args,
+            env,
+            cwd,
+        } => {
+            if env.is_some() || cwd.is_some() {
+                return Err(McpServerError::StdioSettingsNotAllowed);
+            }
+            let Some(allowed) = allowed_command(command, args)? else {
+                return Err(McpServerError::CommandNotAllowed(
+                    std::iter::once(command)
+                        .chain(args)
+                        .cloned()
+                        .collect::<Vec<_>>()
+                        .join(" "),
+                ));
+            };
+
+            let mut cmd = tokio::process::Command::new(command);
+            cmd.args(args).envs(&allowed.env).kill_on_drop(true);
+            if let Some(cwd) = &allowed.cwd {
+                cmd.current_dir(cwd);
+            }
+            // The child is shut down and reaped when the session is closed
+            let transport = TokioChildProcess::new(cmd)?;
+
+            Ok(handler
+                .into_dyn()
+                .serve(transport)
+                .await
+                .map_err(|e| McpServerError::ClientStartError(e.to_string()))?)
+        }
+    }
+}
+
+/// A command line allowed for stdio servers, with the environment and working
+/// directory the operator launches it with.
+#[derive(Debug, Default, PartialEq, serde::Deserialize)]
+struct AllowedCommand {
+    command: String,
+    #[serde(default)]
+    args: Vec<String>,
+    #[serde(default)]
+    env: HashMap<String, String>,
+    cwd: Option<String>,
+}
+
+/// Looks up the command line in the allowlist, stdio servers are disabled when unset.
+/// The command and its arguments come from the request, so the whole line has to match.
+fn allowed_command(
+    command: &str,
+    args: &[String],
+) -> Result<Option<AllowedCommand>, McpServerError> {
+    let Ok(commands) = std::env::var(ALLOWED_COMMANDS_ENV) else {
+        return Ok(None);
+    };
+
+    Ok(parse_allowed_commands(&commands)?
+        .into_iter()
+        .find(|allowed| allowed.command == command && allowed.args == args))
+}
+
+/// Reads either comma separated command lines or a JSON array of commands with
+/// `env` and `cwd`.
+fn parse_allowed_commands(commands: &str) -> Result<Vec<AllowedCommand>, serde_json::Error> {
+    if commands.trim_start().starts_with('[') {
+        return serde_json::from_str(commands);
+    }
+
+    Ok(commands
+        .split(',')
+        .filter_map(|allowed| {
+            let mut allowed = allowed.split_whitespace();
+            Some(AllowedCommand {
+                command: allowed.next()?.to_string(),
+                args: allowed.map(str::to_string).collect(),
+                ..Default::default()
+            })
+        })
+        .collect())
+}
+
+async fn websocket_transport(
+    server_url: &str,
+    headers: &HashMap<String, String>,
+) -> Result<
+    impl IntoTransport<RoleClient, tungstenite::Error, TransportAdapterSinkStream>,
+    McpServerError,
+> {
+    let mut request = server_url
+        .into_client_request()
+        .map_err(|e| McpServerError::WebSocketError(e.to_string()))?;
+    for (key, value) in headers {
+        match (
+            key.parse::<reqwest::header::HeaderName>(),
+            value.parse::<reqwest::header::HeaderValue>(),
+        ) {
+            (Ok(header_name), Ok(header_value)) => {
+                request.headers_mut().insert(header_name, header_value);
+            }
+            _ => tracing::warn!("Invalid header: {:?}", (key, value)),
+        }
     }
+
+    let (stream, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .map_err(|e| McpServerError::WebSocketError(e.to_string()))?;
+    let (sink, stream) = stream.split();
+
+    let sink = sink.with(|message: TxJsonRpcMessage<RoleClient>| async move {
+        let text = serde_json::to_string(&message)
+            .map_err(|e| tungstenite::Error::Io(std::io::Error::other(e)))?;
+        Ok::<_, tungstenite::Error>(tungstenite::Message::Text(text.into()))
+    });
+    let stream = stream.filter_map(|message| async move {
+        let message = match message {
+            Ok(tungstenite::Message::Text(text)) => {
+                serde_json::from_str::<RxJsonRpcMessage<RoleClient>>(&text)
+            }
+            Ok(tungstenite::Message::Binary(data)) => serde_json::from_slice(&data),
+            Ok(_) => return None,
+            Err(e) => {
+                tracing::warn!("WebSocket receive error: {e}");
+                return None;
+            }
+        };
+        message
+            .inspect_err(|e| tracing::warn!("Invalid MCP message: {e}"))
+            .ok()
+    });
+
+    Ok((Box::pin(sink), Box::pin(stream)))
 }
 
 pub async fn get_tools(definitions: &[McpDefinition]) -> Result<Vec<ServerTools>, McpServerError> {
@@ -171,11 +347,7 @@ pub async fn get_tools(definitions: &[McpDefinition]) -> Result<Vec<ServerTools>
 
     for tool_def in definitions {
         let mcp_server_name = tool_def.server_name();
-        let client = get_transport(tool_def).await?;
-        let tools = client.list_tools(Default::default()).await?;
-        client.cancel().await?;
-
-        let mut tools = tools.tools;
+        let mut tools = McpConnectionManager::global().list_tools(tool_def).await?;
         let total_tools = tools.len();
 
         // Filter tools based on actions_filter if specified
@@ -228,11 +400,7 @@ pub async fn get_tools(definitions: &[McpDefinition]) -> Result<Vec<ServerTools>
 pub async fn get_raw_tools(
     definitions: &McpDefinition,
 ) -> Result<Vec<rmcp::model::Tool>, McpServerError> {
-    let client = get_transport(definitions).await?;
-    let tools = client.list_tools(Default::default()).await?;
-    client.cancel().await?;
-
-    Ok(tools.tools)
+    McpConnectionManager::global().list_tools(definitions).await
 }
 
 pub async fn execute_mcp_tool(
@@ -240,7 +408,7 @@ pub async fn execute_mcp_tool(
     tool: &rmcp::model::Tool,
     inputs: HashMap<String, serde_json::Value>,
     mut meta: Option<serde_json::Value>,
-) -> Result<String, McpServerError> {
+) -> Result<McpToolOutput, McpServerError> {
     if let McpTransportType::InMemory { .. } = def.r#type {
         if def.server_name() == "websearch" {
             if let Ok(var) = std::env::var("TAVILY_API_KEY") {
@@ -252,8 +420,6 @@ pub async fn execute_mcp_tool(
     }
     let name = tool.name.clone();
 
-    let client = get_transport(def).await?;
-
     let mut args = serde_json::Map::new();
 
     for (key, value) in inputs {
@@ -277,26 +443,179 @@ pub async fn execute_mcp_tool(
         }
     }
 
-    let response = client.send_request(t).await?;
-    client.cancel().await?;
+    let response = McpConnectionManager::global().send_request(def, t).await?;
 
     let response = match response {
         ServerResult::CallToolResult(result) => Ok(result),
         _ => Err(ServiceError::UnexpectedResponse),
     }?;
 
-    // Extract text from the response
-    if !response.content.is_empty() {
-        // Try to extract text from the first content item
-        if let Some(content) = response.content.first() {
-            // Access text content from the raw field
-            if let Some(text) = content.raw.as_text().map(|t| t.text.clone()) {
-                tracing::debug!("Tool {name}: execution completed successfully", name = name);
-                return Ok(text);
+    let output = McpToolOutput::from_result(&name, response)?;
+    tracing::debug!("Tool {name}: execution completed successfully", name = name);
+    Ok(output)
+}
+
+fn tool_image(name: &str, url: &str) -> Result<ImageData, McpServerError> {
+    ImageData::from_url(url)
+        .map_err(|e| McpServerError::InvalidImage(name.to_string(), e.to_string()))
+}
+
+/// Tool call result converted into the content sent back to the model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct McpToolOutput {
+    /// Text items and resource references, one per line
+    pub text: String,
+    pub images: Vec<ImageData>,
+    pub structured_content: Option<serde_json::Value>,
+}
+
+impl McpToolOutput {
+    pub fn from_result(name: &str, result: CallToolResult) -> Result<Self, McpServerError> {
+        let mut lines = vec![];
+        let mut images = vec![];
+        for content in result.content {
+            match content.raw {
+                RawContent::Text(text) => lines.push(text.text),
+                RawContent::Image(image) => images.push(tool_image(
+                    name,
+                    &format!("data:{};base64,{}", image.mime_type, image.data),
+                )?),
+                RawContent::Resource(resource) => match resource.resource {
+                    ResourceContents::TextResourceContents { uri, text, .. } => {
+                        lines.push(format!("[resource {uri}]\n{text}"))
+                    }
+                    ResourceContents::BlobResourceContents {
+                        uri,
+                        mime_type,
+                        blob,
+                        ..
+                    } => match mime_type {
+                        Some(mime_type) if mime_type.starts_with("image/") => images.push(
+                            tool_image(name, &format!("data:{mime_type};base64,{blob}"))?,
+                        ),
+                        mime_type => lines.push(format!(
+                            "[resource {uri} ({})]",
+                            mime_type.as_deref().unwrap_or("application/octet-stream")
+                        )),
+                    },
+                },
+                RawContent::ResourceLink(link) => {
+                    lines.push(format!("[resource {}: {}]", link.name, link.uri))
+                }
+                RawContent::Audio(_) => {
+                    tracing::warn!("Tool {name}: audio content is not supported, skipping")
+                }
             }
         }
+
+        let mut text = lines.join("\n");
+        if result.is_error.unwrap_or(false) {
+            return Err(McpServerError::ToolError(name.to_string(), text));
+        }
+
+        // Servers also send structured content serialized as text, prefer the JSON form
+        if let Some(structured) = &result.structured_content {
+            text = serde_json::to_string(structured)?;
+        }
+
+        if text.is_empty() && images.is_empty() {
+            tracing::error!("Tool {name}: No content in tool response");
+            return Err(McpServerError::NoTextInToolResult(name.to_string()));
+        }
+
+        Ok(Self {
+            text,
+            images,
+            structured_content: result.structured_content,
+        })
     }
+}
+
+impl From<McpToolOutput> for ToolOutput {
+    fn from(output: McpToolOutput) -> Self {
+        Self {
+            text: output.text,
+            images: output.images,
+        }
+    }
+}
 
-    tracing::error!("Tool {name}: No text content in tool response", name = name);
-    Err(McpServerError::NoTextInToolResult(name.to_string()))
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rmcp::model::Content;
+
+    #[test]
+    fn test_tool_output_from_result() {
+        let png = "iVBORw0KGgoAAAANSUhEUgAAAAEAAAAB";
+        let result = CallToolResult::success(vec![
+            Content::text("first"),
+            Content::image(png, "image/png"),
+            Content::text("second"),
+        ]);
+        let output = McpToolOutput::from_result("search", result).unwrap();
+        assert_eq!(output.text, "first\nsecond");
+        assert_eq!(output.images.len(), 1);
+        assert_eq!(output.images[0].mime_type, "image/png");
+
+        let result = CallToolResult::error(vec![Content::text("rate limited")]);
+        assert!(matches!(
+            McpToolOutput::from_result("search", result),
+            Err(McpServerError::ToolError(_, text)) if text == "rate limited"
+        ));
+    }
+
+    #[test]
+    fn test_stdio_command_allowlist() {
+        let allowed = parse_allowed_commands(
+            "npx -y @modelcontextprotocol/server-everything, uvx mcp-server-time",
+        )
+        .unwrap();
+        let is_listed = |command: &str, args: &[&str]| {
+            allowed
+                .iter()
+                .any(|allowed| allowed.command == command && allowed.args == args)
+        };
+
+        assert!(is_listed(
+            "npx",
+            &["-y", "@modelcontextprotocol/server-everything"]
+        ));
+        assert!(is_listed("uvx", &["mcp-server-time"]));
+        // The command name alone is not enough
+        assert!(!is_listed("npx", &["-y", "malicious-package"]));
+        assert!(!is_listed("uvx", &[]));
+        assert!(!is_listed("uvx", &["mcp-server-time", "--extra"]));
+
+        let allowed = parse_allowed_commands(
+            r#"[{"command": "uvx", "args": ["mcp-server-git"], "env": {"GIT_DIR": "/srv/repo/.git"}, "cwd": "/srv/repo"}]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            allowed,
+            vec![AllowedCommand {
+                command: "uvx".to_string(),
+                args: vec!["mcp-server-git".to_string()],
+                env: HashMap::from([("GIT_DIR".to_string(), "/srv/repo/.git".to_string())]),
+                cwd: Some("/srv/repo".to_string()),
+            }]
+        );
+        assert!(parse_allowed_commands("[{\"args\": []}]").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_stdio_rejects_request_env() {
+        let definition: McpDefinition = serde_json::from_value(serde_json::json!({
+            "type": "stdio",
+            "command": "uvx",
+            "args": ["mcp-server-time"],
+            "cwd": "/tmp"
+        }))
+        .unwrap();
+
+        assert!(matches!(
+            get_transport(&definition).await,
+            Err(McpServerError::StdioSettingsNotAllowed)
+        ));
+    }
 }
@@ -0,0 +1,329 @@
+use std::collections::HashMap;
+use std::sync::{Arc, LazyLock, Weak};
+use std::time::{Duration, Instant};
+
+use rmcp::model::{ClientRequest, Extensions, PingRequest, PingRequestMethod, ServerResult};
+use rmcp::service::{DynService, NotificationContext, RunningService};
+use rmcp::{ClientHandler, RoleClient, ServiceError};
+
+use super::mcp::{connect, McpServerError};
+use crate::types::gateway::McpDefinition;
+
+pub type McpClient = RunningService<RoleClient, Box<dyn DynService<RoleClient>>>;
+
+static MCP_CONNECTIONS: LazyLock<McpConnectionManager> =
+    LazyLock::new(|| McpConnectionManager::new(McpPoolOptions::default()));
+
+#[derive(Debug, Clone)]
+pub struct McpPoolOptions {
+    /// Sessions idle for longer than this are pinged before being reused
+    pub health_check_interval: Duration,
+    /// How long `tools/list` results are reused
+    pub tools_ttl: Duration,
+    pub max_connect_attempts: u32,
+    pub initial_backoff: Duration,
+    /// Sessions unused for longer than this are closed
+    pub idle_ttl: Duration,
+}
+
+impl Default for McpPoolOptions {
+    fn default() -> Self {
+        Self {
+            health_check_interval: Duration::from_secs(30),
+            tools_ttl: Duration::from_secs(300),
+            max_connect_attempts: 3,
+            initial_backoff: Duration::from_millis(200),
+            idle_ttl: Duration::from_secs(600),
+        }
+    }
+}
+
+struct Session {
+    client: Arc<McpClient>,
+    checked_at: Instant,
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        // Closing the service closes its transport, which waits for a stdio child to exit
+        self.client.cancellation_token().cancel();
+    }
+}
+
+struct CachedTools {
+    tools: Vec<rmcp::model::Tool>,
+    fetched_at: Instant,
+}
+
+struct McpConnection {
+    // Async lock so that concurrent requests wait for a single connect
+    session: tokio::sync::Mutex<Option<Session>>,
+    tools: parking_lot::Mutex<Option<CachedTools>>,
+    used_at: parking_lot::Mutex<Instant>,
+}
+
+impl Default for McpConnection {
+    fn default() -> Self {
+        Self {
+            session: Default::default(),
+            tools: Default::default(),
+            used_at: parking_lot::Mutex::new(Instant::now()),
+        }
+    }
+}
+
+impl McpConnection {
+    /// Idle connections nobody holds on to can be dropped, closing their session.
+    fn is_expired(self: &Arc<Self>, idle_ttl: Duration) -> bool {
+        Arc::strong_count(self) == 1 && self.used_at.lock().elapsed() >= idle_ttl
+    }
+
+    fn cached_tools(&self, ttl: Duration) -> Option<Vec<rmcp::model::Tool>> {
+        self.tools
+            .lock()
+            .as_ref()
+            .filter(|cached| cached.fetched_at.elapsed() < ttl)
+            .map(|cached| cached.tools.clone())
+    }
+
+    fn invalidate_tools(&self) {
+        self.tools.lock().take();
+    }
+}
+
+/// Client handler of pooled sessions, drops the cached tool list when the server reports changes.
+#[derive(Clone)]
+struct PooledClientHandler {
+    connection: Weak<McpConnection>,
+}
+
+impl ClientHandler for PooledClientHandler {
+    async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
+        if let Some(connection) = self.connection.upgrade() {
+            tracing::debug!("MCP tool list changed, invalidating cached tools");
+            connection.invalidate_tools();
+        }
+    }
+}
+
+/// Long-lived MCP client sessions shared across requests, keyed by [`McpTransportType::key`].
+///
+/// [`McpTransportType::key`]: crate::types::gateway::McpTransportType::key
+pub struct McpConnectionManager {
+    options: McpPoolOptions,
+    connections: parking_lot::Mutex<HashMap<String, Arc<McpConnection>>>,
+}
+
+impl McpConnectionManager {
+    pub fn new(options: McpPoolOptions) -> Self {
+        Self {
+            options,
+            connections: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn global() -> &'static McpConnectionManager {
+        &MCP_CONNECTIONS
+    }
+
+    fn connection(&self, definition: &McpDefinition) -> Arc<McpConnection> {
+        let mut connections = self.connections.lock();
+        connections.retain(|_, connection| !connection.is_expired(self.options.idle_ttl));
+
+        let connection = connections
+            .entry(definition.r#type.key())
+            .or_default()
+            .clone();
+        *connection.used_at.lock() = Instant::now();
+        connection
+    }
+
+    /// Returns a healthy session for the server, reconnecting when needed.
+    pub async fn client(
+        &self,
+        definition: &McpDefinition,
+    ) -> Result<Arc<McpClient>, McpServerError> {
+        let connection = self.connection(definition);
+        let mut session = connection.session.lock().await;
+
+        if let Some(current) = session.as_mut() {
+            if current.checked_at.elapsed() < self.options.health_check_interval {
+                return Ok(current.client.clone());
+            }
+
+            match ping(&current.client).await {
+                Ok(()) => {
+                    current.checked_at = Instant::now();
+                    return Ok(current.client.clone());
+                }
+                Err(e) => {
+                    tracing::warn!(
+                        "MCP session {} failed health check: {e}",
+                        definition.server_name()
+                    );
+                    session.take();
+                }
+            }
+        }
+
+        let handler = PooledClientHandler {
+            connection: Arc::downgrade(&connection),
+        };
+        let client = Arc::new(self.connect_with_backoff(definition, handler).await?);
+        // Tools may have changed while disconnected
+        connection.invalidate_tools();
+        *session = Some(Session {
+            client: client.clone(),
+            checked_at: Instant::now(),
+        });
+
+        Ok(client)
+    }
+
+    async fn connect_with_backoff(
+        &self,
+        definition: &McpDefinition,
+        handler: PooledClientHandler,
+    ) -> Result<McpClient, McpServerError> {
+        let mut backoff = self.options.initial_backoff;
+        let mut attempt = 1;
+        loop {
+            match connect(definition, handler.clone()).await {
+                Ok(client) => return Ok(client),
+                Err(e) if attempt < self.options.max_connect_attempts => {
+                    tracing::warn!(
+                        "Failed to connect to MCP server {} (attempt {attempt}): {e}",
+                        definition.server_name()
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    /// Drops the session of the server so that the next call reconnects.
+    pub async fn invalidate(&self, definition: &McpDefinition) {
+        let connection = self.connection(definition);
+        connection.session.lock().await.take();
+    }
+
+    pub async fn list_tools(
+        &self,
+        definition: &McpDefinition,
+    ) -> Result<Vec<rmcp::model::Tool>, McpServerError> {
+        let connection = self.connection(definition);
+        if let Some(tools) = connection.cached_tools(self.options.tools_ttl) {
+            return Ok(tools);
+        }
+
+        let client = self.client(definition).await?;
+        let tools = match client.list_tools(Default::default()).await {
+            Err(e) if is_connection_error(&e) => {
+                self.invalidate(definition).await;
+                self.client(definition)
+                    .await?
+                    .list_tools(Default::default())
+                    .await?
+            }
+            result => result?,
+        };
+
+        *connection.tools.lock() = Some(CachedTools {
+            tools: tools.tools.clone(),
+            fetched_at: Instant::now(),
+        });
+
+        Ok(tools.tools)
+    }
+
+    /// Sends a request over the pooled session, retrying once on a fresh session
+    /// when the connection was lost.
+    pub async fn send_request(
+        &self,
+        definition: &McpDefinition,
+        request: ClientRequest,
+    ) -> Result<ServerResult, McpServerError> {
+        let client = self.client(definition).await?;
+        match client.send_request(request.clone()).await {
+            Err(e) if is_connection_error(&e) => {
+                self.invalidate(definition).await;
+                Ok(self.client(definition).await?.send_request(request).await?)
+            }
+            result => Ok(result?),
+        }
+    }
+}
+
+async fn ping(client: &McpClient) -> Result<(), ServiceError> {
+    client
+        .send_request(ClientRequest::PingRequest(PingRequest {
+            method: PingRequestMethod,
+            extensions: Extensions::default(),
+        }))
+        .await
+        .map(|_| ())
+}
+
+fn is_connection_error(error: &ServiceError) -> bool {
+    !matches!(error, ServiceError::McpError(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached_tools_expire() {
+        let connection = McpConnection::default();
+        *connection.tools.lock() = Some(CachedTools {
+            tools: vec![],
+            fetched_at: Instant::now(),
+        });
+        assert!(connection.cached_tools(Duration::from_secs(60)).is_some());
+        assert!(connection.cached_tools(Duration::ZERO).is_none());
+
+        connection.invalidate_tools();
+        assert!(connection.cached_tools(Duration::from_secs(60)).is_none());
+    }
+
+    #[test]
+    fn test_idle_connections_are_evicted() {
+        let manager = McpConnectionManager::new(McpPoolOptions {
+            idle_ttl: Duration::ZERO,
+            ..Default::default()
+        });
+        let definition = |command: &str| -> McpDefinition {
+            serde_json::from_value(serde_json::json!({"type": "stdio", "command": command}))
+                .unwrap()
+        };
+
+        let held = manager.connection(&definition("first"));
+        manager.connection(&definition("second"));
+        assert_eq!(manager.connections.lock().len(), 2);
+
+        // The unused connection is dropped, the one still held is kept
+        manager.connection(&definition("third"));
+        let connections = manager.connections.lock();
+        assert_eq!(connections.len(), 2);
+        assert!(connections.values().any(|c| Arc::ptr_eq(c, &held)));
+    }
+
+    #[test]
+    fn test_pool_key_includes_headers() {
+        let definition = |token: &str| -> McpDefinition {
+            serde_json::from_value(serde_json::json!({
+                "type": "http",
+                "server_url": "https://mcp.example.com",
+                "headers": {"Authorization": token}
+            }))
+            .unwrap()
+        };
+
+        assert_eq!(definition("a").r#type.key(), definition("a").r#type.key());
+        assert_ne!(definition("a").r#type.key(), definition("b").r#type.key());
+        assert!(!definition("secret").r#type.key().contains("secret"));
+    }
+}
@@ -1,5 +1,6 @@
 use crate::events::{JsonValue, RecordResult, SPAN_MODEL_CALL};
 use crate::executor::context::ExecutorContext;
+use crate::llm_gateway::message_mapper::MessageMapper;
 use crate::model::bedrock::BedrockModel;
 use crate::model::cached::CachedModel;
 use crate::model::error::ModelError;
@@ -9,32 +10,40 @@ use crate::types::gateway::{
     ChatCompletionContent, ChatCompletionMessage, ChatCompletionMessageWithFinishReason,
     ContentType, Extra, GuardOrName, GuardWithParameters, Usage,
 };
+use crate::types::guardrails::redaction::{restore_message, StreamRestorer};
 use crate::types::guardrails::service::GuardrailsEvaluator;
-use crate::types::guardrails::{GuardError, GuardResult, GuardStage};
+use crate::types::guardrails::{
+    GuardError, GuardOutcome, GuardResult, GuardStage, GuardTimeoutPolicy,
+};
 use crate::types::provider::ModelPrice;
 use crate::types::threads::Message;
 use crate::GatewayResult;
 use anthropic::AnthropicModel;
-use async_openai::config::OpenAIConfig;
-use async_openai::Client;
 use async_trait::async_trait;
 use futures::future::join;
+use futures::stream::FuturesUnordered;
+use futures::StreamExt;
 use gemini::GeminiModel;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fmt::Display;
+use std::sync::Arc;
 use tokio::sync::mpsc::{self, channel};
+use tokio::sync::Mutex;
 use tools::Tool;
 use tracing::{info_span, Instrument};
 use types::{ModelEvent, ModelEventType};
 use valuable::Valuable;
 pub mod handler;
-use self::openai::OpenAIModel;
+use self::openai::{openai_client_with_options, OpenAIModel};
 use crate::handler::find_model_by_full_name;
 use crate::model::proxy::OpenAISpecModel;
-use crate::models::ModelMetadata;
+use crate::models::{ModelAliases, ModelMetadata, VirtualModel};
+use crate::routing::strategy::metric::{MetricOptimizationDirection, MetricSelector};
+use crate::usage::{InMemoryStorage, ProviderMetrics};
 use crate::GatewayApiError;
+use crate::GatewayError;
 
 pub mod anthropic;
 pub mod azure;
@@ -42,10 +51,13 @@ pub mod bedrock;
 pub mod cached;
 pub mod embeddings;
 pub mod error;
+pub mod files;
 pub mod gemini;
 pub mod google_vertex;
 pub mod image_generation;
+pub mod images;
 pub mod mcp;
+pub mod mcp_pool;
 pub mod mcp_server;
 pub mod openai;
 pub mod openai_spec_client;
@@ -53,6 +65,9 @@ pub mod proxy;
 pub mod tools;
 pub mod types;
 
+#[cfg(test)]
+mod tool_choice_tests;
+
 #[async_trait]
 pub trait ModelInstance: Sync + Send {
     async fn invoke(
@@ -119,6 +134,7 @@ pub async fn init_completion_model_instance(
     cached_model: Option<CachedModel>,
     cache_state: Option<ResponseCacheState>,
 ) -> Result<Box<dyn ModelInstance>, ModelError> {
+    let tools = tools::with_tool_guards(tools, executor_context, extra);
     if let Some(cached_model) = cached_model {
         return Ok(Box::new(TracedModel {
             inner: cached_model,
@@ -158,6 +174,7 @@ pub async fn init_completion_model_instance(
             execution_options,
             credentials,
             endpoint,
+            client_options,
         } => {
             // Check if the endpoint is an Azure OpenAI endpoint
             if let Some(ep) = endpoint.as_ref() {
@@ -171,6 +188,7 @@ pub async fn init_completion_model_instance(
                             definition.prompt.clone(),
                             tools,
                             ep,
+                            client_options,
                         )?,
                         definition,
                         executor_context: executor_context.clone(),
@@ -190,8 +208,12 @@ pub async fn init_completion_model_instance(
                     execution_options.clone(),
                     definition.prompt.clone(),
                     tools,
-                    None::<Client<OpenAIConfig>>,
-                    endpoint.as_ref().map(|x| x.as_str()),
+                    Some(openai_client_with_options(
+                        credentials.as_ref(),
+                        endpoint.as_deref(),
+                        client_options,
+                    )?),
+                    endpoint.as_deref(),
                 )?,
                 definition,
                 executor_context: executor_context.clone(),
@@ -205,6 +227,7 @@ pub async fn init_completion_model_instance(
             params,
             execution_options,
             credentials,
+            client_options,
         } => {
             let provider_name = provider_name.expect("provider_name is expected here");
             Ok(Box::new(TracedModel {
@@ -216,6 +239,7 @@ pub async fn init_completion_model_instance(
                     tools,
                     endpoint,
                     provider_name,
+                    client_options,
                 )?,
                 definition,
                 executor_context: executor_context.clone(),
@@ -310,9 +334,11 @@ impl TraceModelDefinition {
         match &mut model.model_params.engine {
             CompletionEngineParams::OpenAi {
                 ref mut credentials,
+                ref mut client_options,
                 ..
             } => {
                 credentials.take();
+                client_options.headers.clear();
             }
             CompletionEngineParams::Bedrock {
                 ref mut credentials,
@@ -335,9 +361,11 @@ impl TraceModelDefinition {
             }
             CompletionEngineParams::Proxy {
                 ref mut credentials,
+                ref mut client_options,
                 ..
             } => {
                 credentials.take();
+                client_options.headers.clear();
             }
         }
         let model = serde_json::to_value(&model)?;
@@ -409,7 +437,7 @@ impl<Inner: ModelInstance> ModelInstance for TracedModel<Inner> {
             span.record("cache", state.to_string());
         }
 
-        apply_guardrails(
+        let guard_outcome = apply_guardrails(
             &self.initial_messages,
             self.extra.as_ref(),
             self.executor_context.evaluator_service.as_ref().as_ref(),
@@ -418,6 +446,7 @@ impl<Inner: ModelInstance> ModelInstance for TracedModel<Inner> {
         )
         .instrument(span.clone())
         .await?;
+        let previous_messages = guarded_messages(&guard_outcome, previous_messages)?;
 
         let cost_calculator = self.executor_context.cost_calculator.clone();
         let price = self.definition.db_model.price.clone();
@@ -484,7 +513,7 @@ impl<Inner: ModelInstance> ModelInstance for TracedModel<Inner> {
         );
 
         async {
-            let result = self
+            let mut result = self
                 .inner
                 .invoke(input_vars, tx, previous_messages, tags)
                 .await;
@@ -499,6 +528,7 @@ impl<Inner: ModelInstance> ModelInstance for TracedModel<Inner> {
                                 ContentType::Text => a.text.clone().unwrap_or_default(),
                                 ContentType::ImageUrl => "".to_string(),
                                 ContentType::InputAudio => "".to_string(),
+                                ContentType::File => "".to_string(),
                             })
                             .collect::<Vec<String>>()
                             .join("\n"),
@@ -507,8 +537,8 @@ impl<Inner: ModelInstance> ModelInstance for TracedModel<Inner> {
                 })
                 .record();
 
-            if let Ok(message) = &result {
-                apply_guardrails(
+            if let Ok(message) = result.as_mut() {
+                let output_outcome = apply_guardrails(
                     std::slice::from_ref(message.message()),
                     self.extra.as_ref(),
                     self.executor_context.evaluator_service.as_ref().as_ref(),
@@ -517,6 +547,11 @@ impl<Inner: ModelInstance> ModelInstance for TracedModel<Inner> {
                 )
                 .instrument(span.clone())
                 .await?;
+
+                if let Some(guarded) = output_outcome.messages.and_then(|mut m| m.pop()) {
+                    *message.message_mut() = guarded;
+                }
+                restore_message(message.message_mut(), &guard_outcome.replacements);
             }
 
             result
@@ -566,7 +601,7 @@ impl<Inner: ModelInstance> ModelInstance for TracedModel<Inner> {
             span.record("cache", state.to_string());
         }
 
-        apply_guardrails(
+        let guard_outcome = apply_guardrails(
             &self.initial_messages,
             self.extra.as_ref(),
             self.executor_context.evaluator_service.as_ref().as_ref(),
@@ -575,16 +610,35 @@ impl<Inner: ModelInstance> ModelInstance for TracedModel<Inner> {
         )
         .instrument(span.clone())
         .await?;
+        let previous_messages = guarded_messages(&guard_outcome, previous_messages)?;
 
         async {
             let (tx, mut rx) = channel(outer_tx.max_capacity());
             let mut output = String::new();
             let mut start_time = None;
+            let mut restorer = StreamRestorer::new(guard_outcome.replacements.clone());
             let result = join(
                 self.inner
                     .stream(input_vars, tx, previous_messages, tags.clone()),
                 async {
-                    while let Some(Some(msg)) = rx.recv().await {
+                    while let Some(Some(mut msg)) = rx.recv().await {
+                        if !restorer.is_empty() {
+                            if let ModelEventType::LlmContent(event) = &mut msg.event {
+                                event.content = restorer.push(&event.content);
+                            } else if let ModelEventType::LlmStop(_) = &msg.event {
+                                let rest = restorer.finish();
+                                if !rest.is_empty() {
+                                    output.push_str(&rest);
+                                    let content = ModelEvent {
+                                        event: ModelEventType::LlmContent(types::LLMContentEvent {
+                                            content: rest,
+                                        }),
+                                        ..msg.clone()
+                                    };
+                                    outer_tx.send(Some(content)).await.unwrap();
+                                }
+                            }
+                        }
                         match &msg.event {
                             ModelEventType::LlmStart(_event) => {
                                 start_time = Some(msg.timestamp.timestamp_micros() as u64);
@@ -679,49 +733,182 @@ impl Display for CredentialsIdent {
     }
 }
 
+/// Evaluates the guards of a stage concurrently on the original messages.
+///
+/// Rewrites are applied in the order the guards are listed, guards rewriting messages
+/// already rewritten by an earlier guard are evaluated again on the rewritten messages.
 pub async fn apply_guardrails(
     messages: &[ChatCompletionMessage],
     extra: Option<&Extra>,
     evaluator: &dyn GuardrailsEvaluator,
     executor_context: &ExecutorContext,
     guard_stage: GuardStage,
-) -> Result<(), GuardError> {
-    let Some(Extra { guards, .. }) = extra else {
-        return Ok(());
+) -> Result<GuardOutcome, GuardError> {
+    let mut outcome = GuardOutcome::default();
+    let Some(Extra {
+        guards,
+        guard_options,
+        ..
+    }) = extra
+    else {
+        return Ok(outcome);
     };
+    if guards.is_empty() {
+        return Ok(outcome);
+    }
 
-    for guard in guards {
-        let (guard_id, parameters) = match guard {
-            GuardOrName::GuardId(guard_id) => (guard_id, None),
-            GuardOrName::GuardWithParameters(GuardWithParameters { id, parameters }) => {
-                (id, Some(parameters))
-            }
-        };
+    let options = guard_options.clone().unwrap_or_default();
+    let deadline = options
+        .timeout_ms
+        .map(|ms| tokio::time::Instant::now() + std::time::Duration::from_millis(ms));
 
-        let result = evaluator
-            .evaluate(
+    let stage = &guard_stage;
+    let mut pending: FuturesUnordered<_> = guards
+        .iter()
+        .enumerate()
+        .map(|(index, guard)| async move {
+            let result = evaluate_guard(
+                guard,
                 messages,
-                guard_id,
+                evaluator,
                 executor_context,
-                parameters,
-                &guard_stage,
+                stage,
+                deadline,
             )
-            .await
-            .map_err(GuardError::GuardEvaluationError)?;
+            .await;
+            (index, result)
+        })
+        .collect();
+
+    let mut results: Vec<Option<GuardResult>> = vec![None; guards.len()];
+    let mut first_error: Option<(usize, GuardError)> = None;
+    while let Some((index, result)) = pending.next().await {
+        let error = match result {
+            Ok(result) if result.passed() => {
+                results[index] = Some(result);
+                continue;
+            }
+            Ok(result) => {
+                GuardError::GuardNotPassed(guards[index].id().to_string(), Box::new(result))
+            }
+            Err(e) => e,
+        };
+
+        if options.fail_fast {
+            return Err(error);
+        }
+        // Report the failure of the first listed guard once all guards finished
+        if first_error.as_ref().is_none_or(|(first, _)| index < *first) {
+            first_error = Some((index, error));
+        }
+    }
+    if let Some((_, error)) = first_error {
+        return Err(error);
+    }
+
+    for (guard, result) in guards.iter().zip(results.into_iter().flatten()) {
+        let result = match (&outcome.messages, result.messages()) {
+            (Some(rewritten), Some(_)) => {
+                let result = evaluate_guard(
+                    guard,
+                    rewritten,
+                    evaluator,
+                    executor_context,
+                    &guard_stage,
+                    deadline,
+                )
+                .await?;
+                if !result.passed() {
+                    return Err(GuardError::GuardNotPassed(
+                        guard.id().to_string(),
+                        Box::new(result),
+                    ));
+                }
+                result
+            }
+            _ => result,
+        };
 
         match result {
-            GuardResult::Json { passed, .. }
-            | GuardResult::Boolean { passed, .. }
-            | GuardResult::Text { passed, .. }
-                if !passed =>
-            {
-                return Err(GuardError::GuardNotPassed(guard_id.clone(), result));
+            GuardResult::Redacted {
+                messages,
+                replacements,
+                ..
+            } => {
+                outcome.messages = Some(messages);
+                outcome.replacements.extend(replacements);
             }
+            GuardResult::Transformed { messages, .. } => outcome.messages = Some(messages),
             _ => {}
         }
     }
 
-    Ok(())
+    Ok(outcome)
+}
+
+async fn evaluate_guard(
+    guard: &GuardOrName,
+    messages: &[ChatCompletionMessage],
+    evaluator: &dyn GuardrailsEvaluator,
+    executor_context: &ExecutorContext,
+    guard_stage: &GuardStage,
+    deadline: Option<tokio::time::Instant>,
+) -> Result<GuardResult, GuardError> {
+    let (guard_id, parameters) = match guard {
+        GuardOrName::GuardId(guard_id) => (guard_id, None),
+        GuardOrName::GuardWithParameters(GuardWithParameters { id, parameters }) => {
+            (id, Some(parameters))
+        }
+    };
+
+    let evaluation = evaluator.evaluate(
+        messages,
+        guard_id,
+        executor_context,
+        parameters,
+        guard_stage,
+    );
+    let result = match deadline {
+        Some(deadline) => match tokio::time::timeout_at(deadline, evaluation).await {
+            Ok(result) => result,
+            Err(_) => {
+                return match evaluator.timeout_policy(guard_id) {
+                    GuardTimeoutPolicy::FailOpen => {
+                        tracing::warn!("Guard {guard_id} timed out, letting the request through");
+                        Ok(GuardResult::Boolean {
+                            passed: true,
+                            confidence: None,
+                        })
+                    }
+                    GuardTimeoutPolicy::FailClosed => {
+                        Err(GuardError::GuardTimeout(guard_id.clone()))
+                    }
+                };
+            }
+        },
+        None => evaluation.await,
+    };
+
+    result.map_err(GuardError::GuardEvaluationError)
+}
+
+/// Maps messages rewritten by input guards to the messages sent to the model.
+fn guarded_messages(
+    outcome: &GuardOutcome,
+    previous_messages: Vec<Message>,
+) -> GatewayResult<Vec<Message>> {
+    let Some(messages) = &outcome.messages else {
+        return Ok(previous_messages);
+    };
+
+    let (model_name, user_id) = previous_messages
+        .first()
+        .map(|m| (m.model_name.clone(), m.user_id.clone()))
+        .unwrap_or_default();
+    messages
+        .iter()
+        .map(|m| MessageMapper::map_completions_message_to_langdb_message(m, &model_name, &user_id))
+        .collect()
 }
 
 #[async_trait]
@@ -750,16 +937,43 @@ pub trait ModelMetadataFactory: Send + Sync {
         ranking_name: &str,
         top: u8,
     ) -> Result<Vec<ModelMetadata>, GatewayApiError>;
+
+    /// Returns the virtual model definition when `model_name` refers to one, directly or via an alias.
+    fn get_virtual_model(&self, _model_name: &str) -> Option<VirtualModel> {
+        None
+    }
 }
 
 pub struct DefaultModelMetadataFactory {
     models: Vec<ModelMetadata>,
+    aliases: ModelAliases,
+    metrics_storage: Option<Arc<Mutex<InMemoryStorage>>>,
 }
 
 impl DefaultModelMetadataFactory {
     pub fn new(models: &[ModelMetadata]) -> Self {
         Self {
             models: models.to_vec(),
+            aliases: ModelAliases::default(),
+            metrics_storage: None,
+        }
+    }
+
+    pub fn with_aliases(mut self, aliases: ModelAliases) -> Self {
+        self.aliases = aliases;
+        self
+    }
+
+    /// Storage with observed provider metrics, used to rank models by latency, ttft, tps etc.
+    pub fn with_metrics_storage(mut self, storage: Option<Arc<Mutex<InMemoryStorage>>>) -> Self {
+        self.metrics_storage = storage;
+        self
+    }
+
+    async fn get_metrics(&self) -> BTreeMap<String, ProviderMetrics> {
+        match &self.metrics_storage {
+            Some(storage) => storage.lock().await.get_all_counters().await,
+            None => BTreeMap::new(),
         }
     }
 }
@@ -775,7 +989,15 @@ impl ModelMetadataFactory for DefaultModelMetadataFactory {
         _include_benchmark: bool,
         _project_id: Option<&uuid::Uuid>,
     ) -> Result<ModelMetadata, GatewayApiError> {
-        find_model_by_full_name(model_name, &self.models)
+        let Some(target) = self.aliases.resolve_target(model_name) else {
+            return find_model_by_full_name(model_name, &self.models);
+        };
+
+        let mut model = find_model_by_full_name(&target, &self.models)?;
+        if let Some((virtual_model_id, _)) = self.aliases.get_virtual_model(model_name) {
+            model.virtual_model_id = Some(virtual_model_id.to_string());
+        }
+        Ok(model)
     }
 
     async fn get_cheapest_model_metadata(
@@ -794,18 +1016,147 @@ impl ModelMetadataFactory for DefaultModelMetadataFactory {
 
     async fn get_models_by_name(
         &self,
-        _model_name: &str,
+        model_name: &str,
         _project_id: Option<&uuid::Uuid>,
     ) -> Result<Vec<ModelMetadata>, GatewayApiError> {
-        unimplemented!()
+        let resolved_name = self.aliases.resolve_target(model_name);
+        let name = resolved_name.as_deref().unwrap_or(model_name);
+
+        let (provider, name) = match name.split_once('/') {
+            Some(("*", name)) => (None, name),
+            Some((provider, name)) => (Some(provider), name),
+            None => (None, name),
+        };
+        let name = name.split('@').next().unwrap_or(name).to_lowercase();
+
+        let mut models = self
+            .models
+            .iter()
+            .filter(|m| {
+                m.model.to_lowercase() == name
+                    || m.inference_provider.model_name.to_lowercase() == name
+            })
+            .filter(|m| provider.is_none_or(|p| m.inference_provider.provider.to_string() == p))
+            .cloned()
+            .collect::<Vec<_>>();
+
+        if models.is_empty() {
+            return Err(GatewayApiError::GatewayError(GatewayError::ModelError(
+                Box::new(ModelError::ModelNotFound(model_name.to_string())),
+            )));
+        }
+
+        // Cheapest providers first, so fallbacks over the list prefer them
+        models.sort_by(|a, b| {
+            completion_price(a)
+                .partial_cmp(&completion_price(b))
+                .unwrap_or(std::cmp::Ordering::Equal)
+        });
+
+        Ok(models)
     }
 
     async fn get_top_by_ranking(
         &self,
-        _ranking_name: &str,
-        _top: u8,
+        ranking_name: &str,
+        top: u8,
     ) -> Result<Vec<ModelMetadata>, GatewayApiError> {
-        unimplemented!()
+        let candidates = self
+            .models
+            .iter()
+            .filter(|m| m.r#type == crate::models::ModelType::Completions);
+
+        let ranking = ModelRanking::from_name(ranking_name);
+        let metrics = match &ranking {
+            ModelRanking::Metric(_) => self.get_metrics().await,
+            _ => BTreeMap::new(),
+        };
+
+        let mut scored = candidates
+            .filter_map(|m| ranking.score(m, &metrics).map(|score| (score, m)))
+            .collect::<Vec<_>>();
+
+        if scored.is_empty() {
+            return Err(GatewayApiError::CustomError(format!(
+                "No models found for ranking {ranking_name}"
+            )));
+        }
+
+        scored.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(std::cmp::Ordering::Equal));
+
+        Ok(scored
+            .into_iter()
+            .take(top as usize)
+            .map(|(_, m)| m.clone())
+            .collect())
+    }
+
+    fn get_virtual_model(&self, model_name: &str) -> Option<VirtualModel> {
+        self.aliases
+            .get_virtual_model(model_name)
+            .map(|(_, model)| model.clone())
+    }
+}
+
+/// Criteria supported by [`ModelMetadataFactory::get_top_by_ranking`].
+enum ModelRanking {
+    Price,
+    ContextSize,
+    Metric(MetricSelector),
+    /// Score stored under this key in `ModelMetadata::benchmark_info`
+    Benchmark(String),
+}
+
+impl ModelRanking {
+    fn from_name(name: &str) -> Self {
+        match name {
+            "price" | "cheapest" => ModelRanking::Price,
+            "context" | "context_size" => ModelRanking::ContextSize,
+            name => match serde_json::from_value(Value::String(name.to_string())) {
+                Ok(metric) => ModelRanking::Metric(metric),
+                Err(_) => ModelRanking::Benchmark(name.to_string()),
+            },
+        }
+    }
+
+    /// Higher scores rank first.
+    fn score(
+        &self,
+        model: &ModelMetadata,
+        metrics: &BTreeMap<String, ProviderMetrics>,
+    ) -> Option<f64> {
+        match self {
+            ModelRanking::Price => completion_price(model).map(|price| -price),
+            ModelRanking::ContextSize => Some(model.limits.max_context_size as f64),
+            ModelRanking::Metric(metric) => {
+                let value = metrics
+                    .get(&model.inference_provider.provider.to_string())
+                    .and_then(|p| {
+                        p.models
+                            .get(&model.model)
+                            .or_else(|| p.models.get(&model.inference_provider.model_name))
+                    })
+                    .and_then(|m| metric.get_value(&m.metrics.total))?;
+
+                match metric.get_optimization_direction() {
+                    MetricOptimizationDirection::Maximize => Some(value),
+                    MetricOptimizationDirection::Minimize => Some(-value),
+                }
+            }
+            ModelRanking::Benchmark(key) => {
+                let value = model.benchmark_info.as_ref()?.get(key)?;
+                value
+                    .as_f64()
+                    .or_else(|| value.get("score").and_then(|v| v.as_f64()))
+            }
+        }
+    }
+}
+
+fn completion_price(model: &ModelMetadata) -> Option<f64> {
+    match &model.price {
+        ModelPrice::Completion(price) => Some(price.per_input_token + price.per_output_token),
+        _ => None,
     }
 }
 
@@ -831,3 +1182,76 @@ pub fn get_cheapest_model_metadata(
 
     Ok(cheapest_model.clone())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{InferenceProvider, Limits};
+    use crate::types::provider::{CompletionModelPrice, InferenceModelProvider};
+
+    fn model(provider: &str, name: &str, price: f64, context: u32) -> ModelMetadata {
+        ModelMetadata {
+            model: name.to_string(),
+            model_provider: "openai".to_string(),
+            inference_provider: InferenceProvider {
+                provider: InferenceModelProvider::from(provider.to_string()),
+                model_name: name.to_string(),
+                endpoint: None,
+            },
+            price: ModelPrice::Completion(CompletionModelPrice {
+                per_input_token: price,
+                per_output_token: price,
+                per_cached_input_token: None,
+                per_cached_input_write_token: None,
+                valid_from: None,
+            }),
+            limits: Limits::new(context),
+            benchmark_info: Some(json!({ "mmlu": context as f64 / 1000.0 })),
+            ..Default::default()
+        }
+    }
+
+    fn factory() -> DefaultModelMetadataFactory {
+        DefaultModelMetadataFactory::new(&[
+            model("openai", "gpt-4o", 5.0, 128000),
+            model("azure", "gpt-4o", 4.0, 128000),
+            model("openai", "gpt-4o-mini", 0.5, 64000),
+        ])
+    }
+
+    #[tokio::test]
+    async fn test_get_models_by_name() {
+        let factory = factory();
+
+        let models = factory.get_models_by_name("*/gpt-4o", None).await.unwrap();
+        let names = models
+            .iter()
+            .map(|m| m.qualified_model_name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["azure/gpt-4o", "openai/gpt-4o"]);
+
+        let models = factory
+            .get_models_by_name("openai/gpt-4o", None)
+            .await
+            .unwrap();
+        assert_eq!(models.len(), 1);
+
+        assert!(factory.get_models_by_name("*/gpt-5", None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_top_by_ranking() {
+        let factory = factory();
+
+        let models = factory.get_top_by_ranking("price", 1).await.unwrap();
+        assert_eq!(models[0].qualified_model_name(), "openai/gpt-4o-mini");
+
+        let models = factory.get_top_by_ranking("context_size", 2).await.unwrap();
+        assert!(models.iter().all(|m| m.model == "gpt-4o"));
+
+        let models = factory.get_top_by_ranking("mmlu", 3).await.unwrap();
+        assert_eq!(models.last().unwrap().model, "gpt-4o-mini");
+
+        assert!(factory.get_top_by_ranking("unknown", 3).await.is_err());
+    }
+}
@@ -2,7 +2,7 @@ use super::error::{AuthorizationError, ModelError};
 use super::tools::Tool;
 use super::types::{
     LLMContentEvent, LLMFinishEvent, LLMStartEvent, ModelEvent, ModelEventType, ModelFinishReason,
-    ModelToolCall,
+    ModelToolCall, ToolOutput,
 };
 use super::{CredentialsIdent, ModelInstance};
 use crate::error::GatewayError;
@@ -10,12 +10,16 @@ use crate::events::JsonValue;
 use crate::events::SPAN_OPENAI;
 use crate::events::{self, RecordResult};
 use crate::model::error::ModelFinishError;
-use crate::model::handler::handle_tool_call;
+use crate::model::files::{extract_documents, FilePart};
+use crate::model::handler::{handle_tool_call, ToolLoopGuard};
 use crate::model::types::LLMFirstToken;
 use crate::model::{async_trait, DEFAULT_MAX_RETRIES};
-use crate::types::credentials::ApiKeyCredentials;
+use crate::types::credentials::{ApiKeyCredentials, ClientOptions};
 use crate::types::engine::{ExecutionOptions, OpenAiModelParams, Prompt};
-use crate::types::gateway::{ChatCompletionContent, ChatCompletionMessage, ToolCall};
+use crate::types::gateway::{
+    ChatCompletionContent, ChatCompletionMessage, ReasoningEffort, ToolCall, ToolChoice,
+    ToolChoiceMode,
+};
 use crate::types::gateway::{ChatCompletionMessageWithFinishReason, CompletionModelUsage};
 use crate::types::message::{MessageType, PromptMessage};
 use crate::types::threads::{InnerMessage, Message};
@@ -25,19 +29,23 @@ use async_openai::config::{AzureConfig, OpenAIConfig};
 use async_openai::error::OpenAIError;
 use async_openai::types::{
     ChatChoice, ChatCompletionMessageToolCall, ChatCompletionMessageToolCallChunk,
-    ChatCompletionRequestAssistantMessageArgs, ChatCompletionRequestMessage,
-    ChatCompletionRequestSystemMessageArgs, ChatCompletionRequestToolMessage,
-    ChatCompletionRequestToolMessageContent, ChatCompletionRequestUserMessageArgs,
-    ChatCompletionRequestUserMessageContentPart, ChatCompletionResponseMessage, ChatCompletionTool,
-    ChatCompletionToolArgs, ChatCompletionToolChoiceOption, ChatCompletionToolType,
-    CreateChatCompletionRequest, CreateChatCompletionRequestArgs, CreateChatCompletionResponse,
-    FinishReason, FunctionCall, FunctionCallStream, FunctionObject,
+    ChatCompletionNamedToolChoice, ChatCompletionRequestAssistantMessageArgs,
+    ChatCompletionRequestMessage, ChatCompletionRequestSystemMessageArgs,
+    ChatCompletionRequestToolMessage, ChatCompletionRequestToolMessageContent,
+    ChatCompletionRequestUserMessageArgs, ChatCompletionRequestUserMessageContentPart,
+    ChatCompletionResponseMessage, ChatCompletionTool, ChatCompletionToolArgs,
+    ChatCompletionToolChoiceOption, ChatCompletionToolType, CreateChatCompletionRequest,
+    CreateChatCompletionRequestArgs, CreateChatCompletionResponse, FinishReason, FunctionCall,
+    FunctionCallStream, FunctionName, FunctionObject,
 };
 use async_openai::types::{
     ChatCompletionRequestMessageContentPartImage, CreateChatCompletionStreamResponse, ImageUrl,
 };
 use async_openai::types::{ChatCompletionRequestToolMessageArgs, CompletionUsage};
-use async_openai::types::{ChatCompletionRequestUserMessageContent, ChatCompletionStreamOptions};
+use async_openai::types::{
+    ChatCompletionRequestUserMessage, ChatCompletionRequestUserMessageContent,
+    ChatCompletionStreamOptions,
+};
 use async_openai::Client;
 use futures::Stream;
 use futures::StreamExt;
@@ -66,7 +74,7 @@ macro_rules! target {
 }
 
 enum InnerExecutionResult {
-    Finish(ChatCompletionMessageWithFinishReason),
+    Finish(Box<ChatCompletionMessageWithFinishReason>),
     NextCall(Vec<ChatCompletionRequestMessage>),
 }
 
@@ -75,17 +83,61 @@ pub fn is_azure_endpoint(endpoint: &str) -> bool {
     endpoint.contains("azure.com")
 }
 
+/// Azure OpenAI API version used when the providers config does not set one
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
+fn openai_api_key(credentials: Option<&ApiKeyCredentials>) -> Result<String, ModelError> {
+    match credentials {
+        Some(credentials) => Ok(credentials.api_key.clone()),
+        None => std::env::var("LANGDB_OPENAI_API_KEY")
+            .map_err(|_| AuthorizationError::InvalidApiKey.into()),
+    }
+}
+
+/// Attaches extra headers from the providers config to every request made by the client
+pub fn with_client_headers<C: Config>(
+    client: Client<C>,
+    headers: &HashMap<String, String>,
+) -> Result<Client<C>, ModelError> {
+    if headers.is_empty() {
+        return Ok(client);
+    }
+
+    let mut header_map = reqwest::header::HeaderMap::new();
+    for (key, value) in headers {
+        let name = key
+            .parse::<reqwest::header::HeaderName>()
+            .map_err(|e| ModelError::CustomError(format!("Invalid header name {key}: {e}")))?;
+        let value = value
+            .parse::<reqwest::header::HeaderValue>()
+            .map_err(|e| ModelError::CustomError(format!("Invalid value for header {key}: {e}")))?;
+        header_map.insert(name, value);
+    }
+
+    let http_client = reqwest::Client::builder()
+        .default_headers(header_map)
+        .build()
+        .map_err(|e| ModelError::CustomError(e.to_string()))?;
+
+    Ok(client.with_http_client(http_client))
+}
+
 /// Create an OpenAI client with standard OpenAI configuration
 /// Note: This does not handle Azure OpenAI endpoints. Use azure_openai_client for Azure endpoints.
 pub fn openai_client(
     credentials: Option<&ApiKeyCredentials>,
     endpoint: Option<&str>,
 ) -> Result<Client<OpenAIConfig>, ModelError> {
-    let api_key = if let Some(credentials) = credentials {
-        credentials.api_key.clone()
-    } else {
-        std::env::var("LANGDB_OPENAI_API_KEY").map_err(|_| AuthorizationError::InvalidApiKey)?
-    };
+    openai_client_with_options(credentials, endpoint, &ClientOptions::default())
+}
+
+/// Same as [`openai_client`], applying organization and extra headers from the providers config
+pub fn openai_client_with_options(
+    credentials: Option<&ApiKeyCredentials>,
+    endpoint: Option<&str>,
+    options: &ClientOptions,
+) -> Result<Client<OpenAIConfig>, ModelError> {
+    let api_key = openai_api_key(credentials)?;
 
     let mut config = OpenAIConfig::new();
     config = config.with_api_key(api_key);
@@ -102,7 +154,11 @@ pub fn openai_client(
         config = config.with_api_base(endpoint);
     }
 
-    Ok(Client::with_config(config))
+    if let Some(organization) = &options.organization {
+        config = config.with_org_id(organization);
+    }
+
+    with_client_headers(Client::with_config(config), &options.headers)
 }
 
 /// Create an Azure OpenAI client from endpoint URL
@@ -110,14 +166,40 @@ pub fn azure_openai_client(
     api_key: String,
     endpoint: &str,
     deployment_id: &str,
-) -> Client<AzureConfig> {
+    options: &ClientOptions,
+) -> Result<Client<AzureConfig>, ModelError> {
     let azure_config = AzureConfig::new()
         .with_api_base(endpoint)
-        .with_api_version("2024-10-21".to_string())
+        .with_api_version(
+            options
+                .api_version
+                .clone()
+                .unwrap_or(DEFAULT_AZURE_API_VERSION.to_string()),
+        )
         .with_api_key(api_key)
         .with_deployment_id(deployment_id.to_string());
 
-    Client::with_config(azure_config)
+    with_client_headers(Client::with_config(azure_config), &options.headers)
+}
+
+pub(crate) fn openai_tool_choice(
+    tool_choice: Option<&ToolChoice>,
+) -> ChatCompletionToolChoiceOption {
+    match tool_choice {
+        None | Some(ToolChoice::Mode(ToolChoiceMode::Auto)) => ChatCompletionToolChoiceOption::Auto,
+        Some(ToolChoice::Mode(ToolChoiceMode::None)) => ChatCompletionToolChoiceOption::None,
+        Some(ToolChoice::Mode(ToolChoiceMode::Required)) => {
+            ChatCompletionToolChoiceOption::Required
+        }
+        Some(ToolChoice::Named(named)) => {
+            ChatCompletionToolChoiceOption::Named(ChatCompletionNamedToolChoice {
+                r#type: ChatCompletionToolType::Function,
+                function: FunctionName {
+                    name: named.function.name.clone(),
+                },
+            })
+        }
+    }
 }
 
 #[derive(Clone)]
@@ -179,13 +261,12 @@ impl OpenAIModel<AzureConfig> {
         let client = if let Some(client) = client {
             client
         } else if let Some(endpoint) = endpoint {
-            let api_key = if let Some(credentials) = credentials {
-                credentials.api_key.clone()
-            } else {
-                std::env::var("LANGDB_OPENAI_API_KEY")
-                    .map_err(|_| AuthorizationError::InvalidApiKey)?
-            };
-            azure_openai_client(api_key, endpoint, &params.model.clone().unwrap_or_default())
+            azure_openai_client(
+                openai_api_key(credentials)?,
+                endpoint,
+                &params.model.clone().unwrap_or_default(),
+                &ClientOptions::default(),
+            )?
         } else {
             return Err(ModelError::CustomError(
                 "Azure OpenAI requires an endpoint URL".to_string(),
@@ -212,14 +293,21 @@ impl OpenAIModel<AzureConfig> {
         prompt: Prompt,
         tools: HashMap<String, Box<dyn Tool>>,
         endpoint: &str,
+        options: &ClientOptions,
     ) -> Result<Self, ModelError> {
+        let client = azure_openai_client(
+            openai_api_key(credentials)?,
+            endpoint,
+            &params.model.clone().unwrap_or_default(),
+            options,
+        )?;
         Self::new_azure(
             params,
             credentials,
             execution_options,
             prompt,
             tools,
-            None,
+            Some(client),
             Some(endpoint),
         )
     }
@@ -240,45 +328,88 @@ impl<C: Config> OpenAIModel<C> {
         tools: &HashMap<String, Box<dyn Tool>>,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
-    ) -> HashMap<String, String> {
-        let result = futures::future::join_all(function_calls.map(|tool_call| {
-            let tags_value = tags.clone();
-            async move {
-                let id = tool_call.id.clone();
-                let function = tool_call.function.clone();
-                tracing::trace!("Calling tool ({id}) {function:?}");
-
-                let tool_call = Self::map_tool_call(tool_call);
-                let result = handle_tool_call(&tool_call, tools, tx, tags_value).await;
-                tracing::trace!("Result ({id}): {result:?}");
-                let content = result.unwrap_or_else(|err| err.to_string());
-                (id, content)
-            }
-        }))
-        .await;
+        guard: &ToolLoopGuard,
+    ) -> HashMap<String, ToolOutput> {
+        let result = guard
+            .run_tools(function_calls.map(|tool_call| {
+                let tags_value = tags.clone();
+                async move {
+                    let id = tool_call.id.clone();
+                    let function = tool_call.function.clone();
+                    tracing::trace!("Calling tool ({id}) {function:?}");
+
+                    let tool_call = Self::map_tool_call(tool_call);
+                    let result = handle_tool_call(&tool_call, tools, tx, tags_value, guard).await;
+                    tracing::trace!("Result ({id}): {result:?}");
+                    let content = result.unwrap_or_else(|err| ToolOutput {
+                        text: err.to_string(),
+                        images: vec![],
+                    });
+                    (id, content)
+                }
+            }))
+            .await;
 
         HashMap::from_iter(result)
     }
 
     fn map_tool_call_results(
-        results: HashMap<String, String>,
+        results: HashMap<String, ToolOutput>,
     ) -> Vec<ChatCompletionRequestMessage> {
-        results
+        let mut images = vec![];
+        let mut messages: Vec<ChatCompletionRequestMessage> = results
             .into_iter()
             .map(|(id, content)| {
+                images.extend(content.images);
                 ChatCompletionRequestMessage::Tool(ChatCompletionRequestToolMessage {
-                    content: ChatCompletionRequestToolMessageContent::Text(content),
+                    content: ChatCompletionRequestToolMessageContent::Text(content.text),
                     tool_call_id: id,
                 })
             })
-            .collect()
+            .collect();
+
+        // Tool messages only take text, images are passed in a following user message
+        if !images.is_empty() {
+            messages.push(ChatCompletionRequestMessage::User(
+                ChatCompletionRequestUserMessage {
+                    content: ChatCompletionRequestUserMessageContent::Array(
+                        images
+                            .into_iter()
+                            .map(|image| {
+                                ChatCompletionRequestUserMessageContentPart::ImageUrl(
+                                    ChatCompletionRequestMessageContentPartImage {
+                                        image_url: ImageUrl {
+                                            url: image.to_data_url(),
+                                            detail: None,
+                                        },
+                                    },
+                                )
+                            })
+                            .collect(),
+                    ),
+                    name: None,
+                },
+            ));
+        }
+
+        messages
+    }
+
+    fn tool_results_value(results: &HashMap<String, ToolOutput>) -> Value {
+        Value::Object(
+            results
+                .iter()
+                .map(|(id, output)| (id.clone(), Value::String(output.text.clone())))
+                .collect(),
+        )
     }
 
-    #[tracing::instrument(level = "debug", skip(self))]
-    fn build_request(
+    #[tracing::instrument(level = "debug", skip(self, guard))]
+    pub(crate) fn build_request(
         &self,
         messages: &[ChatCompletionRequestMessage],
         stream: bool,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<CreateChatCompletionRequest> {
         let mut chat_completion_tools: Vec<ChatCompletionTool> = vec![];
 
@@ -335,6 +466,15 @@ impl<C: Config> OpenAIModel<C> {
             builder.prompt_cache_key(prompt_cache_key.clone());
         }
 
+        if let Some(reasoning_effort) = model_params.reasoning_effort {
+            builder.reasoning_effort(match reasoning_effort {
+                ReasoningEffort::Minimal => async_openai::types::ReasoningEffort::Minimal,
+                ReasoningEffort::Low => async_openai::types::ReasoningEffort::Low,
+                ReasoningEffort::Medium => async_openai::types::ReasoningEffort::Medium,
+                ReasoningEffort::High => async_openai::types::ReasoningEffort::High,
+            });
+        }
+
         if stream {
             builder.stream_options(ChatCompletionStreamOptions {
                 include_usage: true,
@@ -348,7 +488,14 @@ impl<C: Config> OpenAIModel<C> {
         if !self.tools.is_empty() {
             builder
                 .tools(chat_completion_tools)
-                .tool_choice(ChatCompletionToolChoiceOption::Auto);
+                .tool_choice(openai_tool_choice(
+                    guard
+                        .tool_choice(model_params.tool_choice.as_ref())
+                        .as_ref(),
+                ));
+            if let Some(parallel_tool_calls) = model_params.parallel_tool_calls {
+                builder.parallel_tool_calls(parallel_tool_calls);
+            }
         }
 
         Ok(builder.build().map_err(ModelError::OpenAIApi)?)
@@ -543,8 +690,9 @@ impl<C: Config> OpenAIModel<C> {
         messages: Vec<ChatCompletionRequestMessage>,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<InnerExecutionResult> {
-        let call = self.build_request(&messages, false)?;
+        let call = self.build_request(&messages, false, guard)?;
         span.record("request", serde_json::to_string(&call)?);
 
         let input_messages = call.messages.clone();
@@ -643,7 +791,7 @@ impl<C: Config> OpenAIModel<C> {
                     .await
                     .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                    Ok(InnerExecutionResult::Finish(
+                    Ok(InnerExecutionResult::Finish(Box::new(
                         ChatCompletionMessageWithFinishReason::new(
                             ChatCompletionMessage {
                                 role: "assistant".to_string(),
@@ -671,7 +819,7 @@ impl<C: Config> OpenAIModel<C> {
                             },
                             finish_reason,
                         ),
-                    ))
+                    )))
                 } else {
                     let mut messages: Vec<ChatCompletionRequestMessage> =
                         vec![ChatCompletionRequestMessage::Assistant(
@@ -680,13 +828,18 @@ impl<C: Config> OpenAIModel<C> {
                                 .build()
                                 .map_err(ModelError::OpenAIApi)?,
                         )];
-                    let result_tool_calls =
-                        Self::handle_tool_calls(tool_calls.iter(), &self.tools, tx, tags.clone())
-                            .instrument(tools_span.clone())
-                            .await;
+                    let result_tool_calls = Self::handle_tool_calls(
+                        tool_calls.iter(),
+                        &self.tools,
+                        tx,
+                        tags.clone(),
+                        guard,
+                    )
+                    .instrument(tools_span.clone())
+                    .await;
                     tools_span.record(
                         "tool_results",
-                        JsonValue(&serde_json::to_value(&result_tool_calls)?).as_value(),
+                        JsonValue(&Self::tool_results_value(&result_tool_calls)).as_value(),
                     );
                     messages.extend(Self::map_tool_call_results(result_tool_calls));
 
@@ -717,7 +870,7 @@ impl<C: Config> OpenAIModel<C> {
                     .await
                     .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-                    Ok(InnerExecutionResult::Finish(
+                    Ok(InnerExecutionResult::Finish(Box::new(
                         ChatCompletionMessageWithFinishReason::new(
                             ChatCompletionMessage {
                                 role: "assistant".to_string(),
@@ -726,7 +879,7 @@ impl<C: Config> OpenAIModel<C> {
                             },
                             finish_reason,
                         ),
-                    ))
+                    )))
                 } else {
                     Err(ModelError::FinishError(ModelFinishError::NoOutputProvided).into())
                 }
@@ -751,6 +904,7 @@ impl<C: Config> OpenAIModel<C> {
             .execution_options
             .max_retries
             .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut guard = ToolLoopGuard::new(self.execution_options.tool_loop.as_ref());
         while let Some(messages) = openai_calls.pop() {
             let input = serde_json::to_string(&messages)?;
             let span = create_model_span!(
@@ -762,11 +916,12 @@ impl<C: Config> OpenAIModel<C> {
             );
 
             match self
-                .execute_inner(span.clone(), messages.clone(), tx, tags.clone())
+                .execute_inner(span.clone(), messages.clone(), tx, tags.clone(), &guard)
                 .await
             {
-                Ok(InnerExecutionResult::Finish(message)) => return Ok(message),
+                Ok(InnerExecutionResult::Finish(message)) => return Ok(*message),
                 Ok(InnerExecutionResult::NextCall(messages)) => {
+                    guard.next_iteration()?;
                     openai_calls.push(messages);
                 }
                 Err(e) => {
@@ -840,6 +995,7 @@ impl<C: Config> OpenAIModel<C> {
         input_messages: Vec<ChatCompletionRequestMessage>,
         tx: &tokio::sync::mpsc::Sender<Option<ModelEvent>>,
         tags: HashMap<String, String>,
+        guard: &ToolLoopGuard,
     ) -> GatewayResult<InnerExecutionResult> {
         tx.send(Some(ModelEvent::new(
             &span,
@@ -852,7 +1008,7 @@ impl<C: Config> OpenAIModel<C> {
         .await
         .map_err(|e| GatewayError::CustomError(e.to_string()))?;
 
-        let request = self.build_request(&input_messages, true)?;
+        let request = self.build_request(&input_messages, true, guard)?;
         span.record("request", serde_json::to_string(&request)?);
 
         let started_at = std::time::Instant::now();
@@ -897,12 +1053,12 @@ impl<C: Config> OpenAIModel<C> {
 
         match finish_reason {
             FinishReason::Stop | FinishReason::Length => Ok(InnerExecutionResult::Finish(
-                ChatCompletionMessageWithFinishReason::new(
+                Box::new(ChatCompletionMessageWithFinishReason::new(
                     ChatCompletionMessage {
                         ..Default::default()
                     },
                     Self::map_finish_reason(&finish_reason),
-                ),
+                )),
             )),
             FinishReason::ToolCalls => {
                 let tool = self
@@ -922,14 +1078,14 @@ impl<C: Config> OpenAIModel<C> {
                 tools_span.follows_from(span.id());
 
                 if tool.stop_at_call() {
-                    Ok(InnerExecutionResult::Finish(
+                    Ok(InnerExecutionResult::Finish(Box::new(
                         ChatCompletionMessageWithFinishReason::new(
                             ChatCompletionMessage {
                                 ..Default::default()
                             },
                             Self::map_finish_reason(&finish_reason),
                         ),
-                    ))
+                    )))
                 } else {
                     let mut messages: Vec<ChatCompletionRequestMessage> =
                         vec![ChatCompletionRequestMessage::Assistant(
@@ -938,13 +1094,18 @@ impl<C: Config> OpenAIModel<C> {
                                 .build()
                                 .map_err(ModelError::OpenAIApi)?,
                         )];
-                    let result_tool_calls =
-                        Self::handle_tool_calls(tool_calls.iter(), &self.tools, tx, tags.clone())
-                            .instrument(tools_span.clone())
-                            .await;
+                    let result_tool_calls = Self::handle_tool_calls(
+                        tool_calls.iter(),
+                        &self.tools,
+                        tx,
+                        tags.clone(),
+                        guard,
+                    )
+                    .instrument(tools_span.clone())
+                    .await;
                     tools_span.record(
                         "tool_results",
-                        JsonValue(&serde_json::to_value(&result_tool_calls)?).as_value(),
+                        JsonValue(&Self::tool_results_value(&result_tool_calls)).as_value(),
                     );
                     messages.extend(Self::map_tool_call_results(result_tool_calls));
 
@@ -970,6 +1131,7 @@ impl<C: Config> OpenAIModel<C> {
             .execution_options
             .max_retries
             .unwrap_or(DEFAULT_MAX_RETRIES);
+        let mut guard = ToolLoopGuard::new(self.execution_options.tool_loop.as_ref());
         while let Some(input_messages) = openai_calls.pop() {
             let input = serde_json::to_string(&input_messages)?;
             let span = create_model_span!(
@@ -981,13 +1143,20 @@ impl<C: Config> OpenAIModel<C> {
             );
 
             match self
-                .execute_stream_inner(span.clone(), input_messages.clone(), tx, tags.clone())
+                .execute_stream_inner(
+                    span.clone(),
+                    input_messages.clone(),
+                    tx,
+                    tags.clone(),
+                    &guard,
+                )
                 .await
             {
                 Ok(InnerExecutionResult::Finish(_)) => {
                     break;
                 }
                 Ok(InnerExecutionResult::NextCall(messages)) => {
+                    guard.next_iteration()?;
                     openai_calls.push(messages);
                 }
                 Err(e) => {
@@ -1047,7 +1216,7 @@ impl<C: Config> OpenAIModel<C> {
                         )
                     }
                     MessageType::HumanMessage => {
-                        construct_user_message(&m.clone().into(), input_variables.clone())
+                        construct_user_message(&m.clone().into(), input_variables.clone())?
                     }
                     MessageType::ToolResult => ChatCompletionRequestMessage::Tool(
                         ChatCompletionRequestToolMessageArgs::default()
@@ -1078,8 +1247,9 @@ impl<C: Config + std::marker::Sync + std::marker::Send> ModelInstance for OpenAI
         previous_messages: Vec<Message>,
         tags: HashMap<String, String>,
     ) -> GatewayResult<ChatCompletionMessageWithFinishReason> {
+        let previous_messages = extract_documents(previous_messages).await?;
         let conversational_messages =
-            self.construct_messages(input_variables, previous_messages.clone())?;
+            self.construct_messages(input_variables, previous_messages)?;
         self.execute(conversational_messages, &tx, tags).await
     }
 
@@ -1090,8 +1260,9 @@ impl<C: Config + std::marker::Sync + std::marker::Send> ModelInstance for OpenAI
         previous_messages: Vec<Message>,
         tags: HashMap<String, String>,
     ) -> GatewayResult<()> {
+        let previous_messages = extract_documents(previous_messages).await?;
         let conversational_messages =
-            self.construct_messages(input_variables, previous_messages.clone())?;
+            self.construct_messages(input_variables, previous_messages)?;
 
         self.execute_stream(conversational_messages, &tx, tags)
             .await
@@ -1155,7 +1326,7 @@ fn map_chat_messages(
             } else {
                 InnerMessage::Text(Prompt::render(msg, variables))
             };
-            construct_user_message(&inner_message, variables.clone())
+            construct_user_message(&inner_message, variables.clone())?
         }
         MessageType::SystemMessage => {
             let raw_message = Prompt::render(prompt.msg, variables);
@@ -1176,7 +1347,7 @@ fn map_chat_messages(
 fn construct_user_message(
     m: &InnerMessage,
     variables: HashMap<String, Value>,
-) -> ChatCompletionRequestMessage {
+) -> GatewayResult<ChatCompletionRequestMessage> {
     let content = match m {
         crate::types::threads::InnerMessage::Text(text) => {
             ChatCompletionRequestUserMessageContent::Text(Prompt::render(
@@ -1220,18 +1391,27 @@ fn construct_user_message(
                     crate::types::threads::MessageContentType::InputAudio => {
                         todo!()
                     }
+                    // Chat completions take no documents, so files are sent as their text,
+                    // PDFs were already extracted by `extract_documents`
+                    crate::types::threads::MessageContentType::File => {
+                        let file = FilePart::from_message_part(m)
+                            .ok_or(ModelError::CustomError("Invalid file content".to_string()))?;
+                        ChatCompletionRequestUserMessageContentPart::Text(
+                            file.extract_text()?.into(),
+                        )
+                    }
                 };
                 messages.push(msg)
             }
             ChatCompletionRequestUserMessageContent::Array(messages)
         }
     };
-    ChatCompletionRequestMessage::User(
+    Ok(ChatCompletionRequestMessage::User(
         ChatCompletionRequestUserMessageArgs::default()
             .content(content)
             .build()
             .unwrap_or_default(),
-    )
+    ))
 }
 
 pub fn record_map_err(e: impl Into<GatewayError> + ToString, span: tracing::Span) -> GatewayError {
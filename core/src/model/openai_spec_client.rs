@@ -1,13 +1,15 @@
 use async_openai::{config::OpenAIConfig, Client};
 
-use crate::types::credentials::ApiKeyCredentials;
+use crate::types::credentials::{ApiKeyCredentials, ClientOptions};
 
 use super::error::ModelError;
+use super::openai::with_client_headers;
 
 pub fn openai_spec_client(
     credentials: Option<&ApiKeyCredentials>,
     endpoint: Option<&str>,
     provider_name: &str,
+    options: &ClientOptions,
 ) -> Result<async_openai::Client<async_openai::config::OpenAIConfig>, ModelError> {
     let mut config = OpenAIConfig::new();
 
@@ -32,5 +34,9 @@ pub fn openai_spec_client(
 
     config = config.with_api_base(api_base);
 
-    Ok(Client::with_config(config))
+    if let Some(organization) = &options.organization {
+        config = config.with_org_id(organization);
+    }
+
+    with_client_headers(Client::with_config(config), &options.headers)
 }
@@ -5,7 +5,7 @@ use super::types::ModelEvent;
 use super::ModelInstance;
 use crate::model::async_trait;
 use crate::model::OpenAIModel;
-use crate::types::credentials::ApiKeyCredentials;
+use crate::types::credentials::{ApiKeyCredentials, ClientOptions};
 use crate::types::engine::ExecutionOptions;
 use crate::types::engine::OpenAiModelParams;
 use crate::types::engine::Prompt;
@@ -34,6 +34,7 @@ impl OpenAISpecModel {
         tools: HashMap<String, Box<dyn Tool>>,
         endpoint: Option<&str>,
         provider_name: &str,
+        client_options: &ClientOptions,
     ) -> Result<Self, ModelError> {
         if provider_name == "togetherai" {
             if let Some(model_name) = &params.model {
@@ -45,7 +46,7 @@ impl OpenAISpecModel {
         }
 
         let client: Client<OpenAIConfig> =
-            openai_spec_client(credentials, endpoint, provider_name)?;
+            openai_spec_client(credentials, endpoint, provider_name, client_options)?;
         let openai_model = OpenAIModel::new(
             params,
             credentials,
@@ -0,0 +1,345 @@
+//! Checks that every provider translates `tool_choice` and `parallel_tool_calls` consistently.
+
+use async_openai::types::{ChatCompletionToolChoiceOption, ChatCompletionToolType};
+use aws_sdk_bedrockruntime::types::{
+    ContentBlock, ConversationRole, Message, ToolChoice as BedrockToolChoice, ToolUseBlock,
+};
+use clust::messages::Message as ClustMessage;
+use serde_json::json;
+use std::collections::HashMap;
+
+use super::anthropic::{anthropic_sends_tools, anthropic_tool_choice, AnthropicModel};
+use super::bedrock::bedrock_tool_choice;
+use super::bedrock::BedrockModel;
+use super::gemini::model::gemini_tool_config;
+use super::gemini::model::GeminiModel;
+use super::gemini::types::FunctionCallingMode;
+use super::handler::ToolLoopGuard;
+use super::openai::{openai_tool_choice, OpenAIModel};
+use super::tools::{GatewayTool, Tool};
+use crate::types::credentials::{ApiKeyCredentials, AwsApiKeyCredentials, BedrockCredentials};
+use crate::types::engine::{ExecutionOptions, Prompt};
+use crate::types::gateway::{ChatCompletionRequest, ToolChoice, ToolChoiceMode};
+
+fn choices() -> Vec<ToolChoice> {
+    vec![
+        ToolChoice::Mode(ToolChoiceMode::None),
+        ToolChoice::Mode(ToolChoiceMode::Auto),
+        ToolChoice::Mode(ToolChoiceMode::Required),
+        ToolChoice::function("get_weather"),
+    ]
+}
+
+#[test]
+fn test_deserialize_tool_choice() {
+    let request = serde_json::from_value::<ChatCompletionRequest>(json!({
+        "model": "gpt-4o",
+        "messages": [],
+        "tool_choice": { "type": "function", "function": { "name": "get_weather" } },
+        "parallel_tool_calls": false
+    }))
+    .unwrap();
+    assert_eq!(
+        request.tool_choice,
+        Some(ToolChoice::function("get_weather"))
+    );
+    assert_eq!(request.parallel_tool_calls, Some(false));
+
+    for (value, mode) in [
+        ("none", ToolChoiceMode::None),
+        ("auto", ToolChoiceMode::Auto),
+        ("required", ToolChoiceMode::Required),
+    ] {
+        let choice = serde_json::from_value::<ToolChoice>(json!(value)).unwrap();
+        assert_eq!(choice, ToolChoice::Mode(mode));
+        assert_eq!(serde_json::to_value(&choice).unwrap(), json!(value));
+    }
+}
+
+#[test]
+fn test_openai_tool_choice() {
+    let mapped = choices()
+        .iter()
+        .map(|c| openai_tool_choice(Some(c)))
+        .collect::<Vec<_>>();
+
+    assert!(matches!(mapped[0], ChatCompletionToolChoiceOption::None));
+    assert!(matches!(mapped[1], ChatCompletionToolChoiceOption::Auto));
+    assert!(matches!(
+        mapped[2],
+        ChatCompletionToolChoiceOption::Required
+    ));
+    match &mapped[3] {
+        ChatCompletionToolChoiceOption::Named(named) => {
+            assert_eq!(named.r#type, ChatCompletionToolType::Function);
+            assert_eq!(named.function.name, "get_weather");
+        }
+        other => panic!("Unexpected tool choice {other:?}"),
+    }
+    assert!(matches!(
+        openai_tool_choice(None),
+        ChatCompletionToolChoiceOption::Auto
+    ));
+}
+
+#[test]
+fn test_anthropic_tool_choice() {
+    let tool_use = serde_json::from_value::<ClustMessage>(json!({
+        "role": "assistant",
+        "content": [{ "type": "tool_use", "id": "call_1", "name": "get_weather", "input": {} }]
+    }))
+    .unwrap();
+    let none = ToolChoice::Mode(ToolChoiceMode::None);
+
+    assert!(anthropic_sends_tools(None, &[]));
+    assert!(anthropic_sends_tools(
+        Some(&ToolChoice::Mode(ToolChoiceMode::Required)),
+        &[]
+    ));
+    assert!(!anthropic_sends_tools(Some(&none), &[]));
+    // Tool blocks in the history need the tool definitions
+    assert!(anthropic_sends_tools(Some(&none), &[tool_use]));
+
+    let mapped = choices()
+        .iter()
+        .map(|c| anthropic_tool_choice(Some(c), None))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        mapped,
+        vec![
+            Some(json!({ "type": "none" })),
+            Some(json!({ "type": "auto" })),
+            Some(json!({ "type": "any" })),
+            Some(json!({ "type": "tool", "name": "get_weather" })),
+        ]
+    );
+    assert_eq!(anthropic_tool_choice(None, None), None);
+    assert_eq!(
+        anthropic_tool_choice(None, Some(false)),
+        Some(json!({ "type": "auto", "disable_parallel_tool_use": true }))
+    );
+    assert_eq!(
+        anthropic_tool_choice(Some(&choices()[2]), Some(false)),
+        Some(json!({ "type": "any", "disable_parallel_tool_use": true }))
+    );
+}
+
+#[test]
+fn test_anthropic_request_tool_choice() {
+    let model = AnthropicModel::new(
+        serde_json::from_value(json!({
+            "model": "claude-sonnet-4-20250514",
+            "tool_choice": { "type": "function", "function": { "name": "get_weather" } },
+            "parallel_tool_calls": false
+        }))
+        .unwrap(),
+        ExecutionOptions::default(),
+        Some(&api_key()),
+        prompt(),
+        tools(),
+    )
+    .unwrap();
+
+    let mut guard = ToolLoopGuard::new(None);
+    let request = model.build_request(None, vec![], false, &guard).unwrap();
+    let body = serde_json::to_value(&request).unwrap();
+    assert_eq!(
+        body["tool_choice"],
+        json!({ "type": "tool", "name": "get_weather", "disable_parallel_tool_use": true })
+    );
+    assert_eq!(body["tools"][0]["name"], "get_weather");
+
+    guard.next_iteration().unwrap();
+    let request = model.build_request(None, vec![], false, &guard).unwrap();
+    assert_eq!(
+        request.tool_choice,
+        Some(json!({ "type": "auto", "disable_parallel_tool_use": true }))
+    );
+}
+
+#[test]
+fn test_gemini_tool_choice() {
+    let mapped = choices()
+        .iter()
+        .map(|c| gemini_tool_config(Some(c)).unwrap().function_calling_config)
+        .collect::<Vec<_>>();
+
+    assert_eq!(mapped[0].mode, FunctionCallingMode::None);
+    assert_eq!(mapped[1].mode, FunctionCallingMode::Auto);
+    assert_eq!(mapped[2].mode, FunctionCallingMode::Any);
+    assert_eq!(mapped[2].allowed_function_names, None);
+    assert_eq!(mapped[3].mode, FunctionCallingMode::Any);
+    assert_eq!(
+        mapped[3].allowed_function_names,
+        Some(vec!["get_weather".to_string()])
+    );
+    assert!(gemini_tool_config(None).is_none());
+
+    assert_eq!(
+        serde_json::to_value(gemini_tool_config(Some(&choices()[3])).unwrap()).unwrap(),
+        json!({
+            "functionCallingConfig": {
+                "mode": "ANY",
+                "allowedFunctionNames": ["get_weather"]
+            }
+        })
+    );
+}
+
+#[test]
+fn test_bedrock_tool_choice() {
+    let mapped = choices()
+        .iter()
+        .map(|c| bedrock_tool_choice(Some(c)).unwrap())
+        .collect::<Vec<_>>();
+
+    assert!(mapped[0].is_none());
+    assert!(matches!(mapped[1], Some(BedrockToolChoice::Auto(_))));
+    assert!(matches!(mapped[2], Some(BedrockToolChoice::Any(_))));
+    match &mapped[3] {
+        Some(BedrockToolChoice::Tool(tool)) => assert_eq!(tool.name(), "get_weather"),
+        other => panic!("Unexpected tool choice {other:?}"),
+    }
+    assert!(bedrock_tool_choice(None).unwrap().is_none());
+}
+
+fn tools() -> HashMap<String, Box<dyn Tool>> {
+    let tool = GatewayTool {
+        def: serde_json::from_value(json!({
+            "type": "function",
+            "function": {
+                "name": "get_weather",
+                "parameters": { "type": "object", "properties": {} }
+            }
+        }))
+        .unwrap(),
+    };
+    HashMap::from([("get_weather".to_string(), Box::new(tool) as Box<dyn Tool>)])
+}
+
+fn prompt() -> Prompt {
+    Prompt {
+        name: "test".to_string(),
+        messages: vec![],
+        owning_model: None,
+    }
+}
+
+fn api_key() -> ApiKeyCredentials {
+    ApiKeyCredentials {
+        api_key: "test".to_string(),
+    }
+}
+
+#[test]
+fn test_forced_tool_choice_is_reset_after_first_iteration() {
+    let openai = OpenAIModel::new(
+        serde_json::from_value(json!({ "model": "gpt-4o", "tool_choice": "required" })).unwrap(),
+        Some(&api_key()),
+        ExecutionOptions::default(),
+        prompt(),
+        tools(),
+        None,
+        None,
+    )
+    .unwrap();
+    let gemini = GeminiModel::new(
+        serde_json::from_value(json!({
+            "model": "gemini-2.5-flash",
+            "tool_choice": { "type": "function", "function": { "name": "get_weather" } }
+        }))
+        .unwrap(),
+        ExecutionOptions::default(),
+        Some(&api_key()),
+        prompt(),
+        tools(),
+    )
+    .unwrap();
+
+    let mut guard = ToolLoopGuard::new(None);
+    let request = openai.build_request(&[], false, &guard).unwrap();
+    assert!(matches!(
+        request.tool_choice,
+        Some(ChatCompletionToolChoiceOption::Required)
+    ));
+    let request = gemini.build_request(vec![], &guard).unwrap();
+    assert_eq!(
+        request.tool_config.unwrap().function_calling_config.mode,
+        FunctionCallingMode::Any
+    );
+
+    // After the tool results the model has to be able to answer
+    guard.next_iteration().unwrap();
+    let request = openai.build_request(&[], false, &guard).unwrap();
+    assert!(matches!(
+        request.tool_choice,
+        Some(ChatCompletionToolChoiceOption::Auto)
+    ));
+    let request = gemini.build_request(vec![], &guard).unwrap();
+    let config = request.tool_config.unwrap().function_calling_config;
+    assert_eq!(config.mode, FunctionCallingMode::Auto);
+    assert_eq!(config.allowed_function_names, None);
+}
+
+#[tokio::test]
+async fn test_bedrock_tool_config_across_iterations() {
+    let credentials = BedrockCredentials::ApiKey(AwsApiKeyCredentials {
+        api_key: "test".to_string(),
+        region: Some("us-east-1".to_string()),
+    });
+    let model = |tool_choice: &str| {
+        serde_json::from_value(json!({
+            "model_id": "anthropic.claude-3-5-sonnet-20240620-v1:0",
+            "tool_choice": tool_choice
+        }))
+        .unwrap()
+    };
+    let required = BedrockModel::new(
+        model("required"),
+        ExecutionOptions::default(),
+        Some(&credentials),
+        prompt(),
+        tools(),
+    )
+    .await
+    .unwrap();
+    let none = BedrockModel::new(
+        model("none"),
+        ExecutionOptions::default(),
+        Some(&credentials),
+        prompt(),
+        tools(),
+    )
+    .await
+    .unwrap();
+
+    let mut guard = ToolLoopGuard::new(None);
+    let config = required.get_tools_config(&[], &guard).unwrap().unwrap();
+    assert!(matches!(
+        config.tool_choice(),
+        Some(BedrockToolChoice::Any(_))
+    ));
+    guard.next_iteration().unwrap();
+    let config = required.get_tools_config(&[], &guard).unwrap().unwrap();
+    assert!(matches!(
+        config.tool_choice(),
+        Some(BedrockToolChoice::Auto(_))
+    ));
+
+    assert!(none.get_tools_config(&[], &guard).unwrap().is_none());
+    // Converse rejects tool blocks without a tool config, so it stays without forcing
+    let history = Message::builder()
+        .role(ConversationRole::Assistant)
+        .content(ContentBlock::ToolUse(
+            ToolUseBlock::builder()
+                .tool_use_id("call_1")
+                .name("get_weather")
+                .input(aws_smithy_types::Document::Object(HashMap::new()))
+                .build()
+                .unwrap(),
+        ))
+        .build()
+        .unwrap();
+    let config = none.get_tools_config(&[history], &guard).unwrap().unwrap();
+    assert!(config.tool_choice().is_none());
+}
@@ -1,10 +1,16 @@
 use std::collections::HashMap;
 
+use crate::executor::context::ExecutorContext;
 use crate::types::gateway::ChatCompletionTool;
+use crate::types::gateway::{
+    ChatCompletionMessage, Extra, FunctionCall, FunctionParameters, McpTool, ToolCall,
+};
+use crate::types::guardrails::GuardStage;
+use crate::GatewayError;
 
+use super::apply_guardrails;
 use super::mcp::execute_mcp_tool;
-use crate::types::gateway::FunctionParameters;
-use crate::types::gateway::McpTool;
+use super::types::ToolOutput;
 
 pub struct GatewayTool {
     pub def: ChatCompletionTool,
@@ -20,6 +26,14 @@ pub trait Tool: Send + Sync + 'static {
         input: HashMap<String, serde_json::Value>,
         tags: HashMap<String, String>,
     ) -> crate::GatewayResult<serde_json::Value>;
+    /// Runs the tool and returns its output in the form sent back to the model.
+    async fn run_with_output(
+        &self,
+        input: HashMap<String, serde_json::Value>,
+        tags: HashMap<String, String>,
+    ) -> crate::GatewayResult<ToolOutput> {
+        self.run(input, tags).await.map(ToolOutput::from)
+    }
     fn stop_at_call(&self) -> bool {
         false
     }
@@ -77,17 +91,186 @@ impl Tool for McpTool {
         inputs: HashMap<String, serde_json::Value>,
         tags: HashMap<String, String>,
     ) -> crate::GatewayResult<serde_json::Value> {
-        let env = self.1.env();
-        let meta = match env {
+        let output = execute_mcp_tool(&self.1, &self.0, inputs, Some(self.meta(tags)?)).await?;
+        Ok(output
+            .structured_content
+            .unwrap_or(serde_json::Value::String(output.text)))
+    }
+
+    async fn run_with_output(
+        &self,
+        inputs: HashMap<String, serde_json::Value>,
+        tags: HashMap<String, String>,
+    ) -> crate::GatewayResult<ToolOutput> {
+        let output = execute_mcp_tool(&self.1, &self.0, inputs, Some(self.meta(tags)?)).await?;
+        Ok(output.into())
+    }
+
+    fn stop_at_call(&self) -> bool {
+        false
+    }
+}
+
+impl McpTool {
+    fn meta(&self, tags: HashMap<String, String>) -> crate::GatewayResult<serde_json::Value> {
+        Ok(match self.1.env() {
             Some(env) => serde_json::json!({"env_vars": env}),
             None => serde_json::to_value(tags)?,
+        })
+    }
+}
+
+/// Evaluates the tool call and tool result guards of a request around the runs of a tool.
+pub struct GuardedTool {
+    inner: Box<dyn Tool>,
+    extra: Extra,
+    executor_context: ExecutorContext,
+}
+
+impl GuardedTool {
+    pub fn new(inner: Box<dyn Tool>, extra: Extra, executor_context: ExecutorContext) -> Self {
+        Self {
+            inner,
+            extra,
+            executor_context,
+        }
+    }
+
+    async fn apply(
+        &self,
+        messages: &[ChatCompletionMessage],
+        stage: GuardStage,
+    ) -> crate::GatewayResult<Option<String>> {
+        let outcome = apply_guardrails(
+            messages,
+            Some(&self.extra),
+            self.executor_context.evaluator_service.as_ref().as_ref(),
+            &self.executor_context,
+            stage,
+        )
+        .await?;
+
+        Ok(outcome
+            .messages
+            .and_then(|mut messages| messages.pop())
+            .and_then(|message| message.content)
+            .and_then(|content| content.as_string()))
+    }
+
+    /// Validates the arguments of the call, returning the call message and the arguments to run
+    /// the tool with.
+    async fn guard_call(
+        &self,
+        input: HashMap<String, serde_json::Value>,
+    ) -> crate::GatewayResult<(ChatCompletionMessage, HashMap<String, serde_json::Value>)> {
+        let name = self.inner.name();
+        let arguments = serde_json::to_string(&input)?;
+        let call = ChatCompletionMessage {
+            tool_calls: Some(vec![ToolCall {
+                r#type: "function".to_string(),
+                function: FunctionCall {
+                    name: name.clone(),
+                    arguments: arguments.clone(),
+                },
+                ..Default::default()
+            }]),
+            ..ChatCompletionMessage::new_text("assistant".to_string(), arguments)
+        };
+
+        let input = match self
+            .apply(std::slice::from_ref(&call), GuardStage::ToolCall)
+            .await?
+        {
+            Some(arguments) => serde_json::from_str(&arguments).map_err(|e| {
+                GatewayError::CustomError(format!(
+                    "Guards rewrote the arguments of tool {name} to invalid JSON: {e}"
+                ))
+            })?,
+            None => input,
         };
-        Ok(execute_mcp_tool(&self.1, &self.0, inputs, Some(meta))
-            .await
-            .map(serde_json::Value::String)?)
+        Ok((call, input))
+    }
+
+    /// Validates the output of the call, returning the rewritten output if a guard changed it.
+    async fn guard_result(
+        &self,
+        call: ChatCompletionMessage,
+        output: &str,
+    ) -> crate::GatewayResult<Option<String>> {
+        let result = ChatCompletionMessage::new_text("tool".to_string(), output.to_string());
+        self.apply(&[call, result], GuardStage::ToolResult).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Tool for GuardedTool {
+    fn name(&self) -> String {
+        self.inner.name()
+    }
+
+    fn description(&self) -> String {
+        self.inner.description()
+    }
+
+    fn get_function_parameters(&self) -> Option<FunctionParameters> {
+        self.inner.get_function_parameters()
+    }
+
+    async fn run(
+        &self,
+        input: HashMap<String, serde_json::Value>,
+        tags: HashMap<String, String>,
+    ) -> crate::GatewayResult<serde_json::Value> {
+        let (call, input) = self.guard_call(input).await?;
+        let output = self.inner.run(input, tags).await?;
+        let text = ToolOutput::from(output.clone()).text;
+        Ok(match self.guard_result(call, &text).await? {
+            Some(text) => serde_json::Value::String(text),
+            None => output,
+        })
+    }
+
+    async fn run_with_output(
+        &self,
+        input: HashMap<String, serde_json::Value>,
+        tags: HashMap<String, String>,
+    ) -> crate::GatewayResult<ToolOutput> {
+        let (call, input) = self.guard_call(input).await?;
+        let mut output = self.inner.run_with_output(input, tags).await?;
+        if let Some(text) = self.guard_result(call, &output.text).await? {
+            output.text = text;
+        }
+        Ok(output)
     }
 
     fn stop_at_call(&self) -> bool {
-        false
+        self.inner.stop_at_call()
     }
 }
+
+/// Wraps the tools run by the gateway with the request guards, tools returned to the
+/// caller are left as is.
+pub fn with_tool_guards(
+    tools: HashMap<String, Box<dyn Tool>>,
+    executor_context: &ExecutorContext,
+    extra: Option<&Extra>,
+) -> HashMap<String, Box<dyn Tool>> {
+    let Some(extra) = extra.filter(|extra| !extra.guards.is_empty()) else {
+        return tools;
+    };
+
+    tools
+        .into_iter()
+        .map(|(name, tool)| {
+            if tool.stop_at_call() {
+                return (name, tool);
+            }
+            let tool: Box<dyn Tool> = Box::new(GuardedTool::new(
+                tool,
+                extra.clone(),
+                executor_context.clone(),
+            ));
+            (name, tool)
+        })
+        .collect()
+}
@@ -5,7 +5,9 @@ use serde::{Deserialize, Serialize};
 use tracing::Span;
 use tracing_opentelemetry::OpenTelemetrySpanExt;
 
+use super::images::ImageData;
 use super::CredentialsIdent;
+use crate::types::gateway::{GroundingMetadata, SafetyRating};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum StreamEvent {
@@ -43,6 +45,8 @@ pub enum ModelEventType {
     LlmStart(LLMStartEvent),
     LlmFirstToken(LLMFirstToken),
     LlmContent(LLMContentEvent),
+    LlmReasoning(LLMReasoningEvent),
+    LlmMetadata(LLMMetadataEvent),
     LlmStop(LLMFinishEvent),
     ToolStart(ToolStartEvent),
     ToolResult(ToolResultEvent),
@@ -57,6 +61,8 @@ impl ModelEventType {
             ModelEventType::RunError(_) => "run_error",
             ModelEventType::LlmStart(_) => "llm_start",
             ModelEventType::LlmContent(_) => "llm_content",
+            ModelEventType::LlmReasoning(_) => "llm_reasoning",
+            ModelEventType::LlmMetadata(_) => "llm_metadata",
             ModelEventType::LlmStop(_) => "llm_stop",
             ModelEventType::ToolStart(_) => "tool_start",
             ModelEventType::ToolResult(_) => "tool_result",
@@ -94,6 +100,22 @@ pub struct LLMContentEvent {
     pub content: String,
 }
 
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LLMReasoningEvent {
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+}
+
+/// Response annotations that are not part of the content, such as safety ratings and grounding.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LLMMetadataEvent {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_ratings: Option<Vec<SafetyRating>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grounding_metadata: Option<GroundingMetadata>,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 
 pub struct LLMStartEvent {
@@ -165,6 +187,27 @@ pub struct ToolResultEvent {
     pub output: String,
 }
 
+/// Output of a tool call as sent back to the model.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ToolOutput {
+    pub text: String,
+    /// Images for models that accept them alongside tool results
+    pub images: Vec<ImageData>,
+}
+
+impl From<serde_json::Value> for ToolOutput {
+    fn from(value: serde_json::Value) -> Self {
+        let text = match value {
+            serde_json::Value::String(text) => text,
+            value => value.to_string(),
+        };
+        Self {
+            text,
+            images: vec![],
+        }
+    }
+}
+
 pub struct ModelToolResult {
     pub tool_id: String,
     pub tool_name: String,
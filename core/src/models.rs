@@ -1,7 +1,12 @@
+use std::collections::{HashMap, HashSet};
 use std::fmt::Display;
 
 use serde::{Deserialize, Serialize};
 
+use crate::routing::RoutingStrategy;
+use crate::types::gateway::{
+    ChatCompletionMessage, ChatCompletionRequestWithTools, DynamicRouter, Extra, GuardOrName,
+};
 use crate::types::provider::{CompletionModelPrice, InferenceModelProvider, ModelPrice};
 
 use std::str::FromStr;
@@ -353,3 +358,272 @@ impl ModelMetadata {
         format!("{}/{}", self.inference_provider.provider, self.model)
     }
 }
+
+/// A config-defined model name that bundles a target model with request defaults.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct VirtualModel {
+    /// Model the virtual model resolves to, e.g. `openai/gpt-4o`. Can be omitted when a router is set.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// Default request parameters, applied only when the request does not set them.
+    #[serde(default, skip_serializing_if = "serde_json::Map::is_empty")]
+    pub parameters: serde_json::Map<String, serde_json::Value>,
+    /// Prepended as a system message when the request does not carry one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub system_prompt: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub guards: Vec<GuardOrName>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub router: Option<DynamicRouter<RoutingStrategy>>,
+}
+
+impl VirtualModel {
+    /// Applies the virtual model defaults to a request addressed to it.
+    pub fn apply(
+        &self,
+        request: ChatCompletionRequestWithTools<RoutingStrategy>,
+    ) -> Result<ChatCompletionRequestWithTools<RoutingStrategy>, serde_json::Error> {
+        let mut request_value = serde_json::to_value(request)?;
+        if let Some(obj) = request_value.as_object_mut() {
+            for (key, value) in &self.parameters {
+                if obj.get(key).is_none_or(|v| v.is_null()) {
+                    obj.insert(key.clone(), value.clone());
+                }
+            }
+        }
+        let mut request: ChatCompletionRequestWithTools<RoutingStrategy> =
+            serde_json::from_value(request_value)?;
+
+        if let Some(target) = &self.target {
+            request.request.model = target.clone();
+        }
+
+        if let Some(system_prompt) = &self.system_prompt {
+            if !request.request.messages.iter().any(|m| m.role == "system") {
+                request.request.messages.insert(
+                    0,
+                    ChatCompletionMessage::new_text("system".to_string(), system_prompt.clone()),
+                );
+            }
+        }
+
+        if !self.guards.is_empty() {
+            let extra = request.extra.get_or_insert_with(|| Extra {
+                user: None,
+                guards: vec![],
+                guard_options: None,
+                cache: None,
+                variables: None,
+            });
+            for guard in &self.guards {
+                if !extra.guards.iter().any(|g| g.id() == guard.id()) {
+                    extra.guards.push(guard.clone());
+                }
+            }
+        }
+
+        if request.router.is_none() {
+            request.router = self.router.clone();
+        }
+
+        Ok(request)
+    }
+}
+
+/// Model aliases and virtual models defined in the gateway config.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct ModelAliases {
+    #[serde(default)]
+    pub aliases: HashMap<String, String>,
+    #[serde(default)]
+    pub virtual_models: HashMap<String, VirtualModel>,
+}
+
+/// Aliases may point to other aliases; this bounds the chain and breaks cycles.
+const MAX_ALIAS_DEPTH: usize = 10;
+
+impl ModelAliases {
+    pub fn is_empty(&self) -> bool {
+        self.aliases.is_empty() && self.virtual_models.is_empty()
+    }
+
+    /// Follows alias chains until a name that is not an alias is reached.
+    pub fn resolve_alias<'a>(&'a self, model_name: &'a str) -> &'a str {
+        let mut name = model_name;
+        for _ in 0..MAX_ALIAS_DEPTH {
+            match self.aliases.get(name) {
+                Some(target) => name = target,
+                None => break,
+            }
+        }
+        name
+    }
+
+    pub fn get_virtual_model(&self, model_name: &str) -> Option<(&str, &VirtualModel)> {
+        let name = self.resolve_alias(model_name);
+        self.virtual_models
+            .get_key_value(name)
+            .map(|(name, model)| (name.as_str(), model))
+    }
+
+    /// Resolves a requested name to the concrete model name it targets, if it is an alias
+    /// or a virtual model with a target.
+    pub fn resolve_target(&self, model_name: &str) -> Option<String> {
+        let mut name = self.resolve_alias(model_name).to_string();
+        let mut resolved = name != model_name;
+        for _ in 0..MAX_ALIAS_DEPTH {
+            match self
+                .virtual_models
+                .get(&name)
+                .and_then(|m| m.target.as_ref())
+            {
+                Some(target) => {
+                    name = self.resolve_alias(target).to_string();
+                    resolved = true;
+                }
+                None => break,
+            }
+        }
+
+        resolved.then_some(name)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, model) in &self.virtual_models {
+            if model.target.is_none() && model.router.is_none() {
+                return Err(format!(
+                    "Virtual model {name} must define either a target or a router"
+                ));
+            }
+            if self.aliases.contains_key(name) {
+                return Err(format!(
+                    "{name} is defined both as an alias and as a virtual model"
+                ));
+            }
+        }
+
+        for name in self.aliases.keys() {
+            if self.aliases.contains_key(self.resolve_alias(name)) {
+                return Err(format!("Alias {name} has a cyclic or too deep definition"));
+            }
+        }
+
+        // Targets may name other virtual models, directly or through an alias
+        for name in self.virtual_models.keys() {
+            let mut visited = HashSet::from([name.as_str()]);
+            let mut current = name.as_str();
+            while let Some(target) = self
+                .virtual_models
+                .get(current)
+                .and_then(|m| m.target.as_deref())
+            {
+                current = self.resolve_alias(target);
+                if !visited.insert(current) || visited.len() > MAX_ALIAS_DEPTH {
+                    return Err(format!(
+                        "Virtual model {name} has a cyclic or too deep target"
+                    ));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::gateway::ChatCompletionRequest;
+
+    fn aliases() -> ModelAliases {
+        serde_json::from_value(serde_json::json!({
+            "aliases": {
+                "fast": "openai/gpt-4o-mini",
+                "bot": "support-bot"
+            },
+            "virtual_models": {
+                "support-bot": {
+                    "target": "fast",
+                    "parameters": { "temperature": 0.2, "max_tokens": 100 },
+                    "system_prompt": "You are a support assistant.",
+                    "guards": ["toxicity"]
+                }
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_resolve_target() {
+        let aliases = aliases();
+        assert!(aliases.validate().is_ok());
+        assert_eq!(
+            aliases.resolve_target("fast"),
+            Some("openai/gpt-4o-mini".to_string())
+        );
+        assert_eq!(
+            aliases.resolve_target("bot"),
+            Some("openai/gpt-4o-mini".to_string())
+        );
+        assert_eq!(aliases.resolve_target("openai/gpt-4o"), None);
+        assert!(aliases.get_virtual_model("bot").is_some());
+    }
+
+    #[test]
+    fn test_cyclic_alias_is_invalid() {
+        let mut aliases = aliases();
+        aliases.aliases.insert("a".to_string(), "b".to_string());
+        aliases.aliases.insert("b".to_string(), "a".to_string());
+        assert!(aliases.validate().is_err());
+    }
+
+    #[test]
+    fn test_cyclic_virtual_model_is_invalid() {
+        // support-bot targets fast, which now leads back to support-bot
+        let mut looped = aliases();
+        looped.aliases.insert("fast".to_string(), "bot".to_string());
+        assert!(looped.validate().is_err());
+
+        let mut aliases = aliases();
+        let mut other = aliases.virtual_models["support-bot"].clone();
+        other.target = Some("support-bot".to_string());
+        aliases
+            .virtual_models
+            .insert("other-bot".to_string(), other);
+        assert!(aliases.validate().is_ok());
+        aliases
+            .virtual_models
+            .get_mut("support-bot")
+            .unwrap()
+            .target = Some("other-bot".to_string());
+        assert!(aliases.validate().is_err());
+    }
+
+    #[test]
+    fn test_apply_virtual_model() {
+        let aliases = aliases();
+        let (_, virtual_model) = aliases.get_virtual_model("support-bot").unwrap();
+        let request = ChatCompletionRequestWithTools {
+            request: ChatCompletionRequest {
+                model: "support-bot".to_string(),
+                messages: vec![ChatCompletionMessage::new_text(
+                    "user".to_string(),
+                    "Hello".to_string(),
+                )],
+                temperature: Some(0.9),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let request = virtual_model.apply(request).unwrap();
+        assert_eq!(request.request.model, "fast");
+        assert_eq!(request.request.temperature, Some(0.9));
+        assert_eq!(request.request.max_tokens, Some(100));
+        assert_eq!(request.request.messages.len(), 2);
+        assert_eq!(request.request.messages[0].role, "system");
+        assert_eq!(request.extra.unwrap().guards[0].id(), "toxicity");
+    }
+}
@@ -60,6 +60,20 @@ impl Interceptor for RouterGuardrailInterceptor {
                 "schema": schema,
                 "passed": passed
             })),
+            GuardResult::Redacted {
+                messages,
+                entities,
+                passed,
+                ..
+            } => Ok(serde_json::json!({
+                "messages": messages,
+                "entities": entities,
+                "passed": passed
+            })),
+            GuardResult::Transformed { messages, passed } => Ok(serde_json::json!({
+                "messages": messages,
+                "passed": passed
+            })),
         }
     }
 
@@ -98,6 +112,20 @@ impl Interceptor for RouterGuardrailInterceptor {
                 "schema": schema,
                 "passed": passed
             })),
+            GuardResult::Redacted {
+                messages,
+                entities,
+                passed,
+                ..
+            } => Ok(serde_json::json!({
+                "messages": messages,
+                "entities": entities,
+                "passed": passed
+            })),
+            GuardResult::Transformed { messages, passed } => Ok(serde_json::json!({
+                "messages": messages,
+                "passed": passed
+            })),
         }
     }
 }
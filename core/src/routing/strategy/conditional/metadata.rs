@@ -354,6 +354,7 @@ mod tests {
         let extra = Some(Extra {
             user: Some(user),
             guards: vec![],
+            guard_options: None,
             cache: None,
             variables: None,
         });
@@ -396,6 +397,7 @@ mod tests {
         let extra = Some(Extra {
             user: None,
             guards: vec![],
+            guard_options: None,
             cache: None,
             variables: Some(variables),
         });
@@ -443,6 +445,7 @@ mod tests {
         let extra = Some(Extra {
             user: Some(user),
             guards: vec![],
+            guard_options: None,
             cache: None,
             variables: None,
         });
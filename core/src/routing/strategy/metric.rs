@@ -32,7 +32,7 @@ pub enum MetricOptimizationDirection {
 }
 
 impl MetricSelector {
-    fn get_optimization_direction(&self) -> MetricOptimizationDirection {
+    pub(crate) fn get_optimization_direction(&self) -> MetricOptimizationDirection {
         match self {
             MetricSelector::Tps => MetricOptimizationDirection::Maximize,
             _ => MetricOptimizationDirection::Minimize,
@@ -41,7 +41,7 @@ impl MetricSelector {
 }
 
 impl MetricSelector {
-    fn get_value(&self, metrics: &Metrics) -> Option<f64> {
+    pub(crate) fn get_value(&self, metrics: &Metrics) -> Option<f64> {
         match self {
             MetricSelector::Requests => metrics.requests,
             MetricSelector::Latency => metrics.latency,
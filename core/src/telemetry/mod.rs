@@ -66,6 +66,9 @@ impl TraceTenantResolver for DummyTraceTenantResolver {
     }
 }
 
+/// Project that spans without a tenant are streamed under.
+pub const DEFAULT_PROJECT_ID: &str = "default";
+
 #[async_trait::async_trait]
 pub trait SpanWriterTransport: Send + Sync {
     async fn insert_values(
@@ -311,16 +314,6 @@ impl TraceService for TraceServiceImpl {
                         .and_then(|v| Some(v.as_str()?.to_owned()))
                         .or(tenant_from_header.as_ref().map(|v| v.0.clone()));
 
-                    if tenant_id.is_none() {
-                        tracing::debug!(
-                            target: "otel",
-                            "No tenant id found in span {} with attributes: {:#?}",
-                            span.name,
-                            attributes
-                        );
-                        continue;
-                    }
-
                     let project_id = attributes
                         .remove("langdb.project_id")
                         .and_then(|v| Some(v.as_str()?.to_owned()))
@@ -360,18 +353,34 @@ impl TraceService for TraceServiceImpl {
                         kind,
                         attributes,
                         tenant_id,
-                        project_id: project_id.clone(),
+                        project_id,
                         thread_id,
                         tags,
                         run_id,
                     };
 
-                    if let Some(project_id) = project_id.as_ref() {
+                    // Spans without a tenant are only streamed live, never stored
+                    let stream_project_id = match (&span.tenant_id, &span.project_id) {
+                        (_, Some(project_id)) => Some(project_id.as_str()),
+                        (None, None) => Some(DEFAULT_PROJECT_ID),
+                        (Some(_), None) => None,
+                    };
+                    if let Some(project_id) = stream_project_id {
                         if let Some(sender) = self.project_trace_senders.get(project_id).as_deref()
                         {
                             let _result = sender.send(span.clone());
                         }
                     }
+
+                    if span.tenant_id.is_none() {
+                        tracing::debug!(
+                            target: "otel",
+                            "No tenant id found in span {} with attributes: {:#?}",
+                            span.operation_name,
+                            span.attributes
+                        );
+                        continue;
+                    }
                     self.writer_sender.send(span).await.unwrap();
                 }
             }
@@ -33,6 +33,22 @@ impl Credentials {
     }
 }
 
+/// Client settings that accompany provider credentials (OpenAI compatible clients only).
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq, Default)]
+pub struct ClientOptions {
+    pub organization: Option<String>,
+    pub api_version: Option<String>,
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub headers: HashMap<String, String>,
+}
+
+impl ClientOptions {
+    pub fn is_empty(&self) -> bool {
+        self.organization.is_none() && self.api_version.is_none() && self.headers.is_empty()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Eq, PartialEq)]
 pub struct IntegrationCredentials {
     pub secrets: HashMap<String, Value>,
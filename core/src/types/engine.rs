@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::{collections::HashMap, fmt::Display, ops::Deref, str::FromStr};
 
+use crate::model::images::ImageOptions;
 use crate::model::CredentialsIdent;
 use crate::types::credentials::BedrockCredentials;
 use crate::types::json::JsonStringCond;
@@ -17,7 +18,14 @@ use validator::Validate;
 
 use super::message::MessageType;
 use super::message::PromptMessage;
-use super::{credentials::ApiKeyCredentials, provider::BedrockProvider};
+use super::{
+    credentials::{ApiKeyCredentials, ClientOptions},
+    gateway::{
+        BedrockGuardrailConfig, ReasoningEffort, SafetySetting, ThinkingConfig, ToolChoice,
+        ToolLoopOptions,
+    },
+    provider::BedrockProvider,
+};
 use serde::de::Error;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -174,6 +182,8 @@ impl CompletionModelDefinition {
 #[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, Default)]
 pub struct ExecutionOptions {
     pub max_retries: Option<u32>,
+    pub images: Option<ImageOptions>,
+    pub tool_loop: Option<ToolLoopOptions>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq)]
@@ -333,6 +343,8 @@ pub enum CompletionEngineParams {
         execution_options: ExecutionOptions,
         credentials: Option<ApiKeyCredentials>,
         endpoint: Option<String>,
+        #[serde(default)]
+        client_options: ClientOptions,
     },
     Bedrock {
         credentials: Option<BedrockCredentials>,
@@ -354,6 +366,8 @@ pub enum CompletionEngineParams {
         params: OpenAiModelParams,
         execution_options: ExecutionOptions,
         credentials: Option<ApiKeyCredentials>,
+        #[serde(default)]
+        client_options: ClientOptions,
     },
 }
 
@@ -537,6 +551,18 @@ pub struct OpenAiModelParams {
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt_cache_key: Option<String>,
+
+    /// Constrains effort on reasoning for reasoning models.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+
+    /// Controls which (if any) tool is called by the model.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+
+    /// Whether to enable parallel function calling during tool use.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, Validate)]
@@ -684,6 +710,10 @@ pub struct BedrockModelParams {
     /// A list of stop sequences. A stop sequence is a sequence of characters that causes the model to stop generating the response.
     #[serde(alias = "stop")]
     pub stop_sequences: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guardrail_config: Option<BedrockGuardrailConfig>,
     #[serde(flatten)]
     pub additional_parameters: HashMap<String, Value>,
 }
@@ -712,8 +742,8 @@ impl Deref for ClaudeModel {
 impl From<claude::ClaudeModel> for ClaudeModel {
     fn from(model: claude::ClaudeModel) -> Self {
         Self {
-            model,
             string: model.to_string(),
+            model,
         }
     }
 }
@@ -772,6 +802,13 @@ pub struct AnthropicModelParams {
     pub top_k: Option<claude::TopK>,
 
     pub thinking: Option<claude::Thinking>,
+
+    /// Sent as Anthropic's `tool_choice` object, `required` maps to `any`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    /// Sent as `disable_parallel_tool_use` when `false`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
@@ -791,6 +828,19 @@ pub struct GeminiModelParams {
     pub response_logprobs: Option<bool>,
     pub logprobs: Option<i32>,
     pub response_format: Option<ResponseFormat>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety_settings: Option<Vec<SafetySetting>>,
+    /// Takes precedence over `thinking_budget`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thinking_config: Option<ThinkingConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub google_search: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code_execution: Option<bool>,
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
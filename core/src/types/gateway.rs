@@ -2,13 +2,15 @@ use crate::model::tools::Tool;
 use crate::model::types::ModelFinishReason;
 use crate::model::CredentialsIdent;
 use crate::types::cache::ResponseCacheOptions;
+use crate::types::guardrails::GuardEvaluationOptions;
 use crate::types::provider::ModelPrice;
 use async_openai::types::Base64EmbeddingVector;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::hash::Hash;
+use std::hash::{BuildHasher, Hash, RandomState};
+use std::sync::LazyLock;
 use thiserror::Error;
 
 pub use async_openai::types::ResponseFormat as OpenaiResponseFormat;
@@ -54,11 +56,15 @@ pub struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tools: Option<Vec<ChatCompletionTool>>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub tool_choice: Option<Value>,
+    pub tool_choice: Option<ToolChoice>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parallel_tool_calls: Option<bool>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stream_options: Option<StreamOptions>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub prompt_cache_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
 }
 
 impl ChatCompletionRequest {
@@ -80,6 +86,100 @@ pub struct Thinking {
     pub budget_tokens: u64,
 }
 
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolChoiceMode {
+    None,
+    Auto,
+    Required,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NamedToolChoice {
+    pub r#type: String,
+    pub function: NamedToolChoiceFunction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NamedToolChoiceFunction {
+    pub name: String,
+}
+
+/// OpenAI style `tool_choice`, translated to each provider's native tool choice config.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+pub enum ToolChoice {
+    Mode(ToolChoiceMode),
+    Named(NamedToolChoice),
+}
+
+impl ToolChoice {
+    pub fn function(name: impl Into<String>) -> Self {
+        ToolChoice::Named(NamedToolChoice {
+            r#type: "function".to_string(),
+            function: NamedToolChoiceFunction { name: name.into() },
+        })
+    }
+
+    pub fn function_name(&self) -> Option<&str> {
+        match self {
+            ToolChoice::Named(named) => Some(named.function.name.as_str()),
+            ToolChoice::Mode(_) => None,
+        }
+    }
+}
+
+/// Provider agnostic reasoning effort, mapped onto each provider's thinking budget.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReasoningEffort {
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    // Anthropic rejects thinking budgets below 1024 tokens
+    pub const MIN_BUDGET_TOKENS: u64 = 1024;
+
+    pub fn budget_tokens(&self) -> u64 {
+        match self {
+            ReasoningEffort::Minimal => Self::MIN_BUDGET_TOKENS,
+            ReasoningEffort::Low => 2048,
+            ReasoningEffort::Medium => 8192,
+            ReasoningEffort::High => 24576,
+        }
+    }
+
+    // Room left for the answer when the request doesn't limit `max_tokens`
+    pub const ANSWER_TOKENS: u64 = 4096;
+
+    /// `max_tokens` to send with thinking enabled, providers count the budget against it.
+    pub fn max_tokens_with(&self, max_tokens: Option<u64>) -> u64 {
+        max_tokens.unwrap_or(self.budget_tokens() + Self::ANSWER_TOKENS)
+    }
+
+    /// Budget below `max_tokens`, or `None` when there is no room for thinking.
+    pub fn budget_tokens_within(&self, max_tokens: u64) -> Option<u64> {
+        let budget = self.budget_tokens();
+        let budget = if budget >= max_tokens {
+            max_tokens / 2
+        } else {
+            budget
+        };
+        (budget >= Self::MIN_BUDGET_TOKENS).then_some(budget)
+    }
+
+    pub fn as_thinking(&self, max_tokens: u64) -> Option<Thinking> {
+        self.budget_tokens_within(max_tokens)
+            .map(|budget_tokens| Thinking {
+                r#type: "enabled".to_string(),
+                budget_tokens,
+            })
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Extra {
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -88,6 +188,9 @@ pub struct Extra {
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub guards: Vec<GuardOrName>,
 
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guard_options: Option<GuardEvaluationOptions>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache: Option<ResponseCacheOptions>,
 
@@ -102,6 +205,15 @@ pub enum GuardOrName {
     GuardWithParameters(GuardWithParameters),
 }
 
+impl GuardOrName {
+    pub fn id(&self) -> &str {
+        match self {
+            GuardOrName::GuardId(id) => id,
+            GuardOrName::GuardWithParameters(GuardWithParameters { id, .. }) => id,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuardWithParameters {
     pub id: String,
@@ -129,18 +241,120 @@ pub struct ChatCompletionRequestWithTools<T> {
     pub extra: Option<Extra>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub fallbacks: Option<Vec<ModelNameOrTarget>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool_loop: Option<ToolLoopOptions>,
     #[serde(flatten)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub provider_specific: Option<ProviderSpecificRequest>,
 }
 
+/// Limits for the server-side loop that runs tool calls and sends their results back to the model.
+#[serde_with::skip_serializing_none]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ToolLoopOptions {
+    /// Maximum number of tool rounds before the execution fails
+    #[serde(default = "default_max_tool_iterations")]
+    pub max_iterations: u32,
+    pub tool_timeout_secs: Option<u64>,
+    /// Deadline for the whole execution, tool calls included
+    pub deadline_secs: Option<u64>,
+    /// Run the tool calls of one model response concurrently
+    #[serde(default = "default_parallel_tools")]
+    pub parallel: bool,
+    #[serde(default)]
+    pub on_tool_error: ToolErrorPolicy,
+}
+
+fn default_parallel_tools() -> bool {
+    true
+}
+
+fn default_max_tool_iterations() -> u32 {
+    10
+}
+
+impl Default for ToolLoopOptions {
+    fn default() -> Self {
+        Self {
+            max_iterations: default_max_tool_iterations(),
+            tool_timeout_secs: None,
+            deadline_secs: None,
+            parallel: default_parallel_tools(),
+            on_tool_error: ToolErrorPolicy::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolErrorPolicy {
+    /// Send the error to the model as the tool result
+    #[default]
+    Continue,
+    /// Fail the execution
+    Stop,
+}
+
+/// Blocking threshold for a harm category, e.g. `HARM_CATEGORY_HATE_SPEECH` / `BLOCK_ONLY_HIGH`.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct SafetySetting {
+    pub category: String,
+    pub threshold: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Default, Clone, PartialEq, Eq)]
+#[serde(rename_all(serialize = "camelCase"))]
+pub struct ThinkingConfig {
+    #[serde(alias = "thinkingBudget", skip_serializing_if = "Option::is_none")]
+    pub thinking_budget: Option<i32>,
+    #[serde(alias = "includeThoughts", skip_serializing_if = "Option::is_none")]
+    pub include_thoughts: Option<bool>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProviderSpecificRequest {
     // Anthropic request
     #[serde(skip_serializing_if = "Option::is_none")]
     pub thinking: Option<Thinking>,
+    // Anthropic and Gemini request
     #[serde(skip_serializing_if = "Option::is_none")]
     pub top_k: Option<u32>,
+    // Gemini request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_settings: Option<Vec<SafetySetting>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thinking_config: Option<ThinkingConfig>,
+    /// Enables Google Search grounding
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub google_search: Option<bool>,
+    /// Enables the built-in code execution tool
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub code_execution: Option<bool>,
+    // Bedrock request
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guardrail_config: Option<BedrockGuardrailConfig>,
+}
+
+/// Bedrock guardrail attached to Converse requests.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct BedrockGuardrailConfig {
+    #[serde(alias = "guardrail_identifier")]
+    pub identifier: String,
+    #[serde(alias = "guardrail_version")]
+    pub version: String,
+    /// Returns the guardrail assessment trace with the response
+    #[serde(default)]
+    pub trace: bool,
+    /// Streaming only, `sync` holds back output until the guardrail has assessed it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stream_processing_mode: Option<GuardrailStreamProcessingMode>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardrailStreamProcessingMode {
+    Sync,
+    Async,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -206,6 +420,18 @@ pub enum McpTransportType {
         #[serde(default)]
         env: Option<HashMap<String, String>>,
     },
+    /// Local server launched as a subprocess, speaking MCP over stdin/stdout.
+    /// Its environment and working directory come from the operator's allowlist,
+    /// requests that set `env` or `cwd` are rejected.
+    Stdio {
+        command: String,
+        #[serde(default)]
+        args: Vec<String>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        env: Option<HashMap<String, String>>,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        cwd: Option<String>,
+    },
     #[serde(rename = "in-memory", alias = "memory")]
     InMemory {
         #[serde(default = "default_in_memory_name")]
@@ -213,13 +439,52 @@ pub enum McpTransportType {
     },
 }
 
+/// Randomly keyed hashers of the connection settings in pool keys, so that
+/// colliding credentials can't be crafted to share another caller's session.
+static MCP_KEY_HASHERS: LazyLock<(RandomState, RandomState)> =
+    LazyLock::new(|| (RandomState::new(), RandomState::new()));
+
+fn connection_digest(
+    headers: &HashMap<String, String>,
+    env: &Option<HashMap<String, String>>,
+) -> String {
+    let mut headers: Vec<_> = headers.iter().collect();
+    headers.sort();
+    let mut env: Vec<_> = env.iter().flatten().collect();
+    env.sort();
+
+    let (first, second) = &*MCP_KEY_HASHERS;
+    format!(
+        "{:016x}{:016x}",
+        first.hash_one((&headers, &env)),
+        second.hash_one((&headers, &env))
+    )
+}
+
 impl McpTransportType {
+    /// Identifies a pooled session. Headers and env are part of the key so servers
+    /// reached with different credentials never share a session.
     pub fn key(&self) -> String {
         match self {
-            McpTransportType::Sse { server_url, .. } => format!("sse:{server_url}"),
-            McpTransportType::Ws { server_url, .. } => format!("ws:{server_url}"),
+            McpTransportType::Sse {
+                server_url,
+                headers,
+                env,
+            } => format!("sse:{server_url}#{}", connection_digest(headers, env)),
+            McpTransportType::Ws {
+                server_url,
+                headers,
+                env,
+            } => format!("ws:{server_url}#{}", connection_digest(headers, env)),
             McpTransportType::InMemory { name, .. } => format!("in-memory:{name}"),
-            McpTransportType::Http { server_url, .. } => format!("http:{server_url}"),
+            McpTransportType::Http {
+                server_url,
+                headers,
+                env,
+            } => format!("http:{server_url}#{}", connection_digest(headers, env)),
+            McpTransportType::Stdio { command, args, .. } => {
+                format!("stdio:{command} {}", args.join(" "))
+            }
         }
     }
 }
@@ -243,6 +508,7 @@ impl McpDefinition {
             McpTransportType::Sse { server_url, .. } => server_url.clone(),
             McpTransportType::Ws { server_url, .. } => server_url.clone(),
             McpTransportType::Http { server_url, .. } => server_url.clone(),
+            McpTransportType::Stdio { command, .. } => command.clone(),
         }
     }
 
@@ -252,6 +518,8 @@ impl McpDefinition {
             McpTransportType::Sse { env, .. } => env.clone(),
             McpTransportType::Ws { env, .. } => env.clone(),
             McpTransportType::Http { env, .. } => env.clone(),
+            // Set by the operator in the allowlist, never by the request
+            McpTransportType::Stdio { .. } => None,
         }
     }
 }
@@ -297,6 +565,19 @@ pub struct InputAudio {
     pub format: String,
 }
 
+/// A document attached to a message, either inline as base64 (optionally a data URL) or by URL.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, Default)]
+pub struct InputFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_data: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum ContentType {
@@ -304,6 +585,7 @@ pub enum ContentType {
     Text,
     ImageUrl,
     InputAudio,
+    File,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, Default)]
@@ -312,6 +594,8 @@ pub struct Content {
     pub text: Option<String>,
     pub image_url: Option<ImageUrl>,
     pub audio: Option<InputAudio>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<InputFile>,
     pub cache_control: Option<CacheControl>,
 }
 
@@ -357,6 +641,70 @@ pub struct ChatCompletionMessage {
     pub tool_call_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cache_control: Option<CacheControl>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+    /// Provider signature of the reasoning, required to send it back in multi-turn conversations
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety_ratings: Option<Vec<SafetyRating>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub grounding_metadata: Option<GroundingMetadata>,
+}
+
+/// Safety rating of a response for one harm category.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct SafetyRating {
+    pub category: String,
+    pub probability: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blocked: Option<bool>,
+}
+
+/// Search grounding of a response and the citations of its sources.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, Hash, PartialEq, Eq)]
+pub struct GroundingMetadata {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub web_search_queries: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grounding_chunks: Vec<GroundingChunk>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub grounding_supports: Vec<GroundingSupport>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub citations: Vec<Citation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct GroundingChunk {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub web: Option<WebChunk>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct WebChunk {
+    pub uri: String,
+    pub title: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct GroundingSupport {
+    pub segment: TextSegment,
+    #[serde(default)]
+    pub grounding_chunk_indices: Vec<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct TextSegment {
+    pub start_index: i32,
+    pub end_index: i32,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct Citation {
+    pub start_index: i32,
+    pub end_index: i32,
+    pub uri: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -380,6 +728,10 @@ impl ChatCompletionMessageWithFinishReason {
     pub fn message(&self) -> &ChatCompletionMessage {
         &self.message
     }
+
+    pub fn message_mut(&mut self) -> &mut ChatCompletionMessage {
+        &mut self.message
+    }
 }
 
 impl ChatCompletionMessage {
@@ -512,7 +864,7 @@ pub struct ChatCompletionChunkChoice {
     pub logprobs: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct ChatCompletionDelta {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub role: Option<String>,
@@ -520,6 +872,14 @@ pub struct ChatCompletionDelta {
     pub content: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_signature: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub safety_ratings: Option<Vec<SafetyRating>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub grounding_metadata: Option<GroundingMetadata>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1055,4 +1415,43 @@ mod tests {
         assert_eq!(cache_control.r#type, CacheControlType::Ephemeral);
         assert_eq!(cache_control.ttl, Some(CacheControlTtl::OneHour));
     }
+
+    #[test]
+    fn test_reasoning_effort() {
+        let request = serde_json::from_value::<ChatCompletionRequest>(serde_json::json!({
+            "model": "claude-sonnet-4",
+            "messages": [],
+            "reasoning_effort": "medium"
+        }))
+        .unwrap();
+        let effort = request.reasoning_effort.unwrap();
+        assert_eq!(effort, ReasoningEffort::Medium);
+        assert_eq!(
+            effort.max_tokens_with(None),
+            8192 + ReasoningEffort::ANSWER_TOKENS
+        );
+        assert_eq!(effort.max_tokens_with(Some(2000)), 2000);
+        assert_eq!(
+            effort.budget_tokens_within(effort.max_tokens_with(None)),
+            Some(8192)
+        );
+        assert_eq!(effort.budget_tokens_within(4096), Some(2048));
+        assert_eq!(ReasoningEffort::Low.budget_tokens_within(1500), None);
+        // The budget has to stay below max_tokens
+        assert!(ReasoningEffort::Minimal.as_thinking(1024).is_none());
+
+        let message = ChatCompletionMessage {
+            role: "assistant".to_string(),
+            content: Some(ChatCompletionContent::Text("42".to_string())),
+            reasoning_content: Some("6 * 7".to_string()),
+            reasoning_signature: Some("sig".to_string()),
+            ..Default::default()
+        };
+        let value = serde_json::to_value(&message).unwrap();
+        assert_eq!(value["reasoning_content"], "6 * 7");
+        assert_eq!(
+            serde_json::from_value::<ChatCompletionMessage>(value).unwrap(),
+            message
+        );
+    }
 }
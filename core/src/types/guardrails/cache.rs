@@ -0,0 +1,132 @@
+use std::collections::HashMap;
+use std::hash::{BuildHasher, Hash, Hasher, RandomState};
+use std::sync::LazyLock;
+use std::time::{Duration, Instant};
+
+use crate::types::gateway::ChatCompletionMessage;
+use crate::types::guardrails::{Guard, GuardResult, GuardStage};
+
+/// Two randomly keyed hashers, combined into a 128 bit digest so that
+/// different evaluations can't be made to share a cached result.
+static KEY_HASHERS: LazyLock<(RandomState, RandomState)> =
+    LazyLock::new(|| (RandomState::new(), RandomState::new()));
+
+/// Guard results keyed by a digest of the guard definition, stage and evaluated messages.
+pub struct GuardResultCache {
+    capacity: usize,
+    ttl: Duration,
+    entries: parking_lot::Mutex<HashMap<u128, (GuardResult, Instant)>>,
+}
+
+impl Default for GuardResultCache {
+    fn default() -> Self {
+        Self::new(1024, Duration::from_secs(600))
+    }
+}
+
+impl GuardResultCache {
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            entries: parking_lot::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Content digest of an evaluation, including the resolved guard parameters.
+    pub fn key(guard: &Guard, stage: &GuardStage, messages: &[ChatCompletionMessage]) -> u128 {
+        let definition = serde_json::to_string(guard).unwrap_or_else(|_| guard.id().clone());
+        let hash = |state: &RandomState| {
+            let mut hasher = state.build_hasher();
+            definition.hash(&mut hasher);
+            stage.hash(&mut hasher);
+            messages.hash(&mut hasher);
+            hasher.finish()
+        };
+
+        let (first, second) = &*KEY_HASHERS;
+        (u128::from(hash(first)) << 64) | u128::from(hash(second))
+    }
+
+    pub fn get(&self, key: u128) -> Option<GuardResult> {
+        let mut entries = self.entries.lock();
+        match entries.get(&key) {
+            Some((result, inserted_at)) if inserted_at.elapsed() < self.ttl => Some(result.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    pub fn insert(&self, key: u128, result: GuardResult) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        let mut entries = self.entries.lock();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, (_, inserted_at)| inserted_at.elapsed() < self.ttl);
+            // Still full, evict the oldest entry
+            if entries.len() >= self.capacity {
+                if let Some(oldest) = entries
+                    .iter()
+                    .min_by_key(|(_, (_, inserted_at))| *inserted_at)
+                    .map(|(key, _)| *key)
+                {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(key, (result, Instant::now()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::guardrails::guard_fixture;
+
+    fn guard(max_words: u64) -> Guard {
+        guard_fixture(
+            "word_count",
+            serde_json::json!({"user_defined_parameters": {"max_words": max_words}}),
+        )
+    }
+
+    #[test]
+    fn test_guard_result_cache() {
+        let cache = GuardResultCache::new(1, Duration::from_secs(60));
+        let messages = vec![ChatCompletionMessage::new_text(
+            "system".to_string(),
+            "You are a helpful assistant".to_string(),
+        )];
+        let result = GuardResult::Boolean {
+            passed: true,
+            confidence: None,
+        };
+
+        let key = GuardResultCache::key(&guard(10), &GuardStage::Input, &messages);
+        assert_eq!(
+            key,
+            GuardResultCache::key(&guard(10), &GuardStage::Input, &messages)
+        );
+        assert_ne!(
+            key,
+            GuardResultCache::key(&guard(20), &GuardStage::Input, &messages)
+        );
+        assert_ne!(
+            key,
+            GuardResultCache::key(&guard(10), &GuardStage::Output, &messages)
+        );
+
+        cache.insert(key, result.clone());
+        assert_eq!(cache.get(key), Some(result.clone()));
+
+        // Capacity of one evicts the previous entry
+        cache.insert(key + 1, result);
+        assert!(cache.get(key).is_none());
+        assert!(cache.get(key + 1).is_some());
+    }
+}
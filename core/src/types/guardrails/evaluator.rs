@@ -1,4 +1,4 @@
-use crate::types::gateway::ChatCompletionMessage;
+use crate::types::gateway::{ChatCompletionContent, ChatCompletionMessage, ContentType};
 use crate::types::guardrails::Guard;
 use crate::types::guardrails::GuardResult;
 
@@ -11,16 +11,117 @@ pub trait Evaluator: Send + Sync {
         guard: &Guard,
     ) -> Result<GuardResult, String>;
 
+    /// Text of the last message, with the text parts of multi-part content joined by newlines.
     fn messages_to_text(&self, messages: &[ChatCompletionMessage]) -> Result<String, String> {
-        let text = messages
+        let content = messages
             .last()
             .ok_or("No message in request")?
             .content
             .as_ref()
-            .ok_or("No content in message")?
-            .as_string()
-            .ok_or("No text in content")?;
+            .ok_or("No content in message")?;
 
-        Ok(text)
+        match content {
+            ChatCompletionContent::Text(text) => Ok(text.clone()),
+            ChatCompletionContent::Content(parts) => {
+                let texts: Vec<&str> = parts
+                    .iter()
+                    .filter(|part| part.r#type == ContentType::Text)
+                    .filter_map(|part| part.text.as_deref())
+                    .collect();
+                if texts.is_empty() {
+                    return Err("No text in content".to_string());
+                }
+                Ok(texts.join("\n"))
+            }
+        }
+    }
+
+    /// Returns the messages with the text of the last one replaced. In multi-part
+    /// content the first text part takes the new text, the other text parts are
+    /// removed and images, audio and files are kept in place.
+    fn with_last_text(
+        &self,
+        messages: &[ChatCompletionMessage],
+        text: String,
+    ) -> Vec<ChatCompletionMessage> {
+        let mut messages = messages.to_vec();
+        if let Some(message) = messages.last_mut() {
+            message.content = match message.content.take() {
+                Some(ChatCompletionContent::Content(parts)) => {
+                    let mut text = Some(text);
+                    let parts = parts
+                        .into_iter()
+                        .filter_map(|mut part| {
+                            if part.r#type != ContentType::Text {
+                                return Some(part);
+                            }
+                            part.text = Some(text.take()?);
+                            Some(part)
+                        })
+                        .collect();
+                    Some(ChatCompletionContent::Content(parts))
+                }
+                _ => Some(ChatCompletionContent::Text(text)),
+            };
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::gateway::{Content, ImageUrl};
+
+    struct Noop;
+
+    #[async_trait::async_trait]
+    impl Evaluator for Noop {
+        async fn evaluate(
+            &self,
+            _messages: &[ChatCompletionMessage],
+            _guard: &Guard,
+        ) -> Result<GuardResult, String> {
+            unreachable!()
+        }
+    }
+
+    #[test]
+    fn test_with_last_text_keeps_non_text_parts() {
+        let text = |text: &str| Content {
+            r#type: ContentType::Text,
+            text: Some(text.to_string()),
+            ..Default::default()
+        };
+        let image = Content {
+            r#type: ContentType::ImageUrl,
+            image_url: Some(ImageUrl {
+                url: "https://example.com/cat.png".to_string(),
+            }),
+            ..Default::default()
+        };
+        let messages = vec![ChatCompletionMessage {
+            role: "user".to_string(),
+            content: Some(ChatCompletionContent::Content(vec![
+                text("call me at"),
+                image.clone(),
+                text("555-0100"),
+            ])),
+            ..Default::default()
+        }];
+
+        assert_eq!(
+            Noop.messages_to_text(&messages).unwrap(),
+            "call me at\n555-0100"
+        );
+
+        let rewritten = Noop.with_last_text(&messages, "call me at\n[REDACTED]".to_string());
+        assert_eq!(
+            rewritten[0].content,
+            Some(ChatCompletionContent::Content(vec![
+                text("call me at\n[REDACTED]"),
+                image,
+            ]))
+        );
     }
 }
@@ -1,12 +1,18 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::types::gateway::ChatCompletionMessage;
 use crate::types::http::response::GuardValidationError;
 use actix_web::{http, HttpResponse, ResponseError};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 
+pub mod cache;
 pub mod evaluator;
 pub mod partner;
+pub mod redaction;
 pub mod service;
+pub mod store;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GuardModel {
@@ -33,7 +39,16 @@ pub enum GuardError {
     OutputGuardrailsNotSupportedInStreaming,
 
     #[error("Guard '{0}' not passed")]
-    GuardNotPassed(String, GuardResult),
+    GuardNotPassed(String, Box<GuardResult>),
+
+    #[error("Guard '{0}' timed out")]
+    GuardTimeout(String),
+
+    #[error("Guard '{0}' does not use a managed dataset")]
+    DatasetNotManaged(String),
+
+    #[error("Dataset error: {0}")]
+    DatasetError(String),
 }
 
 impl ResponseError for GuardError {
@@ -45,6 +60,9 @@ impl ResponseError for GuardError {
             GuardError::GuardNotPassed(_, _) => {
                 crate::types::http::status::GuardValidationFailed::status_code()
             }
+            GuardError::GuardTimeout(_) => http::StatusCode::GATEWAY_TIMEOUT,
+            GuardError::DatasetNotManaged(_) => http::StatusCode::BAD_REQUEST,
+            GuardError::DatasetError(_) => http::StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
 
@@ -74,18 +92,40 @@ impl ResponseError for GuardError {
                 };
                 guard_error.error_response()
             }
+            GuardError::GuardTimeout(id) => {
+                HttpResponse::GatewayTimeout().json(serde_json::json!({
+                    "error": "Guard evaluation timed out",
+                    "guard_id": id
+                }))
+            }
+            GuardError::DatasetNotManaged(id) => {
+                HttpResponse::BadRequest().json(serde_json::json!({
+                    "error": "Guard does not use a managed dataset",
+                    "guard_id": id
+                }))
+            }
+            GuardError::DatasetError(message) => {
+                HttpResponse::InternalServerError().json(serde_json::json!({
+                    "error": "Dataset error",
+                    "message": message
+                }))
+            }
         }
     }
 }
 
 /// Enum representing when a guard should be applied
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[serde(rename_all = "lowercase")]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
 pub enum GuardStage {
     /// Applied to user messages before being sent to the LLM
     Input,
     /// Applied to LLM responses before being returned to the user
     Output,
+    /// Applied to the arguments of a tool call before the gateway runs the tool
+    ToolCall,
+    /// Applied to the output of a tool before it is sent back to the LLM
+    ToolResult,
 }
 
 /// Enum representing what action a guard should take
@@ -96,6 +136,43 @@ pub enum GuardAction {
     Observe,
     /// Validates and can block/fail a request
     Validate,
+    /// Substitutes the messages rewritten by the guard, still failing when the guard does
+    Transform,
+}
+
+/// What to do with a guard that did not finish within the guard timeout
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum GuardTimeoutPolicy {
+    /// Let the request through
+    FailOpen,
+    /// Reject the request
+    #[default]
+    FailClosed,
+}
+
+/// Request level options for evaluating the guards of a stage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GuardEvaluationOptions {
+    /// Return as soon as one guard fails instead of waiting for the others
+    #[serde(default = "default_fail_fast")]
+    pub fail_fast: bool,
+    /// Time budget for all guards of a stage, in milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_fail_fast() -> bool {
+    true
+}
+
+impl Default for GuardEvaluationOptions {
+    fn default() -> Self {
+        Self {
+            fail_fast: default_fail_fast(),
+            timeout_ms: None,
+        }
+    }
 }
 
 /// The result of a guard evaluation
@@ -117,6 +194,52 @@ pub enum GuardResult {
     },
     /// Structured JSON result
     Json { schema: Value, passed: bool },
+    /// Messages with detected entities replaced by placeholders
+    Redacted {
+        messages: Vec<ChatCompletionMessage>,
+        /// Number of redacted values per entity type
+        entities: BTreeMap<String, usize>,
+        passed: bool,
+        /// Placeholders to restore in the model output, never serialized
+        #[serde(skip)]
+        replacements: HashMap<String, String>,
+    },
+    /// Messages rewritten by the guard, e.g. masked or truncated text
+    Transformed {
+        messages: Vec<ChatCompletionMessage>,
+        passed: bool,
+    },
+}
+
+impl GuardResult {
+    pub fn passed(&self) -> bool {
+        match self {
+            GuardResult::Boolean { passed, .. }
+            | GuardResult::Text { passed, .. }
+            | GuardResult::Json { passed, .. }
+            | GuardResult::Redacted { passed, .. }
+            | GuardResult::Transformed { passed, .. } => *passed,
+        }
+    }
+
+    /// Messages to use instead of the evaluated ones, if the guard rewrote them
+    pub fn messages(&self) -> Option<&[ChatCompletionMessage]> {
+        match self {
+            GuardResult::Redacted { messages, .. } | GuardResult::Transformed { messages, .. } => {
+                Some(messages)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Changes made to the messages by the guards of one stage.
+#[derive(Debug, Clone, Default)]
+pub struct GuardOutcome {
+    /// Rewritten messages to use instead of the original ones
+    pub messages: Option<Vec<ChatCompletionMessage>>,
+    /// Placeholders to replace with their original values in the model output
+    pub replacements: HashMap<String, String>,
 }
 
 /// Base guard configuration shared by all guard types
@@ -132,6 +255,11 @@ pub struct GuardConfig {
     pub action: GuardAction,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub user_defined_parameters: Option<Value>,
+    #[serde(default)]
+    pub on_timeout: GuardTimeoutPolicy,
+    /// Tools guarded by a tool stage guard, all tools when not set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tools: Option<Vec<String>>,
 }
 /// The main Guard type that encompasses all guard types
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -169,9 +297,22 @@ pub enum Guard {
         #[serde(flatten)]
         config: GuardConfig,
     },
+    /// Guard evaluated by an external moderation provider
     Partner {
         #[serde(flatten)]
         config: GuardConfig,
+        #[serde(default)]
+        provider: partner::PartnerProvider,
+    },
+    /// PII guard running local recognizers, can redact the detected values
+    Pii {
+        #[serde(flatten)]
+        config: GuardConfig,
+    },
+    /// Credential leak guard, can mask the detected secrets
+    Secrets {
+        #[serde(flatten)]
+        config: GuardConfig,
     },
 }
 
@@ -179,16 +320,31 @@ pub enum Guard {
 #[serde(rename_all = "lowercase", untagged)]
 pub enum DatasetSource {
     /// A dataset of examples without labels
-    Examples {
-        examples: Vec<GuardExample>,
-    },
+    Examples { examples: Vec<GuardExample> },
     /// A dataset name that will be loaded from a source
-    Source {
-        source: String,
-    },
-    Managed {
-        config: serde_json::Value,
-    },
+    Source { source: String },
+    /// A dataset stored by the gateway, see [`ManagedDatasetConfig`]
+    Managed { config: serde_json::Value },
+}
+
+impl DatasetSource {
+    /// Config of a managed dataset, `None` for other sources
+    pub fn managed(&self) -> Option<Result<ManagedDatasetConfig, String>> {
+        match self {
+            DatasetSource::Managed { config } => Some(
+                serde_json::from_value(config.clone())
+                    .map_err(|e| format!("Invalid managed dataset config: {e}")),
+            ),
+            _ => None,
+        }
+    }
+}
+
+/// Config of a dataset stored by the gateway
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ManagedDatasetConfig {
+    /// Name of the dataset in the dataset store
+    pub name: String,
 }
 
 /// Example entry for dataset-based guard
@@ -215,6 +371,37 @@ pub trait DatasetLoader: Send + Sync {
     async fn load(&self, source: &str) -> Result<Vec<GuardExample>, String>;
 }
 
+/// Example of a managed dataset
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct StoredExample {
+    pub id: String,
+    #[serde(flatten)]
+    pub example: GuardExample,
+}
+
+/// Storage of managed datasets
+#[async_trait::async_trait]
+pub trait DatasetStore: Send + Sync {
+    async fn list(&self, dataset: &str) -> Result<Vec<StoredExample>, String>;
+
+    /// Adds examples to the dataset, returning them with their assigned ids
+    async fn add(
+        &self,
+        dataset: &str,
+        examples: Vec<GuardExample>,
+    ) -> Result<Vec<StoredExample>, String>;
+
+    /// Removes an example, returning whether it existed
+    async fn remove(&self, dataset: &str, id: &str) -> Result<bool, String>;
+
+    /// Saves embeddings computed for examples added without one, by example id
+    async fn set_embeddings(
+        &self,
+        dataset: &str,
+        embeddings: HashMap<String, Vec<f32>>,
+    ) -> Result<(), String>;
+}
+
 impl Guard {
     /// Returns the stage at which this guard should be applied
     pub fn stage(&self) -> &GuardStage {
@@ -225,6 +412,8 @@ impl Guard {
             Guard::WordCount { config } => &config.stage,
             Guard::Regex { config, .. } => &config.stage,
             Guard::Partner { config, .. } => &config.stage,
+            Guard::Pii { config } => &config.stage,
+            Guard::Secrets { config } => &config.stage,
         }
     }
 
@@ -237,6 +426,59 @@ impl Guard {
             Guard::Regex { config, .. } => &config.action,
             Guard::WordCount { config } => &config.action,
             Guard::Partner { config, .. } => &config.action,
+            Guard::Pii { config } => &config.action,
+            Guard::Secrets { config } => &config.action,
+        }
+    }
+
+    /// Returns what to do when this guard times out
+    pub fn on_timeout(&self) -> GuardTimeoutPolicy {
+        match self {
+            Guard::Schema { config, .. } => config.on_timeout,
+            Guard::LlmJudge { config, .. } => config.on_timeout,
+            Guard::Dataset { config, .. } => config.on_timeout,
+            Guard::Regex { config, .. } => config.on_timeout,
+            Guard::WordCount { config } => config.on_timeout,
+            Guard::Partner { config, .. } => config.on_timeout,
+            Guard::Pii { config } => config.on_timeout,
+            Guard::Secrets { config } => config.on_timeout,
+        }
+    }
+
+    /// Returns the tools this guard is restricted to
+    pub fn tools(&self) -> Option<&Vec<String>> {
+        match self {
+            Guard::Schema { config, .. } => config.tools.as_ref(),
+            Guard::LlmJudge { config, .. } => config.tools.as_ref(),
+            Guard::Dataset { config, .. } => config.tools.as_ref(),
+            Guard::Regex { config, .. } => config.tools.as_ref(),
+            Guard::WordCount { config } => config.tools.as_ref(),
+            Guard::Partner { config, .. } => config.tools.as_ref(),
+            Guard::Pii { config } => config.tools.as_ref(),
+            Guard::Secrets { config } => config.tools.as_ref(),
+        }
+    }
+
+    /// Whether this guard evaluates the messages of the given stage.
+    ///
+    /// Tool stage messages carry the evaluated call in `tool_calls`, guards restricted to
+    /// other tools are skipped. `tools` is ignored on the input and output stages.
+    pub fn applies_to(&self, stage: &GuardStage, messages: &[ChatCompletionMessage]) -> bool {
+        if stage != self.stage() {
+            return false;
+        }
+        if !matches!(stage, GuardStage::ToolCall | GuardStage::ToolResult) {
+            return true;
+        }
+
+        let tool_name = messages
+            .iter()
+            .rev()
+            .find_map(|m| m.tool_calls.as_ref()?.first())
+            .map(|call| &call.function.name);
+        match (self.tools(), tool_name) {
+            (Some(tools), Some(tool_name)) => tools.contains(tool_name),
+            _ => true,
         }
     }
 
@@ -249,6 +491,8 @@ impl Guard {
             Guard::Regex { config, .. } => &config.id,
             Guard::WordCount { config } => &config.id,
             Guard::Partner { config, .. } => &config.id,
+            Guard::Pii { config } => &config.id,
+            Guard::Secrets { config } => &config.id,
         }
     }
 
@@ -261,6 +505,8 @@ impl Guard {
             Guard::Regex { config, .. } => &config.name,
             Guard::WordCount { config } => &config.name,
             Guard::Partner { config, .. } => &config.name,
+            Guard::Pii { config } => &config.name,
+            Guard::Secrets { config } => &config.name,
         }
     }
     pub fn parameters(&self) -> Option<&Value> {
@@ -271,6 +517,8 @@ impl Guard {
             Guard::Regex { config, .. } => config.user_defined_parameters.as_ref(),
             Guard::WordCount { config } => config.user_defined_parameters.as_ref(),
             Guard::Partner { config, .. } => config.user_defined_parameters.as_ref(),
+            Guard::Pii { config } => config.user_defined_parameters.as_ref(),
+            Guard::Secrets { config } => config.user_defined_parameters.as_ref(),
         }
     }
     pub fn set_parameters(&mut self, parameters: Value) {
@@ -281,6 +529,8 @@ impl Guard {
             Guard::Regex { config, .. } => config.user_defined_parameters = Some(parameters),
             Guard::WordCount { config } => config.user_defined_parameters = Some(parameters),
             Guard::Partner { config, .. } => config.user_defined_parameters = Some(parameters),
+            Guard::Pii { config } => config.user_defined_parameters = Some(parameters),
+            Guard::Secrets { config } => config.user_defined_parameters = Some(parameters),
         }
     }
 
@@ -292,6 +542,8 @@ impl Guard {
             Guard::Regex { config, .. } => &config.template_id,
             Guard::WordCount { config } => &config.template_id,
             Guard::Partner { config, .. } => &config.template_id,
+            Guard::Pii { config } => &config.template_id,
+            Guard::Secrets { config } => &config.template_id,
         }
     }
 
@@ -303,6 +555,58 @@ impl Guard {
             Guard::Regex { .. } => "regex".to_string(),
             Guard::WordCount { .. } => "word_count".to_string(),
             Guard::Partner { .. } => "partner".to_string(),
+            Guard::Pii { .. } => "pii".to_string(),
+            Guard::Secrets { .. } => "secrets".to_string(),
         }
     }
 }
+
+/// Input guard of `guard_type` that validates, `fields` are added over these defaults.
+#[cfg(any(test, feature = "test-utils"))]
+pub fn guard_fixture(guard_type: &str, fields: Value) -> Guard {
+    let mut guard = serde_json::json!({
+        "type": guard_type,
+        "id": guard_type,
+        "name": guard_type,
+        "template_id": guard_type,
+        "stage": "input",
+        "action": "validate",
+    });
+    if let (Some(guard), Value::Object(fields)) = (guard.as_object_mut(), fields) {
+        guard.extend(fields);
+    }
+    serde_json::from_value(guard).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::gateway::{FunctionCall, ToolCall};
+
+    #[test]
+    fn test_tools_only_restrict_tool_stages() {
+        let message = ChatCompletionMessage {
+            tool_calls: Some(vec![ToolCall {
+                r#type: "function".to_string(),
+                function: FunctionCall {
+                    name: "search".to_string(),
+                    arguments: "{}".to_string(),
+                },
+                ..Default::default()
+            }]),
+            ..ChatCompletionMessage::new_text("assistant".to_string(), String::new())
+        };
+        let guard = |stage: &str| {
+            guard_fixture(
+                "regex",
+                serde_json::json!({"stage": stage, "tools": ["shell"], "parameters": {}}),
+            )
+        };
+
+        let messages = [message];
+        assert!(guard("output").applies_to(&GuardStage::Output, &messages));
+        assert!(guard("input").applies_to(&GuardStage::Input, &messages));
+        assert!(!guard("tool_call").applies_to(&GuardStage::ToolCall, &messages));
+        assert!(!guard("tool_result").applies_to(&GuardStage::ToolResult, &messages));
+    }
+}
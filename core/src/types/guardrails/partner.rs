@@ -1,7 +1,79 @@
+use std::collections::HashMap;
+
+use secrecy::{ExposeSecret, SecretString};
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::Value;
+
 use crate::model::error::AuthorizationError;
 use crate::types::gateway::ChatCompletionMessage;
 use crate::types::guardrails::GuardResult;
 
+/// Moderation vendor evaluating a partner guard
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum PartnerProvider {
+    /// OpenAI moderation API, using `LANGDB_OPENAI_API_KEY`
+    #[default]
+    Openai,
+    /// Any HTTP endpoint returning a JSON verdict
+    Webhook(WebhookPartnerConfig),
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookPartnerConfig {
+    pub url: String,
+    /// Usually carries credentials, redacted in `Debug` output
+    #[serde(default, serialize_with = "serialize_headers")]
+    pub headers: HashMap<String, SecretString>,
+    /// JSON body sent to the webhook. String values equal to `{{messages}}` or `{{text}}`
+    /// are replaced by the messages or the text of the last message.
+    #[serde(default = "default_request_template")]
+    pub request_template: Value,
+    pub response_mapping: WebhookResponseMapping,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+fn default_request_template() -> Value {
+    serde_json::json!({ "messages": "{{messages}}" })
+}
+
+// Written out as they are so that configs round-trip and reloads notice rotated credentials
+fn serialize_headers<S: Serializer>(
+    headers: &HashMap<String, SecretString>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.collect_map(
+        headers
+            .iter()
+            .map(|(name, value)| (name, value.expose_secret())),
+    )
+}
+
+impl WebhookPartnerConfig {
+    /// Headers sent with every request, with their values exposed.
+    pub fn exposed_headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.expose_secret()))
+    }
+}
+
+/// JSONPath expressions (dot and index notation, e.g. `$.results[0].flagged`) locating the
+/// verdict in the webhook response.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookResponseMapping {
+    /// Boolean verdict of the webhook
+    pub passed: String,
+    /// Whether the verdict is true for flagged content rather than for passing content
+    #[serde(default)]
+    pub inverted: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub confidence: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum GuardPartnerError {
     #[error("Invalid API key")]
@@ -26,3 +98,28 @@ pub trait GuardPartner {
         messages: &[ChatCompletionMessage],
     ) -> Result<GuardResult, GuardPartnerError>;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_headers_are_redacted() {
+        let value = serde_json::json!({
+            "url": "https://moderation.example.com",
+            "headers": {"Authorization": "Bearer secret"},
+            "response_mapping": {"passed": "$.passed"},
+        });
+        let config: WebhookPartnerConfig = serde_json::from_value(value.clone()).unwrap();
+
+        assert!(!format!("{config:?}").contains("secret"));
+        assert_eq!(
+            config.exposed_headers().collect::<Vec<_>>(),
+            vec![("Authorization", "Bearer secret")]
+        );
+        assert_eq!(
+            serde_json::to_value(&config).unwrap()["headers"],
+            value["headers"]
+        );
+    }
+}
@@ -0,0 +1,116 @@
+use std::collections::HashMap;
+
+use crate::types::gateway::{ChatCompletionContent, ChatCompletionMessage};
+
+/// Placeholder substituted for the `index`th distinct entity of a type, e.g. `<EMAIL_1>`.
+pub fn placeholder(entity: &str, index: usize) -> String {
+    format!("<{}_{index}>", entity.to_uppercase())
+}
+
+/// Replaces placeholders with the values they stand for.
+pub fn restore_text(text: &str, replacements: &HashMap<String, String>) -> String {
+    if !text.contains('<') {
+        return text.to_string();
+    }
+
+    replacements
+        .iter()
+        .fold(text.to_string(), |text, (placeholder, original)| {
+            text.replace(placeholder, original)
+        })
+}
+
+pub fn restore_message(
+    message: &mut ChatCompletionMessage,
+    replacements: &HashMap<String, String>,
+) {
+    if replacements.is_empty() {
+        return;
+    }
+
+    match message.content.as_mut() {
+        Some(ChatCompletionContent::Text(text)) => *text = restore_text(text, replacements),
+        Some(ChatCompletionContent::Content(parts)) => {
+            for part in parts {
+                if let Some(text) = part.text.as_mut() {
+                    *text = restore_text(text, replacements);
+                }
+            }
+        }
+        None => {}
+    }
+}
+
+/// Restores placeholders in streamed content, holding back chunks that end inside a placeholder.
+pub struct StreamRestorer {
+    replacements: HashMap<String, String>,
+    max_placeholder_len: usize,
+    buffer: String,
+}
+
+impl StreamRestorer {
+    pub fn new(replacements: HashMap<String, String>) -> Self {
+        let max_placeholder_len = replacements.keys().map(|k| k.len()).max().unwrap_or(0);
+        Self {
+            replacements,
+            max_placeholder_len,
+            buffer: String::new(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty()
+    }
+
+    /// Returns the restored content that can be emitted after this chunk.
+    pub fn push(&mut self, chunk: &str) -> String {
+        self.buffer.push_str(chunk);
+        let text = restore_text(&self.buffer, &self.replacements);
+
+        // Keep a trailing `<...` that may still become a placeholder
+        let pending = text
+            .rfind('<')
+            .filter(|start| !text[*start..].contains('>'))
+            .filter(|start| text.len() - start < self.max_placeholder_len);
+
+        match pending {
+            Some(start) => {
+                self.buffer = text[start..].to_string();
+                text[..start].to_string()
+            }
+            None => {
+                self.buffer.clear();
+                text
+            }
+        }
+    }
+
+    pub fn finish(&mut self) -> String {
+        std::mem::take(&mut self.buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stream_restorer() {
+        let replacements = HashMap::from([
+            (placeholder("email", 1), "jane@example.com".to_string()),
+            (placeholder("phone", 1), "+1 555 0100".to_string()),
+        ]);
+        let mut restorer = StreamRestorer::new(replacements);
+
+        let mut output = String::new();
+        for chunk in ["Write to <EM", "AIL_1> or call <", "PHONE_1>", " now <3"] {
+            output.push_str(&restorer.push(chunk));
+        }
+        output.push_str(&restorer.finish());
+
+        assert_eq!(
+            output,
+            "Write to jane@example.com or call +1 555 0100 now <3"
+        );
+    }
+}
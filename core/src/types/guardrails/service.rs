@@ -1,8 +1,11 @@
+use std::collections::HashMap;
+
 use crate::executor::context::ExecutorContext;
 use crate::types::gateway::ChatCompletionMessage;
 use crate::types::guardrails::GuardResult;
+use crate::types::reloadable::Reloadable;
 
-use super::GuardStage;
+use super::{Guard, GuardStage, GuardTemplate, GuardTimeoutPolicy};
 
 /// Trait for evaluating text against a guard
 #[async_trait::async_trait]
@@ -15,4 +18,54 @@ pub trait GuardrailsEvaluator: Send + Sync {
         parameters: Option<&serde_json::Value>,
         guard_stage: &GuardStage,
     ) -> Result<GuardResult, String>;
+
+    /// What to do when the guard does not finish within the guard timeout
+    fn timeout_policy(&self, _guard_id: &str) -> GuardTimeoutPolicy {
+        GuardTimeoutPolicy::default()
+    }
+
+    /// Configured guards, keyed by id
+    fn guards(&self) -> HashMap<String, Guard> {
+        HashMap::new()
+    }
+
+    /// Guard templates, keyed by template id
+    fn templates(&self) -> HashMap<String, GuardTemplate> {
+        HashMap::new()
+    }
+}
+
+/// Evaluates with the guards current when the evaluation starts.
+#[async_trait::async_trait]
+impl<T: GuardrailsEvaluator + 'static> GuardrailsEvaluator for Reloadable<T> {
+    async fn evaluate(
+        &self,
+        messages: &[ChatCompletionMessage],
+        guard_id: &str,
+        executor_context: &ExecutorContext,
+        parameters: Option<&serde_json::Value>,
+        guard_stage: &GuardStage,
+    ) -> Result<GuardResult, String> {
+        self.load()
+            .evaluate(
+                messages,
+                guard_id,
+                executor_context,
+                parameters,
+                guard_stage,
+            )
+            .await
+    }
+
+    fn timeout_policy(&self, guard_id: &str) -> GuardTimeoutPolicy {
+        self.load().timeout_policy(guard_id)
+    }
+
+    fn guards(&self) -> HashMap<String, Guard> {
+        self.load().guards()
+    }
+
+    fn templates(&self) -> HashMap<String, GuardTemplate> {
+        self.load().templates()
+    }
 }
@@ -11,6 +11,7 @@ pub mod image;
 pub mod json;
 pub mod message;
 pub mod provider;
+pub mod reloadable;
 pub mod threads;
 
 pub const LANGDB_API_URL: &str = "https://api.us-east-1.langdb.ai/v1";
@@ -0,0 +1,45 @@
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+
+/// Shared value that can be replaced while the gateway is running, e.g. on config reload.
+///
+/// Clones share the value. Readers get the value current at the time of the call, so
+/// in-flight requests keep using the value they started with.
+pub struct Reloadable<T>(Arc<RwLock<Arc<T>>>);
+
+impl<T> Clone for Reloadable<T> {
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T> Reloadable<T> {
+    pub fn new(value: T) -> Self {
+        Self(Arc::new(RwLock::new(Arc::new(value))))
+    }
+
+    pub fn load(&self) -> Arc<T> {
+        self.0.read().clone()
+    }
+
+    pub fn store(&self, value: T) {
+        *self.0.write() = Arc::new(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reloadable_store() {
+        let handle = Reloadable::new(1);
+        let shared = handle.clone();
+        let before = handle.load();
+
+        shared.store(2);
+        assert_eq!(*handle.load(), 2);
+        assert_eq!(*before, 1);
+    }
+}
@@ -40,6 +40,10 @@ pub struct Message {
     pub r#type: MessageType, // Human / AI Message
     pub tool_call_id: Option<String>,
     pub tool_calls: Option<Vec<ToolCall>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_signature: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
@@ -76,6 +80,10 @@ impl<'de> Deserialize<'de> for Message {
             r#type: MessageType,
             tool_call_id: Option<String>,
             tool_calls: Option<serde_json::Value>,
+            #[serde(default)]
+            reasoning_content: Option<String>,
+            #[serde(default)]
+            reasoning_signature: Option<String>,
         }
 
         let helper = Helper::deserialize(deserializer)?;
@@ -96,6 +104,8 @@ impl<'de> Deserialize<'de> for Message {
             r#type: helper.r#type,
             tool_call_id: helper.tool_call_id,
             tool_calls: tool_calls.and_then(|v| serde_json::from_value(v).ok()),
+            reasoning_content: helper.reasoning_content,
+            reasoning_signature: helper.reasoning_signature,
         })
     }
 }
@@ -196,6 +206,7 @@ pub enum MessageContentType {
     Text,
     ImageUrl,
     InputAudio,
+    File,
 }
 
 impl Display for MessageContentType {
@@ -204,6 +215,7 @@ impl Display for MessageContentType {
             MessageContentType::Text => f.write_str("Text"),
             MessageContentType::ImageUrl => f.write_str("ImageUrl"),
             MessageContentType::InputAudio => f.write_str("InputAudio"),
+            MessageContentType::File => f.write_str("File"),
         }
     }
 }
@@ -220,6 +232,15 @@ pub enum MessageContentValue {
 pub enum MessageContentPartOptions {
     Image(ImageDetail),
     Audio(AudioDetail),
+    File(FileDetail),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct FileDetail {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filename: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
@@ -239,6 +260,13 @@ impl MessageContentPartOptions {
             _ => None,
         }
     }
+
+    pub fn as_file(&self) -> Option<&FileDetail> {
+        match self {
+            MessageContentPartOptions::File(file) => Some(file),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
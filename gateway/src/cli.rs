@@ -12,6 +12,18 @@ pub struct Cli {
     pub command: Option<Commands>,
 }
 
+impl Cli {
+    /// Whether the command prints JSON to stdout
+    pub fn json_output(&self) -> bool {
+        matches!(
+            &self.command,
+            Some(Commands::Guards {
+                command: GuardsCommands::Eval(GuardEvalArgs { json: true, .. })
+            })
+        )
+    }
+}
+
 #[derive(Debug, Clone, Parser, Default)]
 pub struct ServeArgs {
     /// Host address to bind to (e.g., 127.0.0.1 for local or 0.0.0.0 for all interfaces)
@@ -57,6 +69,10 @@ pub struct ServeArgs {
     /// Start server in interactive mode with TUI interface
     #[arg(short, long)]
     pub interactive: bool,
+
+    /// Do not reload guards, providers and limits when the config file changes or on SIGHUP
+    #[arg(long)]
+    pub no_reload: bool,
 }
 
 #[derive(Subcommand)]
@@ -73,4 +89,33 @@ pub enum Commands {
     Serve(ServeArgs),
     /// Login to the API server
     Login,
+    /// Manage and benchmark guards
+    Guards {
+        #[command(subcommand)]
+        command: GuardsCommands,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum GuardsCommands {
+    /// Measure a configured guard against a JSONL file of labelled examples
+    Eval(GuardEvalArgs),
+}
+
+#[derive(Debug, Clone, Parser)]
+pub struct GuardEvalArgs {
+    /// Id of a guard from the config file
+    pub guard_id: String,
+
+    /// JSONL file with one {"text": ..., "label": ...} example per line, label true means allowed
+    #[arg(short, long, value_name = "PATH")]
+    pub dataset: String,
+
+    /// Number of examples evaluated at the same time
+    #[arg(long, default_value_t = 4)]
+    pub concurrency: usize,
+
+    /// Print the report as JSON
+    #[arg(long)]
+    pub json: bool,
 }
@@ -1,13 +1,19 @@
 use crate::cli;
 use crate::session::Credentials;
+use langdb_core::database::clickhouse::ClickhouseHttp;
+use langdb_core::database::DatabaseTransportClone;
 use langdb_core::executor::ProvidersConfig;
 use langdb_core::handler::middleware::rate_limit::RateLimiting;
+use langdb_core::model::images::ImageOptions;
+use langdb_core::models::ModelAliases;
 use langdb_core::types::credentials::ApiKeyCredentials;
-use langdb_core::types::guardrails::Guard;
+use langdb_core::types::guardrails::store::{ClickhouseDatasetStore, DirectoryDatasetStore};
+use langdb_core::types::guardrails::{DatasetStore, Guard};
 use minijinja::Environment;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::Path;
+use std::sync::Arc;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -16,6 +22,14 @@ pub enum ConfigError {
     ParseError(#[from] serde_yaml::Error),
     #[error("Failed to read template in config. Error: {0}")]
     ReadError(#[from] minijinja::Error),
+    #[error("Invalid model aliases in config. Error: {0}")]
+    InvalidModelAliases(String),
+    #[error("Invalid guards in config. Error: {0}")]
+    InvalidGuards(String),
+    #[error("Failed to read config file. Error: {0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Invalid datasets in config. Error: {0}")]
+    InvalidDatasets(String),
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -31,6 +45,38 @@ pub struct ClickhouseConfig {
     pub url: String,
 }
 
+/// Storage of the managed datasets of dataset guards
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "storage", rename_all = "snake_case")]
+pub enum DatasetsConfig {
+    /// One JSONL file per dataset
+    Directory {
+        #[serde(default = "default_datasets_path")]
+        path: String,
+    },
+    /// Rows of a table in the configured `clickhouse` database
+    Clickhouse {
+        #[serde(default = "default_datasets_table")]
+        table: String,
+    },
+}
+
+fn default_datasets_path() -> String {
+    "datasets".to_string()
+}
+
+fn default_datasets_table() -> String {
+    "langdb.guard_examples".to_string()
+}
+
+impl Default for DatasetsConfig {
+    fn default() -> Self {
+        Self::Directory {
+            path: default_datasets_path(),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct Config {
     #[serde(default)]
@@ -45,6 +91,40 @@ pub struct Config {
     pub providers: Option<ProvidersConfig>,
     #[serde(default)]
     pub guards: Option<HashMap<String, Guard>>,
+    #[serde(default)]
+    pub guard_cache: GuardCacheConfig,
+    #[serde(default)]
+    pub datasets: DatasetsConfig,
+    #[serde(default)]
+    pub images: Option<ImageOptions>,
+    #[serde(flatten)]
+    pub model_aliases: ModelAliases,
+}
+
+/// Cache of guard results, a capacity of 0 disables it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct GuardCacheConfig {
+    #[serde(default = "default_guard_cache_capacity")]
+    pub capacity: usize,
+    #[serde(default = "default_guard_cache_ttl_secs")]
+    pub ttl_secs: u64,
+}
+
+fn default_guard_cache_capacity() -> usize {
+    1024
+}
+
+fn default_guard_cache_ttl_secs() -> u64 {
+    600
+}
+
+impl Default for GuardCacheConfig {
+    fn default() -> Self {
+        Self {
+            capacity: default_guard_cache_capacity(),
+            ttl_secs: default_guard_cache_ttl_secs(),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, Default)]
@@ -83,10 +163,7 @@ impl Config {
     pub fn load<P: AsRef<Path>>(config_path: P) -> Result<Self, ConfigError> {
         tracing::info!("Loading config from: {}", config_path.as_ref().display());
         match std::fs::read_to_string(config_path) {
-            Ok(content) => {
-                let content = replace_env_vars(content)?;
-                Ok(serde_yaml::from_str(&content)?)
-            }
+            Ok(content) => Self::parse(content),
             Err(e) => {
                 tracing::warn!("Failed to read config: {}. Using default config.", e);
                 Ok(Self::default())
@@ -94,6 +171,34 @@ impl Config {
         }
     }
 
+    /// Parses the content of a config file, substituting environment variables.
+    pub fn parse(content: String) -> Result<Self, ConfigError> {
+        let content = replace_env_vars(content)?;
+        let config: Self = serde_yaml::from_str(&content)?;
+        config
+            .model_aliases
+            .validate()
+            .map_err(ConfigError::InvalidModelAliases)?;
+        Ok(config)
+    }
+
+    pub fn dataset_store(&self) -> Result<Arc<dyn DatasetStore>, ConfigError> {
+        match &self.datasets {
+            DatasetsConfig::Directory { path } => Ok(Arc::new(DirectoryDatasetStore::new(path))),
+            DatasetsConfig::Clickhouse { table } => {
+                let clickhouse = self.clickhouse.as_ref().ok_or_else(|| {
+                    ConfigError::InvalidDatasets(
+                        "clickhouse storage requires the clickhouse url".to_string(),
+                    )
+                })?;
+                Ok(Arc::new(ClickhouseDatasetStore::new(
+                    ClickhouseHttp::root().with_url(&clickhouse.url).clone_box(),
+                    table.clone(),
+                )))
+            }
+        }
+    }
+
     pub fn apply_cli_overrides(mut self, cli_opts: &cli::Commands) -> Self {
         if let cli::Commands::Serve(args) = cli_opts {
             // Apply REST config overrides
@@ -160,14 +265,14 @@ pub fn load_langdb_proxy_config(config: Option<ProvidersConfig>) -> Option<Provi
             if !providers_config.0.contains_key("langdb_proxy") {
                 providers_config.0.insert(
                     "langdb_proxy".to_string(),
-                    ApiKeyCredentials { api_key: key },
+                    ApiKeyCredentials { api_key: key }.into(),
                 );
             }
             Some(providers_config)
         } else {
             Some(ProvidersConfig(HashMap::from([(
                 "langdb_proxy".to_string(),
-                ApiKeyCredentials { api_key: key },
+                ApiKeyCredentials { api_key: key }.into(),
             )])))
         }
     } else {
@@ -1,7 +1,9 @@
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex, PoisonError};
 
 use langdb_core::executor::chat_completion::resolve_model_instance;
 use langdb_core::executor::context::ExecutorContext;
+use langdb_core::executor::embeddings::embed_texts;
 use langdb_core::model::ModelInstance;
 use langdb_core::routing::RoutingStrategy;
 use langdb_core::types::engine::ModelTools;
@@ -9,23 +11,33 @@ use langdb_core::types::gateway::ChatCompletionMessage;
 use langdb_core::types::gateway::ChatCompletionRequest;
 use langdb_core::types::gateway::ChatCompletionRequestWithTools;
 use langdb_core::types::gateway::DynamicRouter;
+use langdb_core::types::guardrails::cache::GuardResultCache;
 use langdb_core::types::guardrails::evaluator::Evaluator;
+use langdb_core::types::guardrails::partner::{
+    GuardPartner, PartnerProvider, WebhookPartnerConfig,
+};
 use langdb_core::types::guardrails::service::GuardrailsEvaluator;
+use langdb_core::types::guardrails::DatasetStore;
 use langdb_core::types::guardrails::Guard;
 use langdb_core::types::guardrails::GuardAction;
 use langdb_core::types::guardrails::GuardResult;
 use langdb_core::types::guardrails::GuardStage;
 use langdb_core::types::guardrails::GuardTemplate;
+use langdb_core::types::guardrails::GuardTimeoutPolicy;
 use langdb_guardrails::guards::config::load_guard_templates;
 use langdb_guardrails::guards::llm_judge::GuardModelInstanceFactory;
 use langdb_guardrails::guards::partner::PartnerEvaluator;
 use langdb_guardrails::guards::partners::openai::OpenaiGuardrailPartner;
+use langdb_guardrails::guards::partners::webhook::WebhookGuardrailPartner;
 use langdb_guardrails::guards::traced::TracedGuard;
 use langdb_guardrails::guards::DatasetEvaluator;
 use langdb_guardrails::guards::FileDatasetLoader;
+use langdb_guardrails::guards::GuardEmbedder;
 use langdb_guardrails::guards::LlmJudgeEvaluator;
+use langdb_guardrails::guards::PiiEvaluator;
 use langdb_guardrails::guards::RegexEvaluator;
 use langdb_guardrails::guards::SchemaEvaluator;
+use langdb_guardrails::guards::SecretsEvaluator;
 use langdb_guardrails::guards::WordCountEvaluator;
 use serde_json::{Map, Value};
 use tracing::Span;
@@ -78,9 +90,32 @@ impl GuardModelInstanceFactory for GuardModelFactory {
     }
 }
 
+pub struct GuardEmbeddingFactory {
+    executor_context: ExecutorContext,
+}
+
+impl GuardEmbeddingFactory {
+    pub fn new(executor_context: ExecutorContext) -> Self {
+        Self { executor_context }
+    }
+}
+
+#[async_trait::async_trait]
+impl GuardEmbedder for GuardEmbeddingFactory {
+    async fn embed(&self, model: &str, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String> {
+        embed_texts(&self.executor_context, model, texts)
+            .await
+            .map_err(|e| e.to_string())
+    }
+}
+
 pub struct GuardrailsService {
     guards: HashMap<String, Guard>,
     templates: HashMap<String, GuardTemplate>,
+    cache: GuardResultCache,
+    dataset_store: Option<Arc<dyn DatasetStore>>,
+    /// Clients of webhook guards by guard id, so connections are reused across evaluations
+    webhook_clients: Mutex<HashMap<String, reqwest::Client>>,
 }
 
 // Implement Send + Sync since all fields are Send + Sync
@@ -89,7 +124,58 @@ unsafe impl Send for GuardrailsService {}
 impl GuardrailsService {
     pub fn new(guards: HashMap<String, Guard>) -> Self {
         let templates = load_guard_templates().unwrap_or_default();
-        Self { guards, templates }
+        Self {
+            guards,
+            templates,
+            cache: GuardResultCache::default(),
+            dataset_store: None,
+            webhook_clients: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Checks that every guard refers to a known template.
+    pub fn validate(&self) -> Result<(), String> {
+        match self
+            .guards
+            .values()
+            .find(|guard| !self.templates.contains_key(guard.termplate_id()))
+        {
+            Some(guard) => Err(format!(
+                "Guard {} refers to unknown template {}",
+                guard.id(),
+                guard.termplate_id()
+            )),
+            None => Ok(()),
+        }
+    }
+
+    pub fn with_cache(mut self, cache: GuardResultCache) -> Self {
+        self.cache = cache;
+        self
+    }
+
+    /// Store of the managed datasets of dataset guards
+    pub fn with_dataset_store(mut self, dataset_store: Arc<dyn DatasetStore>) -> Self {
+        self.dataset_store = Some(dataset_store);
+        self
+    }
+
+    fn webhook_client(
+        &self,
+        guard_id: &str,
+        config: &WebhookPartnerConfig,
+    ) -> Result<reqwest::Client, String> {
+        let mut clients = self
+            .webhook_clients
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if let Some(client) = clients.get(guard_id) {
+            return Ok(client.clone());
+        }
+
+        let client = WebhookGuardrailPartner::client(config).map_err(|e| e.to_string())?;
+        clients.insert(guard_id.to_string(), client.clone());
+        Ok(client)
     }
 
     fn get_evaluator(
@@ -108,12 +194,30 @@ impl GuardrailsService {
             }
             Guard::Dataset { .. } => Box::new(DatasetEvaluator {
                 loader: Box::new(FileDatasetLoader {}),
+                store: self.dataset_store.clone(),
+                embedder: Some(Box::new(GuardEmbeddingFactory::new(
+                    executor_context.clone(),
+                ))),
             }) as Box<dyn Evaluator>,
             Guard::Regex { .. } => Box::new(RegexEvaluator {}) as Box<dyn Evaluator>,
             Guard::WordCount { .. } => Box::new(WordCountEvaluator {}) as Box<dyn Evaluator>,
-            Guard::Partner { .. } => Box::new(PartnerEvaluator::new(Box::new(
-                OpenaiGuardrailPartner::new(None).map_err(|e| e.to_string())?,
-            ))) as Box<dyn Evaluator>,
+            Guard::Pii { .. } => Box::new(PiiEvaluator {}) as Box<dyn Evaluator>,
+            Guard::Secrets { .. } => Box::new(SecretsEvaluator {}) as Box<dyn Evaluator>,
+            Guard::Partner { provider, .. } => {
+                let partner = match provider {
+                    PartnerProvider::Openai => {
+                        Box::new(OpenaiGuardrailPartner::new(None).map_err(|e| e.to_string())?)
+                            as Box<dyn GuardPartner + Send + Sync>
+                    }
+                    PartnerProvider::Webhook(config) => {
+                        Box::new(WebhookGuardrailPartner::with_client(
+                            config.clone(),
+                            self.webhook_client(guard.id(), config)?,
+                        )) as Box<dyn GuardPartner + Send + Sync>
+                    }
+                };
+                Box::new(PartnerEvaluator::new(partner)) as Box<dyn Evaluator>
+            }
         };
 
         Ok(TracedGuard::new(evaluator))
@@ -136,7 +240,7 @@ impl GuardrailsEvaluator for GuardrailsService {
             .ok_or("Guard not found".to_string())
             .cloned()?;
 
-        if stage != guard.stage() {
+        if !guard.applies_to(stage, messages) {
             return Ok(GuardResult::Boolean {
                 passed: true,
                 confidence: None,
@@ -191,15 +295,48 @@ impl GuardrailsEvaluator for GuardrailsService {
 
         guard.set_parameters(Value::Object(final_params));
 
-        let evaluator = self.get_evaluator(&guard, executor_context)?;
-        let result = evaluator.evaluate(messages, &guard).await?;
+        // Examples of managed datasets change through the API or other gateway
+        // instances, so their verdicts are not cached
+        let cache_key = match &guard {
+            Guard::Dataset { dataset, .. } if dataset.managed().is_some() => None,
+            _ => Some(GuardResultCache::key(&guard, stage, messages)),
+        };
+        let result = match cache_key.and_then(|key| self.cache.get(key)) {
+            Some(result) => {
+                tracing::debug!("Using cached result of guard {guard_id}");
+                result
+            }
+            None => {
+                let evaluator = self.get_evaluator(&guard, executor_context)?;
+                let result = evaluator.evaluate(messages, &guard).await?;
+                if let Some(key) = cache_key {
+                    self.cache.insert(key, result.clone());
+                }
+                result
+            }
+        };
 
         match guard.action() {
-            GuardAction::Validate => Ok(result),
+            GuardAction::Validate | GuardAction::Transform => Ok(result),
             GuardAction::Observe => Ok(GuardResult::Boolean {
                 passed: true,
                 confidence: None,
             }),
         }
     }
+
+    fn timeout_policy(&self, guard_id: &str) -> GuardTimeoutPolicy {
+        self.guards
+            .get(guard_id)
+            .map(|guard| guard.on_timeout())
+            .unwrap_or_default()
+    }
+
+    fn guards(&self) -> HashMap<String, Guard> {
+        self.guards.clone()
+    }
+
+    fn templates(&self) -> HashMap<String, GuardTemplate> {
+        self.templates.clone()
+    }
 }
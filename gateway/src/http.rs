@@ -1,10 +1,9 @@
 use crate::callback_handler::init_callback_handler;
-use crate::config::{load_langdb_proxy_config, Config};
+use crate::config::{Config, ConfigError};
 use crate::cost::GatewayCostCalculator;
-use crate::guardrails::GuardrailsService;
-use crate::limit::GatewayLimitChecker;
 use crate::middleware::trace_logger::TraceLogger;
 use crate::otel::DummyTraceWritterTransport;
+use crate::reload::{ConfigSource, ConfigWatcher, ReloadableConfig};
 use actix_cors::Cors;
 use actix_web::Scope as ActixScope;
 use actix_web::{
@@ -16,14 +15,18 @@ use actix_web::{
 use futures::{future::try_join, Future, TryFutureExt};
 use langdb_core::database::clickhouse::ClickhouseHttp;
 use langdb_core::database::DatabaseTransportClone;
-use langdb_core::executor::ProvidersConfig;
 use langdb_core::handler::chat::create_chat_completion;
 use langdb_core::handler::embedding::embeddings_handler;
+use langdb_core::handler::guards::{
+    add_examples, evaluate_guard, list_examples, list_guards, remove_example,
+};
 use langdb_core::handler::image::create_image;
-use langdb_core::handler::middleware::rate_limit::{RateLimitMiddleware, RateLimiting};
+use langdb_core::handler::middleware::rate_limit::RateLimitMiddleware;
 use langdb_core::handler::models::list_gateway_models;
-use langdb_core::handler::{AvailableModels, CallbackHandlerFn, LimitCheckWrapper};
-use langdb_core::models::ModelMetadata;
+use langdb_core::handler::traces::stream_traces;
+use langdb_core::handler::{AvailableModels, CallbackHandlerFn};
+use langdb_core::model::images::ImageOptions;
+use langdb_core::models::{ModelAliases, ModelMetadata};
 use langdb_core::telemetry::database::DatabaseSpanWritter;
 use langdb_core::telemetry::DummyTraceTenantResolver;
 use langdb_core::telemetry::ProjectTraceMap;
@@ -31,10 +34,8 @@ use langdb_core::telemetry::SpanWriterTransport;
 use langdb_core::telemetry::{TraceServiceImpl, TraceServiceServer};
 use langdb_core::types::gateway::CostCalculator;
 use langdb_core::types::guardrails::service::GuardrailsEvaluator;
-use langdb_core::types::guardrails::Guard;
 use langdb_core::usage::InMemoryStorage;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
 use std::sync::Arc;
 use thiserror::Error;
 use tokio::signal;
@@ -55,16 +56,28 @@ pub enum ServerError {
     Tonic(#[from] tonic::transport::Error),
     #[error(transparent)]
     AddrParseError(#[from] std::net::AddrParseError),
+    #[error(transparent)]
+    Config(#[from] ConfigError),
 }
 
 #[derive(Clone, Debug)]
 pub struct ApiServer {
     config: Config,
+    config_source: Option<ConfigSource>,
 }
 
 impl ApiServer {
     pub fn new(config: Config) -> Self {
-        Self { config }
+        Self {
+            config,
+            config_source: None,
+        }
+    }
+
+    /// Reload the config from this source when it changes
+    pub fn with_config_source(mut self, config_source: Option<ConfigSource>) -> Self {
+        self.config_source = config_source;
+        self
     }
 
     pub fn print_useful_info(&self) {
@@ -119,34 +132,28 @@ impl ApiServer {
             CallbackHandlerFn(None)
         };
 
-        let server = HttpServer::new(move || {
-            let limit_checker = if let Some(storage) = storage.clone() {
-                match &server_config.config.cost_control {
-                    Some(cc) => {
-                        let checker = GatewayLimitChecker::new(storage, cc.clone());
-                        Some(LimitCheckWrapper {
-                            checkers: vec![Arc::new(Mutex::new(checker))],
-                        })
-                    }
-                    None => None,
-                }
-            } else {
-                None
-            };
-
-            let providers_config = load_langdb_proxy_config(server_config.config.providers.clone());
+        let project_trace_senders = Arc::new(ProjectTraceMap::new());
+        let trace_senders = project_trace_senders.clone();
 
+        // Shared by all workers so a reload applies to every one of them
+        let reloadable =
+            ReloadableConfig::new(&self.config, storage.clone(), self.config.dataset_store()?);
+        if let Some(source) = self.config_source.clone() {
+            ConfigWatcher::new(source, self.config.clone(), reloadable.clone()).spawn();
+        }
+
+        let server = HttpServer::new(move || {
             let cors = Self::get_cors(CorsOptions::Permissive);
             Self::create_app_entry(
                 cors,
                 storage.clone(),
                 models.clone(),
-                server_config.config.guards.clone(),
+                reloadable.clone(),
                 callback.clone(),
                 cost_calculator.clone(),
-                limit_checker.clone(),
-                server_config.config.rate_limit.clone(),
-                providers_config,
+                server_config.config.model_aliases.clone(),
+                server_config.config.images.clone(),
+                trace_senders.clone(),
             )
         })
         .bind((self.config.http.host.as_str(), self.config.http.port))?
@@ -162,7 +169,7 @@ impl ApiServer {
         };
 
         let trace_service = TraceServiceServer::new(TraceServiceImpl::new(
-            Arc::new(ProjectTraceMap::new()),
+            project_trace_senders,
             writer,
             Box::new(DummyTraceTenantResolver),
         ));
@@ -185,12 +192,12 @@ impl ApiServer {
         cors: Cors,
         in_memory_storage: Option<Arc<Mutex<InMemoryStorage>>>,
         models: Vec<ModelMetadata>,
-        guards: Option<HashMap<String, Guard>>,
+        reloadable: ReloadableConfig,
         callback: CallbackHandlerFn,
         cost_calculator: GatewayCostCalculator,
-        limit_checker: Option<LimitCheckWrapper>,
-        rate_limit: Option<RateLimiting>,
-        providers: Option<ProvidersConfig>,
+        model_aliases: ModelAliases,
+        images: Option<ImageOptions>,
+        project_trace_senders: Arc<ProjectTraceMap>,
     ) -> App<
         impl ServiceFactory<
             ServiceRequest,
@@ -207,23 +214,29 @@ impl ApiServer {
             service = service.app_data(in_memory_storage);
         }
 
-        if let Some(providers) = &providers {
-            service = service.app_data(providers.clone());
+        if !model_aliases.is_empty() {
+            service = service.app_data(model_aliases);
         }
 
-        let guardrails_service = Box::new(GuardrailsService::new(guards.unwrap_or_default()))
-            as Box<dyn GuardrailsEvaluator>;
+        if let Some(images) = images {
+            service = service.app_data(images);
+        }
+
+        let guardrails_service = Box::new(reloadable.guardrails) as Box<dyn GuardrailsEvaluator>;
         app.wrap(TraceLogger)
             .service(
                 service
-                    .app_data(limit_checker)
+                    .app_data(reloadable.providers)
+                    .app_data(reloadable.limit_checker)
+                    .app_data(reloadable.rate_limit)
                     .app_data(Data::new(callback))
                     .app_data(Data::new(AvailableModels(models)))
                     .app_data(Data::new(
                         Box::new(cost_calculator) as Box<dyn CostCalculator>
                     ))
-                    .app_data(rate_limit)
                     .app_data(Data::new(guardrails_service))
+                    .app_data(Data::new(reloadable.dataset_store))
+                    .app_data(Data::new(project_trace_senders))
                     .wrap(RateLimitMiddleware),
             )
             .wrap(cors)
@@ -243,7 +256,16 @@ impl ApiServer {
         scope
             .route("/chat/completions", web::post().to(create_chat_completion))
             .route("/models", web::get().to(list_gateway_models))
+            .route("/guards", web::get().to(list_guards))
+            .route("/guards/{id}/evaluate", web::post().to(evaluate_guard))
+            .route("/guards/{id}/examples", web::get().to(list_examples))
+            .route("/guards/{id}/examples", web::post().to(add_examples))
+            .route(
+                "/guards/{id}/examples/{example_id}",
+                web::delete().to(remove_example),
+            )
             .route("/embeddings", web::post().to(embeddings_handler))
             .route("/images/generations", web::post().to(create_image))
+            .route("/traces/stream", web::get().to(stream_traces))
     }
 }
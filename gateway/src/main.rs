@@ -4,6 +4,7 @@ use clap::Parser;
 use config::{Config, ConfigError};
 use http::ApiServer;
 use langdb_core::{error::GatewayError, usage::InMemoryStorage};
+use reload::ConfigSource;
 use run::models::{load_models, ModelsLoadError};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
@@ -17,6 +18,7 @@ mod http;
 mod limit;
 mod middleware;
 mod otel;
+mod reload;
 mod run;
 mod session;
 mod tracing;
@@ -41,6 +43,8 @@ pub enum CliError {
     ConfigError(#[from] ConfigError),
     #[error(transparent)]
     ModelsError(#[from] ModelsLoadError),
+    #[error("{0}")]
+    GuardEvalError(String),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -65,10 +69,13 @@ pub const LOGO: &str = r#"
 #[actix_web::main]
 async fn main() -> Result<(), CliError> {
     dotenv::dotenv().ok();
-    println!("{LOGO}");
     std::env::set_var("RUST_BACKTRACE", "1");
 
     let cli = cli::Cli::parse();
+    // Keep machine readable output clean
+    if !cli.json_output() {
+        println!("{LOGO}");
+    }
 
     match cli
         .command
@@ -82,6 +89,22 @@ async fn main() -> Result<(), CliError> {
             println!("{} Models updated successfully!", models.len());
             Ok(())
         }
+        cli::Commands::Guards { command } => match command {
+            cli::GuardsCommands::Eval(args) => {
+                if !args.json {
+                    tracing::init_tracing();
+                }
+                let config = Config::load(&cli.config)?;
+                let models = load_models(false).await?;
+                let report = run::guards::eval_guard(&config, &args, models).await?;
+                if args.json {
+                    println!("{}", serde_json::to_string_pretty(&report)?);
+                } else {
+                    run::table::pretty_print_guard_eval(&report);
+                }
+                Ok(())
+            }
+        },
         cli::Commands::List => {
             tracing::init_tracing();
             println!("Available models:");
@@ -103,8 +126,10 @@ async fn main() -> Result<(), CliError> {
                     tokio::spawn(async move { Tui::spawn_counter_loop(storage, counters).await });
 
                 let config = Config::load(&cli.config)?;
-                let config = config.apply_cli_overrides(&cli::Commands::Serve(serve_args));
-                let api_server = ApiServer::new(config);
+                let config = config.apply_cli_overrides(&cli::Commands::Serve(serve_args.clone()));
+                let api_server = ApiServer::new(config).with_config_source(
+                    (!serve_args.no_reload).then(|| ConfigSource::new(&cli.config, serve_args)),
+                );
                 let models = load_models(false).await?;
                 let server_handle = tokio::spawn(async move {
                     match api_server.start(models, Some(storage_clone)).await {
@@ -149,8 +174,10 @@ async fn main() -> Result<(), CliError> {
                 tracing::init_tracing();
 
                 let config = Config::load(&cli.config)?;
-                let config = config.apply_cli_overrides(&cli::Commands::Serve(serve_args));
-                let api_server = ApiServer::new(config);
+                let config = config.apply_cli_overrides(&cli::Commands::Serve(serve_args.clone()));
+                let api_server = ApiServer::new(config).with_config_source(
+                    (!serve_args.no_reload).then(|| ConfigSource::new(&cli.config, serve_args)),
+                );
                 let models = load_models(false).await?;
                 let server_handle = tokio::spawn(async move {
                     let storage = Arc::new(Mutex::new(InMemoryStorage::new()));
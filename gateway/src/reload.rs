@@ -0,0 +1,469 @@
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+
+use langdb_core::executor::ProvidersConfig;
+use langdb_core::handler::middleware::rate_limit::RateLimiting;
+use langdb_core::handler::LimitCheckWrapper;
+use langdb_core::types::guardrails::cache::GuardResultCache;
+use langdb_core::types::guardrails::DatasetStore;
+use langdb_core::types::reloadable::Reloadable;
+use langdb_core::usage::InMemoryStorage;
+use serde::Serialize;
+use serde_json::Value;
+use tokio::sync::Mutex;
+
+use crate::cli::{Commands, ServeArgs};
+use crate::config::{load_langdb_proxy_config, Config, ConfigError, CostControl};
+use crate::guardrails::GuardrailsService;
+use crate::limit::GatewayLimitChecker;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Parts of the config swapped on reload, shared by all workers.
+#[derive(Clone)]
+pub struct ReloadableConfig {
+    pub guardrails: Reloadable<GuardrailsService>,
+    pub providers: Reloadable<Option<ProvidersConfig>>,
+    pub rate_limit: Reloadable<Option<RateLimiting>>,
+    pub limit_checker: Reloadable<Option<LimitCheckWrapper>>,
+    /// Kept across reloads, a change of `datasets` requires a restart
+    pub dataset_store: Arc<dyn DatasetStore>,
+    storage: Option<Arc<Mutex<InMemoryStorage>>>,
+}
+
+impl ReloadableConfig {
+    pub fn new(
+        config: &Config,
+        storage: Option<Arc<Mutex<InMemoryStorage>>>,
+        dataset_store: Arc<dyn DatasetStore>,
+    ) -> Self {
+        Self {
+            guardrails: Reloadable::new(guardrails_service(config, dataset_store.clone())),
+            providers: Reloadable::new(load_langdb_proxy_config(config.providers.clone())),
+            rate_limit: Reloadable::new(config.rate_limit.clone()),
+            limit_checker: Reloadable::new(limit_checker(
+                storage.clone(),
+                config.cost_control.as_ref(),
+            )),
+            dataset_store,
+            storage,
+        }
+    }
+
+    /// Swaps the changed sections only, so unchanged guards keep their cached results
+    /// and webhook clients, and the cost limits keep their state.
+    fn store(&self, config: &Config, diff: &ConfigDiff, guardrails: Option<GuardrailsService>) {
+        if let Some(guardrails) = guardrails {
+            self.guardrails.store(guardrails);
+        }
+        if !diff.providers.is_empty() {
+            self.providers
+                .store(load_langdb_proxy_config(config.providers.clone()));
+        }
+        if diff.rate_limit.is_some() {
+            self.rate_limit.store(config.rate_limit.clone());
+        }
+        if diff.cost_control.is_some() {
+            self.limit_checker.store(limit_checker(
+                self.storage.clone(),
+                config.cost_control.as_ref(),
+            ));
+        }
+    }
+}
+
+fn guardrails_service(config: &Config, dataset_store: Arc<dyn DatasetStore>) -> GuardrailsService {
+    GuardrailsService::new(config.guards.clone().unwrap_or_default())
+        .with_cache(GuardResultCache::new(
+            config.guard_cache.capacity,
+            Duration::from_secs(config.guard_cache.ttl_secs),
+        ))
+        .with_dataset_store(dataset_store)
+}
+
+fn limit_checker(
+    storage: Option<Arc<Mutex<InMemoryStorage>>>,
+    cost_control: Option<&CostControl>,
+) -> Option<LimitCheckWrapper> {
+    let checker = GatewayLimitChecker::new(storage?, cost_control?.clone());
+    Some(LimitCheckWrapper {
+        checkers: vec![Arc::new(Mutex::new(checker))],
+    })
+}
+
+/// Config file and the CLI overrides applied over it.
+#[derive(Clone, Debug)]
+pub struct ConfigSource {
+    pub path: PathBuf,
+    pub overrides: ServeArgs,
+}
+
+impl ConfigSource {
+    pub fn new(path: impl Into<PathBuf>, overrides: ServeArgs) -> Self {
+        Self {
+            path: path.into(),
+            overrides,
+        }
+    }
+
+    /// Unlike [`Config::load`], a missing or invalid file is an error.
+    fn load(&self) -> Result<Config, ConfigError> {
+        let content = std::fs::read_to_string(&self.path)?;
+        Ok(Config::parse(content)?.apply_cli_overrides(&Commands::Serve(self.overrides.clone())))
+    }
+
+    fn modified(&self) -> Option<SystemTime> {
+        std::fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+/// Changes between two configs. Only ids and names of guards and providers are listed,
+/// so credentials never reach the logs.
+#[derive(Debug, Default, Serialize)]
+pub struct ConfigDiff {
+    #[serde(skip_serializing_if = "KeyedDiff::is_empty")]
+    pub guards: KeyedDiff,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub guard_cache: Option<ValueChange>,
+    #[serde(skip_serializing_if = "KeyedDiff::is_empty")]
+    pub providers: KeyedDiff,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate_limit: Option<ValueChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cost_control: Option<ValueChange>,
+    /// Changed sections that only apply after a restart
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub restart_required: Vec<&'static str>,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct KeyedDiff {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub added: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub removed: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub changed: Vec<String>,
+}
+
+impl KeyedDiff {
+    fn new<T: Serialize>(before: &HashMap<String, T>, after: &HashMap<String, T>) -> Self {
+        let before = to_sorted_values(before);
+        let after = to_sorted_values(after);
+
+        let mut diff = Self::default();
+        for (key, value) in &after {
+            match before.get(key) {
+                None => diff.added.push(key.clone()),
+                Some(previous) if previous != value => diff.changed.push(key.clone()),
+                Some(_) => {}
+            }
+        }
+        diff.removed = before
+            .keys()
+            .filter(|key| !after.contains_key(*key))
+            .cloned()
+            .collect();
+        diff
+    }
+
+    fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+fn to_sorted_values<T: Serialize>(map: &HashMap<String, T>) -> BTreeMap<String, Value> {
+    map.iter()
+        .map(|(key, value)| (key.clone(), serde_json::to_value(value).unwrap_or_default()))
+        .collect()
+}
+
+#[derive(Debug, Serialize)]
+pub struct ValueChange {
+    pub before: Value,
+    pub after: Value,
+}
+
+impl ValueChange {
+    fn new<T: Serialize>(before: &T, after: &T) -> Option<Self> {
+        let before = serde_json::to_value(before).unwrap_or_default();
+        let after = serde_json::to_value(after).unwrap_or_default();
+        (before != after).then_some(Self { before, after })
+    }
+}
+
+impl ConfigDiff {
+    pub fn new(before: &Config, after: &Config) -> Self {
+        let restart_required = [
+            ("http", differs(&before.http, &after.http)),
+            ("clickhouse", differs(&before.clickhouse, &after.clickhouse)),
+            ("datasets", differs(&before.datasets, &after.datasets)),
+            ("images", differs(&before.images, &after.images)),
+            (
+                "model_aliases",
+                differs(&before.model_aliases, &after.model_aliases),
+            ),
+        ]
+        .into_iter()
+        .filter_map(|(section, changed)| changed.then_some(section))
+        .collect();
+
+        Self {
+            guards: KeyedDiff::new(
+                &before.guards.clone().unwrap_or_default(),
+                &after.guards.clone().unwrap_or_default(),
+            ),
+            guard_cache: ValueChange::new(&before.guard_cache, &after.guard_cache),
+            providers: KeyedDiff::new(
+                &before.providers.clone().unwrap_or_default().0,
+                &after.providers.clone().unwrap_or_default().0,
+            ),
+            rate_limit: ValueChange::new(&before.rate_limit, &after.rate_limit),
+            cost_control: ValueChange::new(&before.cost_control, &after.cost_control),
+            restart_required,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.guards.is_empty()
+            && self.guard_cache.is_none()
+            && self.providers.is_empty()
+            && self.rate_limit.is_none()
+            && self.cost_control.is_none()
+            && self.restart_required.is_empty()
+    }
+}
+
+fn differs<T: Serialize>(before: &T, after: &T) -> bool {
+    serde_json::to_value(before).ok() != serde_json::to_value(after).ok()
+}
+
+/// Reloads the config when the file changes or the process receives `SIGHUP`.
+pub struct ConfigWatcher {
+    source: ConfigSource,
+    current: Config,
+    handles: ReloadableConfig,
+}
+
+impl ConfigWatcher {
+    pub fn new(source: ConfigSource, current: Config, handles: ReloadableConfig) -> Self {
+        Self {
+            source,
+            current,
+            handles,
+        }
+    }
+
+    pub fn spawn(self) -> tokio::task::JoinHandle<()> {
+        tokio::spawn(self.run())
+    }
+
+    async fn run(mut self) {
+        let mut modified = self.source.modified();
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        #[cfg(unix)]
+        let mut hangup = match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::hangup())
+        {
+            Ok(signal) => Some(signal),
+            Err(e) => {
+                tracing::warn!("Failed to listen for SIGHUP, only watching the config file: {e}");
+                None
+            }
+        };
+
+        loop {
+            #[cfg(unix)]
+            let hangup_received = async {
+                match hangup.as_mut() {
+                    Some(signal) => signal.recv().await,
+                    None => std::future::pending().await,
+                }
+            };
+            #[cfg(not(unix))]
+            let hangup_received = std::future::pending::<Option<()>>();
+
+            tokio::select! {
+                _ = interval.tick() => {
+                    let current = self.source.modified();
+                    if current == modified {
+                        continue;
+                    }
+                    modified = current;
+                }
+                _ = hangup_received => {
+                    tracing::info!("Received SIGHUP, reloading config");
+                }
+            }
+
+            if let Err(e) = self.reload() {
+                tracing::error!(
+                    "Failed to reload config from {}, keeping the current config: {e}",
+                    self.source.path.display()
+                );
+            }
+        }
+    }
+
+    /// Parses and validates the config before swapping it in.
+    fn reload(&mut self) -> Result<(), ConfigError> {
+        let config = self.source.load()?;
+        let diff = ConfigDiff::new(&self.current, &config);
+        if diff.is_empty() {
+            tracing::debug!("Config unchanged");
+            return Ok(());
+        }
+
+        let guardrails = if diff.guards.is_empty() && diff.guard_cache.is_none() {
+            None
+        } else {
+            let guardrails = guardrails_service(&config, self.handles.dataset_store.clone());
+            guardrails.validate().map_err(ConfigError::InvalidGuards)?;
+            Some(guardrails)
+        };
+
+        self.handles.store(&config, &diff, guardrails);
+        tracing::info!(
+            changes = serde_json::to_string(&diff).unwrap_or_default(),
+            "Reloaded config from {}",
+            self.source.path.display()
+        );
+        if !diff.restart_required.is_empty() {
+            tracing::warn!(
+                "Changes to {} only apply after a restart",
+                diff.restart_required.join(", ")
+            );
+        }
+        self.current = config;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use langdb_core::types::guardrails::service::GuardrailsEvaluator;
+    use langdb_core::types::guardrails::store::DirectoryDatasetStore;
+
+    const GUARD: &str = r#"
+guards:
+  word-count:
+    type: word_count
+    id: word-count
+    name: Word Count
+    template_id: validation-word-count
+    stage: input
+    action: validate
+"#;
+
+    fn config(content: &str) -> Config {
+        Config::parse(content.to_string()).unwrap()
+    }
+
+    struct Fixture {
+        path: PathBuf,
+        watcher: ConfigWatcher,
+    }
+
+    impl Fixture {
+        fn new(name: &str, content: &str) -> Self {
+            let path = std::env::temp_dir()
+                .join(format!("gateway-reload-{name}-{}.yaml", std::process::id()));
+            std::fs::write(&path, content).unwrap();
+
+            let current = config(content);
+            let dataset_store: Arc<dyn DatasetStore> =
+                Arc::new(DirectoryDatasetStore::new(std::env::temp_dir()));
+            let handles = ReloadableConfig::new(&current, None, dataset_store);
+            let source = ConfigSource::new(&path, ServeArgs::default());
+            Self {
+                path,
+                watcher: ConfigWatcher::new(source, current, handles),
+            }
+        }
+
+        fn reload(&mut self, content: &str) -> Result<(), ConfigError> {
+            std::fs::write(&self.path, content).unwrap();
+            self.watcher.reload()
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_file(&self.path);
+        }
+    }
+
+    #[test]
+    fn test_config_diff() {
+        let before = config(GUARD);
+        let after = config(&format!(
+            "{}\nrate_limit:\n  hourly: 10\nimages:\n  max_dimension: 512\n",
+            GUARD.replace("Word Count", "Words")
+        ));
+
+        let diff = ConfigDiff::new(&before, &after);
+        assert_eq!(diff.guards.changed, vec!["word-count"]);
+        assert!(diff.guards.added.is_empty() && diff.guards.removed.is_empty());
+        assert!(diff.providers.is_empty());
+        assert!(diff.rate_limit.is_some());
+        assert!(diff.cost_control.is_none());
+        assert_eq!(diff.restart_required, vec!["images"]);
+
+        assert!(ConfigDiff::new(&before, &config(GUARD)).is_empty());
+        assert_eq!(
+            ConfigDiff::new(&before, &config("")).guards.removed,
+            vec!["word-count"]
+        );
+    }
+
+    #[test]
+    fn test_reload_swaps_changed_sections() {
+        let mut fixture = Fixture::new("swap", GUARD);
+        let guardrails = fixture.watcher.handles.guardrails.load();
+        let providers = fixture.watcher.handles.providers.load();
+
+        fixture
+            .reload(&format!("{GUARD}\nrate_limit:\n  hourly: 10\n"))
+            .unwrap();
+        let handles = &fixture.watcher.handles;
+        assert_eq!(
+            handles.rate_limit.load().as_ref().as_ref().unwrap().hourly,
+            Some(10)
+        );
+        assert!(Arc::ptr_eq(&guardrails, &handles.guardrails.load()));
+        assert!(Arc::ptr_eq(&providers, &handles.providers.load()));
+
+        fixture.reload("").unwrap();
+        assert!(fixture
+            .watcher
+            .handles
+            .guardrails
+            .load()
+            .guards()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_reload_rejects_invalid_config() {
+        let mut fixture = Fixture::new("invalid", GUARD);
+        let guardrails = fixture.watcher.handles.guardrails.load();
+
+        let unknown_template = GUARD.replace("validation-word-count", "missing-template");
+        assert!(matches!(
+            fixture.reload(&unknown_template),
+            Err(ConfigError::InvalidGuards(_))
+        ));
+        assert!(fixture.reload("guards: [").is_err());
+
+        // The current config is kept
+        assert!(Arc::ptr_eq(
+            &guardrails,
+            &fixture.watcher.handles.guardrails.load()
+        ));
+        assert!(ConfigDiff::new(&fixture.watcher.current, &config(GUARD)).is_empty());
+    }
+}
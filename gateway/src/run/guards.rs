@@ -0,0 +1,268 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use futures::StreamExt;
+use langdb_core::executor::context::ExecutorContext;
+use langdb_core::handler::{CallbackHandlerFn, ModelEventWithDetails};
+use langdb_core::model::types::ModelEventType;
+use langdb_core::model::{CredentialsIdent, DefaultModelMetadataFactory, ModelMetadataFactory};
+use langdb_core::models::ModelMetadata;
+use langdb_core::routing::interceptor::rate_limiter::InMemoryRateLimiterService;
+use langdb_core::types::gateway::{ChatCompletionMessage, CostCalculator, Usage};
+use langdb_core::types::guardrails::cache::GuardResultCache;
+use langdb_core::types::guardrails::service::GuardrailsEvaluator;
+use langdb_core::types::guardrails::{DatasetLoader, Guard, GuardStage};
+use langdb_guardrails::guards::FileDatasetLoader;
+use serde::Serialize;
+use tokio::sync::broadcast::error::RecvError;
+
+use crate::cli::GuardEvalArgs;
+use crate::config::Config;
+use crate::cost::GatewayCostCalculator;
+use crate::guardrails::GuardrailsService;
+use crate::CliError;
+
+/// Upper bound on waiting for the usage of model calls still in flight
+const USAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a guard on a labelled dataset. Blocked content is the positive class.
+#[derive(Debug, Serialize)]
+pub struct GuardEvalReport {
+    pub guard_id: String,
+    pub examples: usize,
+    pub errors: usize,
+    /// Examples labelled as not allowed that the guard blocked
+    pub true_positives: usize,
+    /// Examples labelled as allowed that the guard blocked
+    pub false_positives: usize,
+    pub true_negatives: usize,
+    pub false_negatives: usize,
+    pub precision: Option<f64>,
+    pub recall: Option<f64>,
+    pub f1: Option<f64>,
+    pub accuracy: Option<f64>,
+    pub latency_ms: LatencyPercentiles,
+    /// Cost of the model calls made by the guard, in dollars
+    pub cost: f64,
+}
+
+#[derive(Debug, Default, Serialize)]
+pub struct LatencyPercentiles {
+    pub p50: f64,
+    pub p90: f64,
+    pub p99: f64,
+    pub max: f64,
+}
+
+impl LatencyPercentiles {
+    fn new(mut latencies: Vec<Duration>) -> Self {
+        if latencies.is_empty() {
+            return Self::default();
+        }
+
+        latencies.sort();
+        let percentile = |p: f64| {
+            let rank = ((p / 100.0) * latencies.len() as f64).ceil() as usize;
+            latencies[rank.clamp(1, latencies.len()) - 1].as_secs_f64() * 1000.0
+        };
+        Self {
+            p50: percentile(50.0),
+            p90: percentile(90.0),
+            p99: percentile(99.0),
+            max: percentile(100.0),
+        }
+    }
+}
+
+struct ExampleOutcome {
+    label: bool,
+    passed: Result<bool, String>,
+    latency: Duration,
+}
+
+impl GuardEvalReport {
+    fn new(guard_id: String, outcomes: Vec<ExampleOutcome>, cost: f64) -> Self {
+        let (mut tp, mut fp, mut tn, mut fn_) = (0, 0, 0, 0);
+        let mut errors = 0;
+        for outcome in &outcomes {
+            match (&outcome.passed, outcome.label) {
+                (Err(_), _) => errors += 1,
+                (Ok(false), false) => tp += 1,
+                (Ok(false), true) => fp += 1,
+                (Ok(true), true) => tn += 1,
+                (Ok(true), false) => fn_ += 1,
+            }
+        }
+
+        let ratio = |a: usize, b: usize| match b {
+            0 => None,
+            b => Some(a as f64 / b as f64),
+        };
+        let precision = ratio(tp, tp + fp);
+        let recall = ratio(tp, tp + fn_);
+        let f1 = match (precision, recall) {
+            (Some(p), Some(r)) if p + r > 0.0 => Some(2.0 * p * r / (p + r)),
+            _ => None,
+        };
+
+        Self {
+            guard_id,
+            examples: outcomes.len(),
+            errors,
+            true_positives: tp,
+            false_positives: fp,
+            true_negatives: tn,
+            false_negatives: fn_,
+            precision,
+            recall,
+            f1,
+            accuracy: ratio(tp + tn, tp + fp + tn + fn_),
+            latency_ms: LatencyPercentiles::new(
+                outcomes
+                    .iter()
+                    .filter(|o| o.passed.is_ok())
+                    .map(|o| o.latency)
+                    .collect(),
+            ),
+            cost,
+        }
+    }
+}
+
+/// Callback handler totalling the cost of the model calls reported to it.
+/// The total is final once every clone of the handler has been dropped.
+fn cost_collector() -> (CallbackHandlerFn, tokio::task::JoinHandle<f64>) {
+    let (tx, mut rx) = tokio::sync::broadcast::channel::<ModelEventWithDetails>(10000);
+    let calculator = GatewayCostCalculator::new();
+    let total = tokio::spawn(async move {
+        let mut cost = 0.0;
+        loop {
+            let event = match rx.recv().await {
+                Ok(event) => event,
+                Err(RecvError::Lagged(missed)) => {
+                    tracing::warn!("Cost of {missed} model events was not counted");
+                    continue;
+                }
+                Err(RecvError::Closed) => return cost,
+            };
+
+            let (ModelEventType::LlmStop(finish), Some(model)) = (&event.event.event, &event.model)
+            else {
+                continue;
+            };
+            let Some(usage) = finish.usage.clone() else {
+                continue;
+            };
+            match calculator
+                .calculate_cost(
+                    &model.price,
+                    &Usage::CompletionModelUsage(usage),
+                    &CredentialsIdent::Own,
+                )
+                .await
+            {
+                Ok(usage) => cost += usage.cost,
+                Err(e) => tracing::warn!("Failed to calculate the cost of {}: {e}", model.name),
+            }
+        }
+    });
+
+    (CallbackHandlerFn(Some(tx)), total)
+}
+
+/// Runs a configured guard over every example of the dataset.
+pub async fn eval_guard(
+    config: &Config,
+    args: &GuardEvalArgs,
+    models: Vec<ModelMetadata>,
+) -> Result<GuardEvalReport, CliError> {
+    let mut guards = config.guards.clone().unwrap_or_default();
+    let guard = guards
+        .get(&args.guard_id)
+        .ok_or_else(|| CliError::GuardEvalError(format!("Guard {} not found", args.guard_id)))?;
+
+    // Measure the verdict of the guard even when it is configured to only observe
+    let mut value = serde_json::to_value(guard)?;
+    value["action"] = serde_json::json!("validate");
+    let guard: Guard = serde_json::from_value(value)?;
+    let stage = guard.stage().clone();
+    guards.insert(args.guard_id.clone(), guard);
+
+    let examples = FileDatasetLoader
+        .load(&args.dataset)
+        .await
+        .map_err(CliError::GuardEvalError)?;
+
+    let (callback_handler, total_cost) = cost_collector();
+    // Every example is evaluated, repeated ones included
+    let service = Arc::new(Box::new(
+        GuardrailsService::new(guards)
+            .with_cache(GuardResultCache::new(0, Duration::ZERO))
+            .with_dataset_store(config.dataset_store()?),
+    ) as Box<dyn GuardrailsEvaluator>);
+
+    let executor_context = {
+        let request = actix_web::test::TestRequest::default().to_http_request();
+        ExecutorContext::new(
+            callback_handler,
+            Arc::new(Box::new(GatewayCostCalculator::new()) as Box<dyn CostCalculator>),
+            Arc::new(Box::new(
+                DefaultModelMetadataFactory::new(&models)
+                    .with_aliases(config.model_aliases.clone()),
+            ) as Box<dyn ModelMetadataFactory>),
+            &request,
+            HashMap::new(),
+            service.clone(),
+            Arc::new(InMemoryRateLimiterService::new()),
+        )
+        .map_err(|e| CliError::GatewayError(Box::new(e)))?
+    };
+
+    // Output guards see the model response, tool guards the call arguments or tool output
+    let role = match stage {
+        GuardStage::Input => "user",
+        GuardStage::Output | GuardStage::ToolCall => "assistant",
+        GuardStage::ToolResult => "tool",
+    };
+    let outcomes: Vec<ExampleOutcome> = futures::stream::iter(&examples)
+        .map(|example| {
+            let (service, executor_context, stage) = (&service, &executor_context, &stage);
+            async move {
+                let messages = vec![ChatCompletionMessage::new_text(
+                    role.to_string(),
+                    example.text.clone(),
+                )];
+                let started = Instant::now();
+                let passed = service
+                    .evaluate(&messages, &args.guard_id, executor_context, None, stage)
+                    .await
+                    .map(|result| result.passed());
+                if let Err(e) = &passed {
+                    tracing::warn!("Failed to evaluate example \"{}\": {e}", example.text);
+                }
+                ExampleOutcome {
+                    label: example.label,
+                    passed,
+                    latency: started.elapsed(),
+                }
+            }
+        })
+        .buffer_unordered(args.concurrency.max(1))
+        .collect()
+        .await;
+
+    // Closes the usage channel once the model calls have reported their usage
+    drop(executor_context);
+    let cost = match tokio::time::timeout(USAGE_TIMEOUT, total_cost).await {
+        Ok(Ok(cost)) => cost,
+        Ok(Err(e)) => return Err(CliError::GuardEvalError(e.to_string())),
+        Err(_) => {
+            return Err(CliError::GuardEvalError(
+                "Timed out waiting for the usage of the guard's model calls".to_string(),
+            ))
+        }
+    };
+
+    Ok(GuardEvalReport::new(args.guard_id.clone(), outcomes, cost))
+}
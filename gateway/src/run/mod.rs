@@ -1,2 +1,3 @@
+pub mod guards;
 pub mod models;
 pub mod table;
@@ -1,6 +1,8 @@
 use langdb_core::{models::ModelMetadata, types::provider::ModelPrice};
 use prettytable::{row, Table};
 
+use super::guards::GuardEvalReport;
+
 pub fn pretty_print_models(models: Vec<ModelMetadata>) {
     let mut table = Table::new();
 
@@ -69,3 +71,43 @@ fn get_price(price: ModelPrice) -> String {
         }
     }
 }
+
+pub fn pretty_print_guard_eval(report: &GuardEvalReport) {
+    let ratio = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{v:.3}"));
+
+    let mut matrix = Table::new();
+    matrix.add_row(row![bF=> "", "Blocked", "Passed"]);
+    matrix.add_row(row![
+        "Labelled not allowed",
+        report.true_positives,
+        report.false_negatives
+    ]);
+    matrix.add_row(row![
+        "Labelled allowed",
+        report.false_positives,
+        report.true_negatives
+    ]);
+
+    let mut table = Table::new();
+    table.add_row(row![bF=> "Guard", report.guard_id]);
+    table.add_row(row!["Examples", report.examples]);
+    table.add_row(row!["Errors", report.errors]);
+    table.add_row(row!["Precision", ratio(report.precision)]);
+    table.add_row(row!["Recall", ratio(report.recall)]);
+    table.add_row(row!["F1", ratio(report.f1)]);
+    table.add_row(row!["Accuracy", ratio(report.accuracy)]);
+    table.add_row(row![
+        "Latency p50 / p90 / p99 / max",
+        format!(
+            "{:.0} / {:.0} / {:.0} / {:.0} ms",
+            report.latency_ms.p50,
+            report.latency_ms.p90,
+            report.latency_ms.p99,
+            report.latency_ms.max
+        )
+    ]);
+    table.add_row(row!["Cost", format!("${:.6}", report.cost)]);
+
+    matrix.printstd();
+    table.printstd();
+}
@@ -1,11 +1,23 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+
 use langdb_core::types::gateway::ChatCompletionMessage;
 
 use langdb_core::types::guardrails::{
-    evaluator::Evaluator, DatasetLoader, Guard, GuardExample, GuardResult,
+    evaluator::Evaluator, DatasetLoader, DatasetSource, DatasetStore, Guard, GuardExample,
+    GuardResult,
 };
 
+/// Computes embeddings of the input and examples of managed datasets
+#[async_trait::async_trait]
+pub trait GuardEmbedder: Send + Sync {
+    async fn embed(&self, model: &str, texts: Vec<String>) -> Result<Vec<Vec<f32>>, String>;
+}
+
 pub struct DatasetEvaluator {
     pub loader: Box<dyn DatasetLoader + Send + Sync>,
+    pub store: Option<Arc<dyn DatasetStore>>,
+    pub embedder: Option<Box<dyn GuardEmbedder>>,
 }
 
 #[async_trait::async_trait]
@@ -16,73 +28,31 @@ impl Evaluator for DatasetEvaluator {
         guard: &Guard,
     ) -> Result<GuardResult, String> {
         if let Guard::Dataset {
-            threshold, dataset, ..
+            threshold,
+            dataset,
+            embedding_model,
+            ..
         } = &guard
         {
             let text = self.messages_to_text(messages)?;
             match dataset {
-                langdb_core::types::guardrails::DatasetSource::Examples { examples } => {
+                DatasetSource::Examples { examples } => {
                     // Simple similarity check (in a real implementation, this would use embeddings)
-                    let mut best_match = None;
-                    let mut best_score = 0.0;
-
-                    for example in examples {
-                        let score = simple_similarity(&example.text, &text);
-                        if score > best_score {
-                            best_score = score;
-                            best_match = Some(example);
-                        }
-                    }
-
-                    if best_score >= *threshold {
-                        if let Some(example) = best_match {
-                            return Ok(GuardResult::Boolean {
-                                passed: example.label,
-                                confidence: Some(best_score),
-                            });
-                        }
-                    }
-
-                    Ok(GuardResult::Boolean {
-                        passed: true,
-                        confidence: Some(1.0 - best_score),
-                    })
+                    Ok(best_match(examples, &text, *threshold))
                 }
-                langdb_core::types::guardrails::DatasetSource::Source { source } => {
+                DatasetSource::Source { source } => {
                     // Load dataset from source
                     match self.loader.load(source).await {
-                        Ok(examples) => {
-                            // Simple similarity check
-                            let mut best_match = None;
-                            let mut best_score = 0.0;
-
-                            for example in &examples {
-                                let score = simple_similarity(&example.text, &text);
-                                if score > best_score {
-                                    best_score = score;
-                                    best_match = Some(example);
-                                }
-                            }
-
-                            if best_score >= *threshold {
-                                if let Some(example) = best_match {
-                                    return Ok(GuardResult::Boolean {
-                                        passed: example.label,
-                                        confidence: Some(best_score),
-                                    });
-                                }
-                            }
-
-                            Ok(GuardResult::Boolean {
-                                passed: true,
-                                confidence: Some(1.0 - best_score),
-                            })
-                        }
+                        Ok(examples) => Ok(best_match(&examples, &text, *threshold)),
                         Err(e) => Err(format!("Error loading dataset: {e}")),
                     }
                 }
-                langdb_core::types::guardrails::DatasetSource::Managed { .. } => {
-                    unimplemented!("Managed datasets are not yet supported. Please use a cloud solution instead.")
+                DatasetSource::Managed { .. } => {
+                    let config = dataset
+                        .managed()
+                        .ok_or("Invalid managed dataset".to_string())??;
+                    self.evaluate_managed(&config.name, embedding_model, &text, *threshold)
+                        .await
                 }
             }
         } else {
@@ -91,6 +61,124 @@ impl Evaluator for DatasetEvaluator {
     }
 }
 
+impl DatasetEvaluator {
+    /// Compares the embedding of the text with the examples of a managed dataset.
+    /// Examples stored without an embedding are embedded along with the text.
+    async fn evaluate_managed(
+        &self,
+        dataset: &str,
+        embedding_model: &str,
+        text: &str,
+        threshold: f64,
+    ) -> Result<GuardResult, String> {
+        let store = self
+            .store
+            .as_ref()
+            .ok_or("Managed datasets are not configured".to_string())?;
+        let embedder = self
+            .embedder
+            .as_ref()
+            .ok_or("No embedder configured for managed datasets".to_string())?;
+
+        let examples = store.list(dataset).await?;
+        let mut texts = vec![text.to_string()];
+        texts.extend(
+            examples
+                .iter()
+                .filter(|stored| stored.example.embedding.is_none())
+                .map(|stored| stored.example.text.clone()),
+        );
+
+        let mut embeddings = embedder.embed(embedding_model, texts).await?.into_iter();
+        let input = embeddings
+            .next()
+            .ok_or("Embedding model returned no embeddings".to_string())?;
+
+        let mut computed = HashMap::new();
+        for stored in &examples {
+            if stored.example.embedding.is_none() {
+                let embedding = embeddings
+                    .next()
+                    .ok_or("Embedding model returned too few embeddings".to_string())?;
+                computed.insert(stored.id.clone(), embedding);
+            }
+        }
+
+        let mut best: Option<(&GuardExample, f64)> = None;
+        for stored in &examples {
+            let Some(embedding) = stored
+                .example
+                .embedding
+                .as_ref()
+                .or_else(|| computed.get(&stored.id))
+            else {
+                continue;
+            };
+
+            let score = cosine_similarity(&input, embedding);
+            if best.is_none_or(|(_, best_score)| score > best_score) {
+                best = Some((&stored.example, score));
+            }
+        }
+
+        // Examples are embedded once, later evaluations reuse the stored embeddings
+        if !computed.is_empty() {
+            if let Err(e) = store.set_embeddings(dataset, computed).await {
+                tracing::warn!("Failed to store embeddings of dataset {dataset}: {e}");
+            }
+        }
+
+        Ok(match best {
+            Some((example, score)) => verdict(Some(example), score, threshold),
+            None => verdict(None, 0.0, threshold),
+        })
+    }
+}
+
+fn best_match(examples: &[GuardExample], text: &str, threshold: f64) -> GuardResult {
+    let mut best_match = None;
+    let mut best_score = 0.0;
+
+    for example in examples {
+        let score = simple_similarity(&example.text, text);
+        if score > best_score {
+            best_score = score;
+            best_match = Some(example);
+        }
+    }
+
+    verdict(best_match, best_score, threshold)
+}
+
+/// The label of the closest example decides when it is similar enough, otherwise the text passes
+fn verdict(best_match: Option<&GuardExample>, best_score: f64, threshold: f64) -> GuardResult {
+    match best_match {
+        Some(example) if best_score >= threshold => GuardResult::Boolean {
+            passed: example.label,
+            confidence: Some(best_score),
+        },
+        _ => GuardResult::Boolean {
+            passed: true,
+            confidence: Some(1.0 - best_score),
+        },
+    }
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f64 {
+    if a.len() != b.len() {
+        return 0.0;
+    }
+
+    let dot: f64 = a.iter().zip(b).map(|(x, y)| *x as f64 * *y as f64).sum();
+    let norm_a = a.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    let norm_b = b.iter().map(|x| (*x as f64).powi(2)).sum::<f64>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
 // Simple similarity function (in a real implementation, this would use embeddings)
 fn simple_similarity(a: &str, b: &str) -> f64 {
     let a_words: Vec<&str> = a.split_whitespace().collect();
@@ -110,25 +198,30 @@ fn simple_similarity(a: &str, b: &str) -> f64 {
     }
 }
 
-// File-based dataset loader implementation
+/// Loads labelled examples from a JSONL file, one `{"text": ..., "label": ...}` object per line.
+/// A label of `true` marks content that should pass the guard.
 pub struct FileDatasetLoader;
 
+impl FileDatasetLoader {
+    pub fn parse(content: &str) -> Result<Vec<GuardExample>, String> {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(number, line)| {
+                serde_json::from_str(line)
+                    .map_err(|e| format!("Invalid example on line {}: {e}", number + 1))
+            })
+            .collect()
+    }
+}
+
 #[async_trait::async_trait]
 impl DatasetLoader for FileDatasetLoader {
-    async fn load(&self, _source: &str) -> Result<Vec<GuardExample>, String> {
-        // In a real implementation, this would load from a file
-        // For this example, we'll just return some dummy data
-        Ok(vec![
-            GuardExample {
-                text: "This is a positive example".to_string(),
-                label: true,
-                embedding: None,
-            },
-            GuardExample {
-                text: "This is a negative example".to_string(),
-                label: false,
-                embedding: None,
-            },
-        ])
+    async fn load(&self, source: &str) -> Result<Vec<GuardExample>, String> {
+        let content = tokio::fs::read_to_string(source)
+            .await
+            .map_err(|e| format!("Failed to read dataset {source}: {e}"))?;
+        Self::parse(&content)
     }
 }
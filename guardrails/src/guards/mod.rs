@@ -3,8 +3,10 @@ pub mod dataset;
 pub mod llm_judge;
 pub mod partner;
 pub mod partners;
+pub mod pii;
 pub mod regex;
 pub mod schema;
+pub mod secrets;
 pub mod traced;
 pub mod word_count;
 
@@ -12,8 +14,10 @@ pub mod word_count;
 pub mod tests;
 
 // Re-export evaluators
-pub use dataset::{DatasetEvaluator, FileDatasetLoader};
+pub use dataset::{DatasetEvaluator, FileDatasetLoader, GuardEmbedder};
 pub use llm_judge::LlmJudgeEvaluator;
+pub use pii::PiiEvaluator;
 pub use regex::RegexEvaluator;
 pub use schema::SchemaEvaluator;
+pub use secrets::SecretsEvaluator;
 pub use word_count::WordCountEvaluator;
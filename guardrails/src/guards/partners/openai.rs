@@ -66,6 +66,9 @@ impl GuardPartner for OpenaiGuardrailPartner {
                                 ContentType::InputAudio => Err(
                                     GuardPartnerError::InputTypeNotSupported("audio".to_string()),
                                 ),
+                                ContentType::File => Err(GuardPartnerError::InputTypeNotSupported(
+                                    "file".to_string(),
+                                )),
                             })
                             .collect::<Result<Vec<ModerationContentPart>, GuardPartnerError>>()?,
                     ),
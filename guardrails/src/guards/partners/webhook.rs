@@ -0,0 +1,204 @@
+use std::time::Duration;
+
+use langdb_core::types::{
+    gateway::{ChatCompletionContent, ChatCompletionMessage},
+    guardrails::{
+        partner::{GuardPartner, GuardPartnerError, WebhookPartnerConfig},
+        GuardResult,
+    },
+};
+use serde_json::{Map, Value};
+use tracing::Span;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Partner posting the messages to an HTTP endpoint and mapping its JSON response.
+pub struct WebhookGuardrailPartner {
+    config: WebhookPartnerConfig,
+    client: reqwest::Client,
+}
+
+impl WebhookGuardrailPartner {
+    pub fn new(config: WebhookPartnerConfig) -> Result<Self, GuardPartnerError> {
+        let client = Self::client(&config)?;
+        Ok(Self::with_client(config, client))
+    }
+
+    /// Reuses a client built by [`Self::client`], keeping its connection pool across evaluations.
+    pub fn with_client(config: WebhookPartnerConfig, client: reqwest::Client) -> Self {
+        Self { config, client }
+    }
+
+    /// Client applying the timeout of the webhook.
+    pub fn client(config: &WebhookPartnerConfig) -> Result<reqwest::Client, GuardPartnerError> {
+        reqwest::Client::builder()
+            .timeout(
+                config
+                    .timeout_ms
+                    .map(Duration::from_millis)
+                    .unwrap_or(DEFAULT_TIMEOUT),
+            )
+            .build()
+            .map_err(|e| GuardPartnerError::BoxedError(e.into()))
+    }
+
+    fn request_body(&self, messages: &[ChatCompletionMessage]) -> Result<Value, GuardPartnerError> {
+        let text = match messages.last().and_then(|m| m.content.as_ref()) {
+            Some(ChatCompletionContent::Text(text)) => text.clone(),
+            Some(ChatCompletionContent::Content(parts)) => parts
+                .iter()
+                .filter_map(|part| part.text.clone())
+                .collect::<Vec<_>>()
+                .join("\n"),
+            None => String::new(),
+        };
+
+        let mut variables = Map::new();
+        variables.insert(
+            "messages".to_string(),
+            serde_json::to_value(messages).map_err(|e| GuardPartnerError::BoxedError(e.into()))?,
+        );
+        variables.insert("text".to_string(), Value::String(text));
+
+        Ok(render_template(&self.config.request_template, &variables))
+    }
+
+    fn map_response(&self, response: &Value) -> Result<GuardResult, GuardPartnerError> {
+        let mapping = &self.config.response_mapping;
+
+        let verdict = select(response, &mapping.passed)?
+            .as_bool()
+            .ok_or_else(|| {
+                GuardPartnerError::EvaluationFailed(format!(
+                    "{} is not a boolean in the webhook response",
+                    mapping.passed
+                ))
+            })?;
+        let passed = verdict != mapping.inverted;
+
+        let confidence = match &mapping.confidence {
+            Some(path) => select(response, path)?.as_f64(),
+            None => None,
+        };
+
+        match &mapping.text {
+            Some(path) => {
+                let text = match select(response, path)? {
+                    Value::String(text) => text.clone(),
+                    value => value.to_string(),
+                };
+                Ok(GuardResult::Text {
+                    text,
+                    passed,
+                    confidence,
+                })
+            }
+            None => Ok(GuardResult::Boolean { passed, confidence }),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl GuardPartner for WebhookGuardrailPartner {
+    async fn evaluate(
+        &self,
+        messages: &[ChatCompletionMessage],
+    ) -> Result<GuardResult, GuardPartnerError> {
+        let span = Span::current();
+        span.record("partner", "webhook".to_string());
+
+        let mut request = self
+            .client
+            .post(&self.config.url)
+            .json(&self.request_body(messages)?);
+        for (name, value) in self.config.exposed_headers() {
+            let mut value = reqwest::header::HeaderValue::from_str(value)
+                .map_err(|e| GuardPartnerError::BoxedError(e.into()))?;
+            value.set_sensitive(true);
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| GuardPartnerError::BoxedError(e.into()))?;
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| GuardPartnerError::BoxedError(e.into()))?;
+        if !status.is_success() {
+            return Err(GuardPartnerError::EvaluationFailed(format!(
+                "Webhook returned {status}: {body}"
+            )));
+        }
+
+        span.record("result_metadata", body.as_str());
+        let response: Value =
+            serde_json::from_str(&body).map_err(|e| GuardPartnerError::BoxedError(e.into()))?;
+
+        self.map_response(&response)
+    }
+}
+
+/// Replaces `{{name}}` placeholders in the string values of the template.
+fn render_template(template: &Value, variables: &Map<String, Value>) -> Value {
+    match template {
+        Value::String(text) => {
+            // A value that is only a placeholder keeps the JSON type of the variable
+            let whole = text
+                .strip_prefix("{{")
+                .and_then(|rest| rest.strip_suffix("}}"))
+                .and_then(|name| variables.get(name.trim()));
+            if let Some(value) = whole {
+                return value.clone();
+            }
+
+            let rendered = variables.iter().fold(text.clone(), |text, (name, value)| {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    value => value.to_string(),
+                };
+                text.replace(&format!("{{{{{name}}}}}"), &value)
+                    .replace(&format!("{{{{ {name} }}}}"), &value)
+            });
+            Value::String(rendered)
+        }
+        Value::Array(items) => Value::Array(
+            items
+                .iter()
+                .map(|item| render_template(item, variables))
+                .collect(),
+        ),
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, value)| (key.clone(), render_template(value, variables)))
+                .collect(),
+        ),
+        value => value.clone(),
+    }
+}
+
+fn select<'a>(value: &'a Value, path: &str) -> Result<&'a Value, GuardPartnerError> {
+    value.pointer(&json_path_to_pointer(path)?).ok_or_else(|| {
+        GuardPartnerError::EvaluationFailed(format!("{path} not found in the webhook response"))
+    })
+}
+
+/// Converts a JSONPath in dot and index notation, e.g. `$.results[0].flagged`, to a JSON pointer.
+fn json_path_to_pointer(path: &str) -> Result<String, GuardPartnerError> {
+    let rest = path.trim().strip_prefix('$').ok_or_else(|| {
+        GuardPartnerError::EvaluationFailed(format!("JSONPath {path} must start with $"))
+    })?;
+
+    Ok(rest
+        .replace('[', ".")
+        .replace(']', "")
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            let segment = segment.trim_matches(|c| c == '\'' || c == '"');
+            format!("/{}", segment.replace('~', "~0").replace('/', "~1"))
+        })
+        .collect())
+}
@@ -0,0 +1,339 @@
+use std::collections::{BTreeMap, HashMap};
+use std::net::{Ipv4Addr, Ipv6Addr};
+use std::sync::LazyLock;
+
+use langdb_core::types::gateway::{ChatCompletionContent, ChatCompletionMessage};
+use langdb_core::types::guardrails::evaluator::Evaluator;
+use langdb_core::types::guardrails::redaction::placeholder;
+use langdb_core::types::guardrails::{Guard, GuardResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}").unwrap());
+static CREDIT_CARD: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[0-9](?:[ -]?[0-9]){12,18}\b").unwrap());
+static IBAN: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b[A-Z]{2}[0-9]{2}(?: ?[A-Z0-9]{4}){2,7}(?: ?[A-Z0-9]{1,4})?\b").unwrap()
+});
+static SSN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b[0-9]{3}-[0-9]{2}-[0-9]{4}\b").unwrap());
+static IPV4: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\b(?:[0-9]{1,3}\.){3}[0-9]{1,3}\b").unwrap());
+static IPV6: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\b(?:[0-9a-f]{0,4}:){2,7}[0-9a-f]{0,4}\b").unwrap());
+static PHONE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:\+[0-9]{1,3}[\s.-]?)?(?:\([0-9]{1,4}\)[\s.-]?|[0-9]{2,4}[\s.-])[0-9]{3,4}[\s.-]?[0-9]{3,4}\b")
+        .unwrap()
+});
+
+/// Built-in entity recognizers, in the order used to resolve overlapping matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiEntity {
+    Email,
+    CreditCard,
+    Iban,
+    Ssn,
+    IpAddress,
+    Phone,
+}
+
+impl PiiEntity {
+    fn all() -> Vec<PiiEntity> {
+        vec![
+            PiiEntity::Email,
+            PiiEntity::CreditCard,
+            PiiEntity::Iban,
+            PiiEntity::Ssn,
+            PiiEntity::IpAddress,
+            PiiEntity::Phone,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            PiiEntity::Email => "email",
+            PiiEntity::CreditCard => "credit_card",
+            PiiEntity::Iban => "iban",
+            PiiEntity::Ssn => "ssn",
+            PiiEntity::IpAddress => "ip_address",
+            PiiEntity::Phone => "phone",
+        }
+    }
+
+    fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        let (regexes, valid): (Vec<&Regex>, fn(&str) -> bool) = match self {
+            PiiEntity::Email => (vec![&*EMAIL], |_| true),
+            PiiEntity::CreditCard => (vec![&*CREDIT_CARD], luhn_valid),
+            PiiEntity::Iban => (vec![&*IBAN], iban_valid),
+            PiiEntity::Ssn => (vec![&*SSN], ssn_valid),
+            PiiEntity::IpAddress => (vec![&*IPV4, &*IPV6], |s| {
+                s.parse::<Ipv4Addr>().is_ok() || s.parse::<Ipv6Addr>().is_ok()
+            }),
+            PiiEntity::Phone => (vec![&*PHONE], |s| {
+                (7..=15).contains(&s.chars().filter(|c| c.is_ascii_digit()).count())
+            }),
+        };
+
+        regexes
+            .into_iter()
+            .flat_map(|regex| regex.find_iter(text))
+            .filter(|m| valid(m.as_str()))
+            .map(|m| (m.start(), m.end()))
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomPattern {
+    pub name: String,
+    pub pattern: String,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiMode {
+    /// Fail the guard when PII is found
+    #[default]
+    Detect,
+    /// Replace PII with placeholders before the provider call
+    Redact,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiParameters {
+    #[serde(default = "PiiEntity::all")]
+    pub entities: Vec<PiiEntity>,
+    #[serde(default)]
+    pub custom_patterns: Vec<CustomPattern>,
+    #[serde(default)]
+    pub mode: PiiMode,
+    /// Put the original values back into the model output
+    #[serde(default)]
+    pub restore_output: bool,
+}
+
+/// Replaces PII with placeholders, giving repeated values the same placeholder.
+pub struct PiiRedactor {
+    recognizers: Vec<(String, Recognizer)>,
+    placeholders: HashMap<(String, String), String>,
+    counters: HashMap<String, usize>,
+}
+
+enum Recognizer {
+    BuiltIn(PiiEntity),
+    Custom(Regex),
+}
+
+impl PiiRedactor {
+    pub fn new(parameters: &PiiParameters) -> Result<Self, String> {
+        let mut recognizers = vec![];
+        for pattern in &parameters.custom_patterns {
+            let regex = Regex::new(&pattern.pattern)
+                .map_err(|e| format!("Invalid pattern for {}: {e}", pattern.name))?;
+            recognizers.push((pattern.name.to_lowercase(), Recognizer::Custom(regex)));
+        }
+        for entity in &parameters.entities {
+            recognizers.push((entity.name().to_string(), Recognizer::BuiltIn(*entity)));
+        }
+
+        Ok(Self {
+            recognizers,
+            placeholders: HashMap::new(),
+            counters: HashMap::new(),
+        })
+    }
+
+    /// Returns the non-overlapping entities found in the text as `(start, end, entity)`.
+    pub fn find(&self, text: &str) -> Vec<(usize, usize, String)> {
+        let mut matches: Vec<(usize, usize, usize)> = self
+            .recognizers
+            .iter()
+            .enumerate()
+            .flat_map(|(priority, (_, recognizer))| {
+                let found = match recognizer {
+                    Recognizer::BuiltIn(entity) => entity.find(text),
+                    Recognizer::Custom(regex) => regex
+                        .find_iter(text)
+                        .map(|m| (m.start(), m.end()))
+                        .collect(),
+                };
+                found
+                    .into_iter()
+                    .map(move |(start, end)| (start, end, priority))
+            })
+            .collect();
+        matches.sort_by_key(|(start, end, priority)| (*start, *priority, usize::MAX - end));
+
+        let mut result: Vec<(usize, usize, String)> = vec![];
+        for (start, end, priority) in matches {
+            if result
+                .last()
+                .is_some_and(|(_, last_end, _)| start < *last_end)
+            {
+                continue;
+            }
+            result.push((start, end, self.recognizers[priority].0.clone()));
+        }
+        result
+    }
+
+    pub fn redact(&mut self, text: &str) -> String {
+        let mut redacted = String::with_capacity(text.len());
+        let mut position = 0;
+        for (start, end, entity) in self.find(text) {
+            redacted.push_str(&text[position..start]);
+            let value = text[start..end].to_string();
+            let placeholder = match self.placeholders.get(&(entity.clone(), value.clone())) {
+                Some(placeholder) => placeholder.clone(),
+                None => {
+                    let counter = self.counters.entry(entity.clone()).or_default();
+                    *counter += 1;
+                    let new_placeholder = placeholder(&entity, *counter);
+                    self.placeholders
+                        .insert((entity, value), new_placeholder.clone());
+                    new_placeholder
+                }
+            };
+            redacted.push_str(&placeholder);
+            position = end;
+        }
+        redacted.push_str(&text[position..]);
+        redacted
+    }
+
+    /// Placeholders mapped to the values they replaced.
+    pub fn replacements(&self) -> HashMap<String, String> {
+        self.placeholders
+            .iter()
+            .map(|((_, value), placeholder)| (placeholder.clone(), value.clone()))
+            .collect()
+    }
+
+    pub fn entity_counts(&self) -> BTreeMap<String, usize> {
+        self.counters
+            .iter()
+            .map(|(entity, count)| (entity.clone(), *count))
+            .collect()
+    }
+}
+
+/// Deterministic PII guard running local recognizers
+pub struct PiiEvaluator;
+
+#[async_trait::async_trait]
+impl Evaluator for PiiEvaluator {
+    async fn evaluate(
+        &self,
+        messages: &[ChatCompletionMessage],
+        guard: &Guard,
+    ) -> Result<GuardResult, String> {
+        let Guard::Pii { config } = guard else {
+            return Err("Invalid guard type for PiiEvaluator".to_string());
+        };
+
+        let parameters: PiiParameters = serde_json::from_value(
+            config
+                .user_defined_parameters
+                .clone()
+                .unwrap_or(serde_json::json!({})),
+        )
+        .map_err(|e| format!("Invalid PII guard parameters: {e}"))?;
+        let mut redactor = PiiRedactor::new(&parameters)?;
+
+        let messages: Vec<ChatCompletionMessage> = messages
+            .iter()
+            .map(|message| {
+                let mut message = message.clone();
+                match message.content.as_mut() {
+                    Some(ChatCompletionContent::Text(text)) => *text = redactor.redact(text),
+                    Some(ChatCompletionContent::Content(parts)) => {
+                        for part in parts {
+                            if let Some(text) = part.text.as_mut() {
+                                *text = redactor.redact(text);
+                            }
+                        }
+                    }
+                    None => {}
+                }
+                message
+            })
+            .collect();
+
+        let entities = redactor.entity_counts();
+        match parameters.mode {
+            PiiMode::Detect => Ok(GuardResult::Text {
+                text: match entities.is_empty() {
+                    true => "No PII detected".to_string(),
+                    false => format!(
+                        "Detected PII: {}",
+                        entities
+                            .iter()
+                            .map(|(entity, count)| format!("{entity} ({count})"))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    ),
+                },
+                passed: entities.is_empty(),
+                confidence: Some(1.0),
+            }),
+            PiiMode::Redact => Ok(GuardResult::Redacted {
+                messages,
+                entities,
+                passed: true,
+                replacements: match parameters.restore_output {
+                    true => redactor.replacements(),
+                    false => HashMap::new(),
+                },
+            }),
+        }
+    }
+}
+
+fn luhn_valid(candidate: &str) -> bool {
+    let digits: Vec<u32> = candidate.chars().filter_map(|c| c.to_digit(10)).collect();
+    if !(13..=19).contains(&digits.len()) {
+        return false;
+    }
+
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, d)| match i % 2 {
+            1 if d * 2 > 9 => d * 2 - 9,
+            1 => d * 2,
+            _ => *d,
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+fn iban_valid(candidate: &str) -> bool {
+    let iban: Vec<char> = candidate.chars().filter(|c| !c.is_whitespace()).collect();
+    if !(15..=34).contains(&iban.len()) || !iban.iter().all(char::is_ascii_alphanumeric) {
+        return false;
+    }
+
+    // Move the country code and check digits to the end, letters become 10..35
+    let rearranged = iban[4..].iter().chain(&iban[..4]);
+    let mut remainder = 0u32;
+    for c in rearranged {
+        let Some(value) = c.to_digit(36) else {
+            return false;
+        };
+        for digit in value.to_string().chars() {
+            remainder = (remainder * 10 + digit.to_digit(10).unwrap_or(0)) % 97;
+        }
+    }
+    remainder == 1
+}
+
+fn ssn_valid(candidate: &str) -> bool {
+    let mut parts = candidate.split('-');
+    let (Some(area), Some(group), Some(serial)) = (parts.next(), parts.next(), parts.next()) else {
+        return false;
+    };
+    area != "000" && area != "666" && !area.starts_with('9') && group != "00" && serial != "0000"
+}
@@ -1,5 +1,5 @@
 use langdb_core::types::gateway::ChatCompletionMessage;
-use langdb_core::types::guardrails::{evaluator::Evaluator, Guard, GuardResult};
+use langdb_core::types::guardrails::{evaluator::Evaluator, Guard, GuardAction, GuardResult};
 use regex::Regex;
 pub struct RegexEvaluator;
 
@@ -38,6 +38,25 @@ impl Evaluator for RegexEvaluator {
 
             let compiled_patterns = compiled_patterns?;
 
+            // Forbidden patterns are masked instead of failing the guard
+            if guard.action() == &GuardAction::Transform && match_type == "none" {
+                let replacement = parameters
+                    .get("replacement")
+                    .and_then(|r| r.as_str())
+                    .unwrap_or("[REDACTED]");
+                let masked = compiled_patterns.iter().fold(text.clone(), |text, regex| {
+                    regex
+                        .replace_all(&text, regex::NoExpand(replacement))
+                        .into_owned()
+                });
+                if masked != text {
+                    return Ok(GuardResult::Transformed {
+                        messages: self.with_last_text(messages, masked),
+                        passed: true,
+                    });
+                }
+            }
+
             // Check pattern matches based on match_type
             let (passed, result_text) = match match_type {
                 "all" => {
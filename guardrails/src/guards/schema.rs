@@ -1,6 +1,6 @@
 use jsonschema::{Draft, Validator};
 use langdb_core::types::gateway::ChatCompletionMessage;
-use langdb_core::types::guardrails::{evaluator::Evaluator, Guard, GuardResult};
+use langdb_core::types::guardrails::{evaluator::Evaluator, Guard, GuardAction, GuardResult};
 use serde_json::Value;
 
 pub struct SchemaEvaluator;
@@ -18,8 +18,12 @@ impl Evaluator for SchemaEvaluator {
             ..
         } = &guard
         {
-            // Try to parse the text as JSON
-            let json_result = serde_json::from_str::<Value>(&text);
+            let transform = guard.action() == &GuardAction::Transform;
+            // Try to parse the text as JSON, accepting fenced code blocks when transforming
+            let json_result = match transform {
+                true => serde_json::from_str::<Value>(strip_code_fence(&text)),
+                false => serde_json::from_str::<Value>(&text),
+            };
 
             match json_result {
                 Ok(json_value) => {
@@ -37,7 +41,13 @@ impl Evaluator for SchemaEvaluator {
                     let json_value_clone = json_value.clone();
                     // Validate against the schema
                     let validation_result = compiled_schema.validate(&json_value_clone);
+                    // Compact serialization of the value, replacing the text on transform
+                    let compact = json_value.to_string();
                     match validation_result {
+                        Ok(_) if transform && compact != text => Ok(GuardResult::Transformed {
+                            messages: self.with_last_text(messages, compact),
+                            passed: true,
+                        }),
                         Ok(_) => Ok(GuardResult::Json {
                             schema: json_value,
                             passed: true,
@@ -67,3 +77,16 @@ impl Evaluator for SchemaEvaluator {
         }
     }
 }
+
+/// Returns the body of a markdown code block wrapping the whole text, or the trimmed text.
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    trimmed
+        .strip_prefix("```")
+        .and_then(|rest| rest.strip_suffix("```"))
+        .map(|body| {
+            // Drop the language tag on the opening line
+            body.split_once('\n').map_or(body, |(_, code)| code).trim()
+        })
+        .unwrap_or(trimmed)
+}
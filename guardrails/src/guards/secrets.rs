@@ -0,0 +1,326 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::LazyLock;
+
+use langdb_core::types::gateway::{ChatCompletionContent, ChatCompletionMessage};
+use langdb_core::types::guardrails::evaluator::Evaluator;
+use langdb_core::types::guardrails::{Guard, GuardAction, GuardResult};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+/// Curated secret detectors, in the order used to resolve overlapping matches.
+static RULES: LazyLock<Vec<(SecretRule, Regex)>> = LazyLock::new(|| {
+    [
+        (
+            SecretRule::PrivateKey,
+            r"-----BEGIN (?:[A-Z0-9]+ )*PRIVATE KEY(?: BLOCK)?-----[\s\S]*?-----END (?:[A-Z0-9]+ )*PRIVATE KEY(?: BLOCK)?-----",
+        ),
+        (
+            SecretRule::AwsAccessKey,
+            r"\b(?:AKIA|ASIA|ABIA|ACCA|AGPA|AIDA|AIPA|ANPA|ANVA|AROA|APKA)[A-Z0-9]{16}\b",
+        ),
+        (
+            SecretRule::AwsSecretKey,
+            r#"(?i)aws.{0,20}?(?:secret|private).{0,20}?['"=:\s]+([A-Za-z0-9/+=]{40})\b"#,
+        ),
+        (
+            SecretRule::GithubToken,
+            r"\b(?:gh[pousr]_[A-Za-z0-9]{36,255}|github_pat_[A-Za-z0-9_]{22,255})\b",
+        ),
+        (
+            SecretRule::SlackToken,
+            r"\bxox[abposr]-[A-Za-z0-9-]{10,}\b",
+        ),
+        (
+            SecretRule::StripeKey,
+            r"\b(?:sk|rk)_(?:live|test)_[A-Za-z0-9]{20,}\b",
+        ),
+        (SecretRule::GoogleApiKey, r"\bAIza[0-9A-Za-z_-]{35}\b"),
+        (
+            SecretRule::LlmApiKey,
+            r"\bsk-(?:ant-|proj-|or-)?[A-Za-z0-9_-]{20,}\b",
+        ),
+        (
+            SecretRule::Jwt,
+            r"\beyJ[A-Za-z0-9_-]{8,}\.eyJ[A-Za-z0-9_-]{8,}\.[A-Za-z0-9_-]{8,}\b",
+        ),
+        (
+            SecretRule::ConnectionString,
+            r"\b[a-z][a-z0-9+.-]{1,20}://[^\s:/@]+:[^\s@/]+@[^\s/?#]+",
+        ),
+    ]
+    .into_iter()
+    .map(|(rule, pattern)| (rule, Regex::new(pattern).unwrap()))
+    .collect()
+});
+
+static CANDIDATE_TOKEN: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[A-Za-z0-9+/=_-]+").unwrap());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretRule {
+    PrivateKey,
+    AwsAccessKey,
+    AwsSecretKey,
+    GithubToken,
+    SlackToken,
+    StripeKey,
+    GoogleApiKey,
+    LlmApiKey,
+    Jwt,
+    ConnectionString,
+    /// Random looking tokens that no other rule recognized
+    HighEntropy,
+}
+
+impl SecretRule {
+    fn all() -> Vec<SecretRule> {
+        vec![
+            SecretRule::PrivateKey,
+            SecretRule::AwsAccessKey,
+            SecretRule::AwsSecretKey,
+            SecretRule::GithubToken,
+            SecretRule::SlackToken,
+            SecretRule::StripeKey,
+            SecretRule::GoogleApiKey,
+            SecretRule::LlmApiKey,
+            SecretRule::Jwt,
+            SecretRule::ConnectionString,
+            SecretRule::HighEntropy,
+        ]
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SecretRule::PrivateKey => "private_key",
+            SecretRule::AwsAccessKey => "aws_access_key",
+            SecretRule::AwsSecretKey => "aws_secret_key",
+            SecretRule::GithubToken => "github_token",
+            SecretRule::SlackToken => "slack_token",
+            SecretRule::StripeKey => "stripe_key",
+            SecretRule::GoogleApiKey => "google_api_key",
+            SecretRule::LlmApiKey => "llm_api_key",
+            SecretRule::Jwt => "jwt",
+            SecretRule::ConnectionString => "connection_string",
+            SecretRule::HighEntropy => "high_entropy",
+        }
+    }
+}
+
+fn default_entropy_threshold() -> f64 {
+    4.0
+}
+
+fn default_min_entropy_length() -> usize {
+    24
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecretsParameters {
+    #[serde(default = "SecretRule::all")]
+    pub rules: Vec<SecretRule>,
+    /// Minimum Shannon entropy, in bits per character, of high entropy tokens
+    #[serde(default = "default_entropy_threshold")]
+    pub entropy_threshold: f64,
+    #[serde(default = "default_min_entropy_length")]
+    pub min_entropy_length: usize,
+    /// Patterns of values that are never reported, e.g. documented example keys
+    #[serde(default)]
+    pub allowlist: Vec<String>,
+}
+
+impl Default for SecretsParameters {
+    fn default() -> Self {
+        Self {
+            rules: SecretRule::all(),
+            entropy_threshold: default_entropy_threshold(),
+            min_entropy_length: default_min_entropy_length(),
+            allowlist: vec![],
+        }
+    }
+}
+
+pub struct SecretScanner {
+    parameters: SecretsParameters,
+    allowlist: Vec<Regex>,
+}
+
+impl SecretScanner {
+    pub fn new(parameters: SecretsParameters) -> Result<Self, String> {
+        let allowlist = parameters
+            .allowlist
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| format!("Invalid allowlist pattern: {e}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            parameters,
+            allowlist,
+        })
+    }
+
+    /// Returns the non-overlapping secrets found in the text as `(start, end, rule)`.
+    pub fn find(&self, text: &str) -> Vec<(usize, usize, SecretRule)> {
+        let mut matches: Vec<(usize, usize, usize, SecretRule)> = RULES
+            .iter()
+            .enumerate()
+            .filter(|(_, (rule, _))| self.parameters.rules.contains(rule))
+            .flat_map(|(priority, (rule, regex))| {
+                regex.captures_iter(text).map(move |captures| {
+                    // Rules with a capture group only report the secret itself
+                    let m = captures.get(1).or(captures.get(0)).unwrap();
+                    (m.start(), m.end(), priority, *rule)
+                })
+            })
+            .collect();
+
+        if self.parameters.rules.contains(&SecretRule::HighEntropy) {
+            matches.extend(
+                CANDIDATE_TOKEN
+                    .find_iter(text)
+                    .filter(|m| self.is_high_entropy(m.as_str()))
+                    .map(|m| (m.start(), m.end(), RULES.len(), SecretRule::HighEntropy)),
+            );
+        }
+        matches.sort_by_key(|(start, end, priority, _)| (*start, *priority, usize::MAX - end));
+
+        let mut result: Vec<(usize, usize, SecretRule)> = vec![];
+        for (start, end, _, rule) in matches {
+            if result
+                .last()
+                .is_some_and(|(_, last_end, _)| start < *last_end)
+            {
+                continue;
+            }
+            if self
+                .allowlist
+                .iter()
+                .any(|regex| regex.is_match(&text[start..end]))
+            {
+                continue;
+            }
+            result.push((start, end, rule));
+        }
+        result
+    }
+
+    fn is_high_entropy(&self, token: &str) -> bool {
+        token.len() >= self.parameters.min_entropy_length
+            && token.chars().any(|c| c.is_ascii_digit())
+            && token.chars().any(|c| c.is_ascii_alphabetic())
+            && shannon_entropy(token) >= self.parameters.entropy_threshold
+    }
+
+    /// Replaces secrets with `[REDACTED:<rule>]`, counting them per rule.
+    pub fn mask(&self, text: &str, counts: &mut BTreeMap<String, usize>) -> String {
+        let mut masked = String::with_capacity(text.len());
+        let mut position = 0;
+        for (start, end, rule) in self.find(text) {
+            masked.push_str(&text[position..start]);
+            masked.push_str(&format!("[REDACTED:{}]", rule.name()));
+            *counts.entry(rule.name().to_string()).or_default() += 1;
+            position = end;
+        }
+        masked.push_str(&text[position..]);
+        masked
+    }
+
+    /// Masks message content, tool call arguments and tool results.
+    pub fn mask_message(
+        &self,
+        message: &ChatCompletionMessage,
+        counts: &mut BTreeMap<String, usize>,
+    ) -> ChatCompletionMessage {
+        let mut message = message.clone();
+        match message.content.as_mut() {
+            Some(ChatCompletionContent::Text(text)) => *text = self.mask(text, counts),
+            Some(ChatCompletionContent::Content(parts)) => {
+                for part in parts {
+                    if let Some(text) = part.text.as_mut() {
+                        *text = self.mask(text, counts);
+                    }
+                }
+            }
+            None => {}
+        }
+        for tool_call in message.tool_calls.iter_mut().flatten() {
+            tool_call.function.arguments = self.mask(&tool_call.function.arguments, counts);
+        }
+        message
+    }
+}
+
+fn shannon_entropy(token: &str) -> f64 {
+    let mut frequencies: HashMap<char, usize> = HashMap::new();
+    for c in token.chars() {
+        *frequencies.entry(c).or_default() += 1;
+    }
+
+    let length = token.chars().count() as f64;
+    frequencies
+        .values()
+        .map(|count| {
+            let p = *count as f64 / length;
+            -p * p.log2()
+        })
+        .sum()
+}
+
+/// Detects credentials in messages, masking them when the guard action is transform
+pub struct SecretsEvaluator;
+
+#[async_trait::async_trait]
+impl Evaluator for SecretsEvaluator {
+    async fn evaluate(
+        &self,
+        messages: &[ChatCompletionMessage],
+        guard: &Guard,
+    ) -> Result<GuardResult, String> {
+        let Guard::Secrets { config } = guard else {
+            return Err("Invalid guard type for SecretsEvaluator".to_string());
+        };
+
+        let parameters: SecretsParameters = serde_json::from_value(
+            config
+                .user_defined_parameters
+                .clone()
+                .unwrap_or(serde_json::json!({})),
+        )
+        .map_err(|e| format!("Invalid secrets guard parameters: {e}"))?;
+        let scanner = SecretScanner::new(parameters)?;
+
+        let mut counts = BTreeMap::new();
+        let masked: Vec<ChatCompletionMessage> = messages
+            .iter()
+            .map(|message| scanner.mask_message(message, &mut counts))
+            .collect();
+
+        // Reported as redacted rather than transformed so the secrets never reach trace diffs
+        if guard.action() == &GuardAction::Transform && !counts.is_empty() {
+            return Ok(GuardResult::Redacted {
+                messages: masked,
+                entities: counts,
+                passed: true,
+                replacements: HashMap::new(),
+            });
+        }
+
+        Ok(GuardResult::Text {
+            text: match counts.is_empty() {
+                true => "No secrets detected".to_string(),
+                false => format!(
+                    "Detected secrets: {}",
+                    counts
+                        .iter()
+                        .map(|(rule, count)| format!("{rule} ({count})"))
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            },
+            passed: counts.is_empty(),
+            confidence: Some(1.0),
+        })
+    }
+}
@@ -1,6 +1,6 @@
 use langdb_core::events::JsonValue;
 use langdb_core::events::SPAN_GUARD_EVAULATION;
-use langdb_core::types::gateway::ChatCompletionMessage;
+use langdb_core::types::gateway::{ChatCompletionContent, ChatCompletionMessage};
 use langdb_core::types::guardrails::evaluator::Evaluator;
 use langdb_core::types::guardrails::Guard;
 use langdb_core::types::guardrails::GuardResult;
@@ -40,6 +40,7 @@ impl Evaluator for TracedGuard {
             result_metadata = field::Empty,
             r#type = guard.r#type(),
             partner = field::Empty,
+            diff = field::Empty,
             error = field::Empty
         );
 
@@ -54,6 +55,15 @@ impl Evaluator for TracedGuard {
                 let result_value =
                     serde_json::to_value(result.clone()).map_err(|e| e.to_string())?;
                 span.record("result", JsonValue(&result_value).as_value());
+                // Redacted values are kept out of traces, only transforms are diffed
+                if let GuardResult::Transformed {
+                    messages: transformed,
+                    ..
+                } = &result
+                {
+                    let diff = message_diff(messages, transformed);
+                    span.record("diff", JsonValue(&diff).as_value());
+                }
                 Ok(result)
             }
             Err(e) => {
@@ -63,3 +73,36 @@ impl Evaluator for TracedGuard {
         }
     }
 }
+
+/// Changed messages as `{index, role, before, after}` entries, `null` for added or removed ones.
+pub fn message_diff(
+    before: &[ChatCompletionMessage],
+    after: &[ChatCompletionMessage],
+) -> serde_json::Value {
+    let changes: Vec<serde_json::Value> = (0..before.len().max(after.len()))
+        .filter(|i| before.get(*i) != after.get(*i))
+        .map(|i| {
+            let (old, new) = (before.get(i), after.get(i));
+            serde_json::json!({
+                "index": i,
+                "role": new.or(old).map(|m| m.role.clone()),
+                "before": old.map(message_text),
+                "after": new.map(message_text),
+            })
+        })
+        .collect();
+
+    serde_json::Value::Array(changes)
+}
+
+fn message_text(message: &ChatCompletionMessage) -> String {
+    match &message.content {
+        Some(ChatCompletionContent::Text(text)) => text.clone(),
+        Some(ChatCompletionContent::Content(parts)) => parts
+            .iter()
+            .filter_map(|part| part.text.clone())
+            .collect::<Vec<_>>()
+            .join("\n"),
+        None => String::new(),
+    }
+}
@@ -1,6 +1,6 @@
 use langdb_core::types::gateway::{ChatCompletionContent, ChatCompletionMessage};
 use langdb_core::types::guardrails::evaluator::Evaluator;
-use langdb_core::types::guardrails::{Guard, GuardResult};
+use langdb_core::types::guardrails::{Guard, GuardAction, GuardResult};
 use regex::Regex;
 
 /// Word count evaluator that checks if text meets specified word count limits
@@ -43,10 +43,26 @@ impl Evaluator for WordCountEvaluator {
                 .unwrap_or(500);
             let count_method = parameters["count_method"].as_str().unwrap_or("split");
 
-            let word_count = match count_method {
-                "regex" => count_words_regex(text),
-                _ => count_words_split(text),
+            let word_pattern = match count_method {
+                "regex" => Regex::new(r"\b\w+\b").unwrap(),
+                _ => Regex::new(r"\S+").unwrap(),
             };
+            let word_count = word_pattern.find_iter(text).count();
+
+            // Cut overly long text at the end of the last allowed word
+            if guard.action() == &GuardAction::Transform && word_count > max_words {
+                let end = match max_words {
+                    0 => 0,
+                    n => word_pattern
+                        .find_iter(text)
+                        .nth(n - 1)
+                        .map_or(0, |m| m.end()),
+                };
+                return Ok(GuardResult::Transformed {
+                    messages: self.with_last_text(messages, text[..end].to_string()),
+                    passed: max_words >= min_words,
+                });
+            }
 
             let passed = word_count >= min_words && word_count <= max_words;
 
@@ -59,16 +75,3 @@ impl Evaluator for WordCountEvaluator {
         }
     }
 }
-
-/// Count words using simple whitespace splitting
-fn count_words_split(text: &str) -> usize {
-    text.split_whitespace().count()
-}
-
-/// Count words using regex pattern matching
-fn count_words_regex(text: &str) -> usize {
-    // This regex matches word characters (including Unicode letters)
-    // separated by word boundaries
-    let word_pattern = Regex::new(r"\b\w+\b").unwrap();
-    word_pattern.find_iter(text).count()
-}
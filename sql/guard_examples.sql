@@ -0,0 +1,11 @@
+CREATE TABLE IF NOT EXISTS langdb.guard_examples
+(
+    dataset     LowCardinality(String),
+    id          String,
+    text        String,
+    label       Bool,
+    embedding   Array(Float32),
+    created_at  DateTime64(6) DEFAULT now64(6)
+)
+ENGINE = MergeTree
+ORDER BY (dataset, created_at, id);